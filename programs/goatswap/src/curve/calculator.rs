@@ -1,6 +1,7 @@
 //! Swap calculations
 
-use crate::curve::{constant_product::ConstantProductCurve, fees::Fees};
+use crate::curve::fees::Fees;
+use crate::utils::{U128, U256};
 use anchor_lang::prelude::*;
 use {crate::error::ErrorCode, std::fmt::Debug};
 
@@ -45,6 +46,94 @@ impl TradeDirection {
     }
 }
 
+/// A curve a pool can trade on. Fees and taxes are taken around the curve by
+/// `CurveCalculator` and the instructions, so implementations only deal with
+/// the raw pool amounts.
+pub trait SwapCurve: Debug {
+    /// Calculates how much destination token will be provided given an amount
+    /// of source token, rounding in favour of the pool
+    fn swap_base_input_without_fees(
+        &self,
+        source_amount: u128,
+        swap_source_amount: u128,
+        swap_destination_amount: u128,
+        trade_direction: TradeDirection,
+    ) -> Option<u128>;
+
+    /// Calculates how much source token is needed to take an amount of
+    /// destination token out of the pool, rounding in favour of the pool
+    fn swap_base_output_without_fees(
+        &self,
+        destination_amount: u128,
+        swap_source_amount: u128,
+        swap_destination_amount: u128,
+        trade_direction: TradeDirection,
+    ) -> Option<u128>;
+
+    /// Get the amount of trading tokens for the given amount of pool tokens,
+    /// provided the total trading tokens and supply of pool tokens.
+    fn lp_tokens_to_trading_tokens(
+        &self,
+        lp_token_amount: u128,
+        lp_token_supply: u128,
+        swap_token_0_amount: u128,
+        swap_token_1_amount: u128,
+        round_direction: RoundDirection,
+    ) -> Option<TradingTokenResult>;
+
+    /// Checks that moving the pool from the old amounts to the new amounts did
+    /// not decrease the invariant of the curve
+    fn check_invariant(
+        &self,
+        swap_source_amount: u128,
+        swap_destination_amount: u128,
+        new_swap_source_amount: u128,
+        new_swap_destination_amount: u128,
+        trade_direction: TradeDirection,
+    ) -> bool;
+
+    /// Marginal price of token_0 in token_1 at the given amounts, as a
+    /// numerator and denominator
+    fn spot_price(&self, token_0_amount: u128, token_1_amount: u128) -> Option<(U256, U256)> {
+        Some((U256::from(token_1_amount), U256::from(token_0_amount)))
+    }
+
+    /// Amount of lp tokens minted when creating the pool with the given amounts
+    fn initial_lp_tokens(&self, token_0_amount: u128, token_1_amount: u128) -> Option<u128> {
+        Some(
+            U128::from(token_0_amount)
+                .checked_mul(token_1_amount.into())?
+                .integer_sqrt()
+                .as_u128(),
+        )
+    }
+}
+
+/// Spot prices of token_0 in token_1 and of token_1 in token_0 as Q32.32,
+/// saturating at u128::MAX
+pub fn spot_price_x32(
+    curve: &dyn SwapCurve,
+    token_0_amount: u128,
+    token_1_amount: u128,
+) -> Option<(u128, u128)> {
+    let (numerator, denominator) = curve.spot_price(token_0_amount, token_1_amount)?;
+    if numerator.is_zero() || denominator.is_zero() {
+        return None;
+    }
+    let price_x32 = |numerator: U256, denominator: U256| {
+        let price = (numerator << 32) / denominator;
+        if price > U256::from(u128::MAX) {
+            u128::MAX
+        } else {
+            price.as_u128()
+        }
+    };
+    Some((
+        price_x32(numerator, denominator),
+        price_x32(denominator, numerator),
+    ))
+}
+
 /// Encodes results of depositing both sides at once
 #[derive(Debug, PartialEq)]
 pub struct TradingTokenResult {
@@ -73,6 +162,29 @@ pub struct SwapResult {
     pub fund_fee: u128,
     /// Amount of source tokens going to lp
     pub lp_fee: u128,
+    /// Amount of source tokens discounted from the trade fee
+    pub fee_discount: u128,
+}
+
+/// Take the discount on the trade fee out of the protocol share, then the fund
+/// share, so the lp share of the trade fee is never discounted. Returns the
+/// discount and the discounted trade, protocol and fund fees
+fn discount_fees(
+    trade_fee: u128,
+    protocol_fee: u128,
+    fund_fee: u128,
+    fee_discount_rate: u64,
+) -> Option<(u128, u128, u128, u128)> {
+    let fee_discount =
+        Fees::fee_discount(trade_fee, fee_discount_rate)?.min(protocol_fee.checked_add(fund_fee)?);
+    let protocol_discount = fee_discount.min(protocol_fee);
+    let fund_discount = fee_discount.checked_sub(protocol_discount)?;
+    Some((
+        fee_discount,
+        trade_fee.checked_sub(fee_discount)?,
+        protocol_fee.checked_sub(protocol_discount)?,
+        fund_fee.checked_sub(fund_discount)?,
+    ))
 }
 
 /// Concrete struct to wrap around the trait object which performs calculation.
@@ -93,13 +205,16 @@ impl CurveCalculator {
     /// Subtract fees and calculate how much destination token will be provided
     /// given an amount of source token.
     pub fn swap_base_input(
+        curve: &dyn SwapCurve,
         source_amount: u128,
         swap_source_amount: u128,
         swap_destination_amount: u128,
         trade_fee_rate: u64,
         protocol_fee_rate: u64,
         fund_fee_rate: u64,
+        fee_discount_rate: u64,
         lp_fee_rate: u64,
+        trade_direction: TradeDirection,
     ) -> Option<SwapResult> {
         let trade_fee_with_lp_fee_rate = trade_fee_rate.checked_add(lp_fee_rate).unwrap();
         // debit the fee to calculate the amount swapped
@@ -110,13 +225,18 @@ impl CurveCalculator {
 
         let lp_fee = trade_fee_with_lp_fee.checked_sub(trade_fee)?;
 
+        let (fee_discount, trade_fee, protocol_fee, fund_fee) =
+            discount_fees(trade_fee, protocol_fee, fund_fee, fee_discount_rate)?;
+        let trade_fee_with_lp_fee = trade_fee_with_lp_fee.checked_sub(fee_discount)?;
+
         let source_amount_less_fees = source_amount.checked_sub(trade_fee_with_lp_fee)?;
 
-        let destination_amount_swapped = ConstantProductCurve::swap_base_input_without_fees(
+        let destination_amount_swapped = curve.swap_base_input_without_fees(
             source_amount_less_fees,
             swap_source_amount,
             swap_destination_amount,
-        );
+            trade_direction,
+        )?;
 
         Some(SwapResult {
             new_swap_source_amount: swap_source_amount.checked_add(source_amount)?,
@@ -128,23 +248,28 @@ impl CurveCalculator {
             protocol_fee,
             fund_fee,
             lp_fee,
+            fee_discount,
         })
     }
 
     pub fn swap_base_output(
+        curve: &dyn SwapCurve,
         destinsation_amount: u128,
         swap_source_amount: u128,
         swap_destination_amount: u128,
         trade_fee_rate: u64,
         protocol_fee_rate: u64,
         fund_fee_rate: u64,
+        fee_discount_rate: u64,
         lp_fee_rate: u64,
+        trade_direction: TradeDirection,
     ) -> Option<SwapResult> {
-        let source_amount_swapped = ConstantProductCurve::swap_base_output_without_fees(
+        let source_amount_swapped = curve.swap_base_output_without_fees(
             destinsation_amount,
             swap_source_amount,
             swap_destination_amount,
-        );
+            trade_direction,
+        )?;
 
         let trade_fee_with_lp_fee_rate = trade_fee_rate.checked_add(lp_fee_rate).unwrap();
         let source_amount =
@@ -158,6 +283,11 @@ impl CurveCalculator {
 
         let lp_fee = trade_fee_with_lp_fee.checked_sub(trade_fee)?;
 
+        // the discount is taken off what the swapper pays on top of the amount swapped
+        let (fee_discount, trade_fee, protocol_fee, fund_fee) =
+            discount_fees(trade_fee, protocol_fee, fund_fee, fee_discount_rate)?;
+        let source_amount = source_amount.checked_sub(fee_discount)?;
+
         Some(SwapResult {
             new_swap_source_amount: swap_source_amount.checked_add(source_amount)?,
             new_swap_destination_amount: swap_destination_amount
@@ -168,19 +298,21 @@ impl CurveCalculator {
             protocol_fee,
             fund_fee,
             lp_fee,
+            fee_discount,
         })
     }
 
     /// Get the amount of trading tokens for the given amount of pool tokens,
     /// provided the total trading tokens and supply of pool tokens.
     pub fn lp_tokens_to_trading_tokens(
+        curve: &dyn SwapCurve,
         lp_token_amount: u128,
         lp_token_supply: u128,
         swap_token_0_amount: u128,
         swap_token_1_amount: u128,
         round_direction: RoundDirection,
     ) -> Option<TradingTokenResult> {
-        ConstantProductCurve::lp_tokens_to_trading_tokens(
+        curve.lp_tokens_to_trading_tokens(
             lp_token_amount,
             lp_token_supply,
             swap_token_0_amount,
@@ -194,8 +326,8 @@ impl CurveCalculator {
 #[cfg(test)]
 pub mod test {
     use {
-        super::*, proptest::prelude::*, spl_math::precise_number::PreciseNumber,
-        spl_math::uint::U256,
+        super::*, crate::curve::constant_product::ConstantProductCurve, proptest::prelude::*,
+        spl_math::precise_number::PreciseNumber, spl_math::uint::U256,
     };
 
     /// The epsilon for most curves when performing the conversion test,
@@ -233,12 +365,11 @@ pub mod test {
         swap_destination_amount: u128,
         trade_direction: TradeDirection,
     ) {
-        let results = ConstantProductCurve::swap_base_input_without_fees(
+        let destination_amount_swapped = ConstantProductCurve::swap_base_input_without_fees(
             source_token_amount,
             swap_source_amount,
             swap_destination_amount,
-        )
-        .unwrap();
+        );
 
         let (swap_token_0_amount, swap_token_1_amount) = match trade_direction {
             TradeDirection::ZeroForOne => (swap_source_amount, swap_destination_amount),
@@ -248,11 +379,9 @@ pub mod test {
             .checked_mul(swap_token_1_amount)
             .unwrap();
 
-        let new_swap_source_amount = swap_source_amount
-            .checked_add(results.source_amount_swapped)
-            .unwrap();
+        let new_swap_source_amount = swap_source_amount.checked_add(source_token_amount).unwrap();
         let new_swap_destination_amount = swap_destination_amount
-            .checked_sub(results.destination_amount_swapped)
+            .checked_sub(destination_amount_swapped)
             .unwrap();
         let (swap_token_0_amount, swap_token_1_amount) = match trade_direction {
             TradeDirection::ZeroForOne => (new_swap_source_amount, new_swap_destination_amount),
@@ -272,12 +401,14 @@ pub mod test {
     /// truncation at some point, meaning a potential for value to be lost if
     /// too much is given to the depositor.
     pub fn check_pool_value_from_deposit(
+        curve: &dyn SwapCurve,
         lp_token_amount: u128,
         lp_token_supply: u128,
         swap_token_0_amount: u128,
         swap_token_1_amount: u128,
     ) {
         let deposit_result = CurveCalculator::lp_tokens_to_trading_tokens(
+            curve,
             lp_token_amount,
             lp_token_supply,
             swap_token_0_amount,
@@ -319,12 +450,14 @@ pub mod test {
     /// truncation at some point, meaning a potential for value to be lost if
     /// too much is given to the depositor.
     pub fn check_pool_value_from_withdraw(
+        curve: &dyn SwapCurve,
         lp_token_amount: u128,
         lp_token_supply: u128,
         swap_token_0_amount: u128,
         swap_token_1_amount: u128,
     ) {
         let withdraw_result = CurveCalculator::lp_tokens_to_trading_tokens(
+            curve,
             lp_token_amount,
             lp_token_supply,
             swap_token_0_amount,
@@ -355,6 +488,76 @@ pub mod test {
             .greater_than_or_equal(&value.checked_mul(&new_lp_token_supply).unwrap()));
     }
 
+    #[test]
+    fn fee_discount_spares_lp_share() {
+        let curve = ConstantProductCurve;
+        let swap = |fee_discount_rate| {
+            CurveCalculator::swap_base_input(
+                &curve,
+                1_000_000,
+                100_000_000,
+                100_000_000,
+                10_000,
+                200_000,
+                100_000,
+                fee_discount_rate,
+                0,
+                TradeDirection::ZeroForOne,
+            )
+            .unwrap()
+        };
+
+        // 10_000 of trade fee: 2_000 protocol, 1_000 fund, 7_000 lp
+        let full = swap(0);
+        assert_eq!(full.trade_fee, 10_000);
+        assert_eq!((full.protocol_fee, full.fund_fee), (2_000, 1_000));
+
+        // the discount comes out of the protocol share first
+        let discounted = swap(100_000);
+        assert_eq!(discounted.fee_discount, 1_000);
+        assert_eq!(discounted.trade_fee, 9_000);
+        assert_eq!(
+            (discounted.protocol_fee, discounted.fund_fee),
+            (1_000, 1_000)
+        );
+        assert!(discounted.destination_amount_swapped > full.destination_amount_swapped);
+
+        // then the fund share, and never the lp share
+        let capped = swap(500_000);
+        assert_eq!(capped.fee_discount, 3_000);
+        assert_eq!((capped.protocol_fee, capped.fund_fee), (0, 0));
+        assert_eq!(capped.trade_fee, 7_000);
+    }
+
+    #[test]
+    fn fee_discount_lowers_swap_base_output_input() {
+        let curve = ConstantProductCurve;
+        let swap = |fee_discount_rate| {
+            CurveCalculator::swap_base_output(
+                &curve,
+                1_000_000,
+                100_000_000,
+                100_000_000,
+                10_000,
+                200_000,
+                100_000,
+                fee_discount_rate,
+                0,
+                TradeDirection::ZeroForOne,
+            )
+            .unwrap()
+        };
+
+        let full = swap(0);
+        let discounted = swap(1_000_000);
+        assert_eq!(
+            discounted.source_amount_swapped + discounted.fee_discount,
+            full.source_amount_swapped
+        );
+        assert_eq!(discounted.fee_discount, full.protocol_fee + full.fund_fee);
+        assert_eq!(discounted.lp_fee, full.lp_fee);
+    }
+
     prop_compose! {
         pub fn total_and_intermediate(max_value: u64)(total in 1..max_value)
                         (intermediate in 1..total, total in Just(total))
@@ -0,0 +1,81 @@
+//! Multiplication and division with a wider intermediate result
+
+use crate::utils::{U256, U512};
+
+fn u256_to_u512(value: U256) -> U512 {
+    let mut words = [0u64; 8];
+    words[..4].copy_from_slice(&value.0);
+    U512(words)
+}
+
+fn u512_to_u256(value: U512) -> Option<U256> {
+    if value.0[4..].iter().any(|word| *word != 0) {
+        return None;
+    }
+    let mut words = [0u64; 4];
+    words.copy_from_slice(&value.0[..4]);
+    Some(U256(words))
+}
+
+/// Calculate floor(a * b / denominator) with a 512 bit intermediate value
+pub fn mul_div_floor(a: U256, b: U256, denominator: U256) -> Option<U256> {
+    if denominator.is_zero() {
+        return None;
+    }
+    let product = u256_to_u512(a).checked_mul(u256_to_u512(b))?;
+    u512_to_u256(product / u256_to_u512(denominator))
+}
+
+/// Calculate ceil(a * b / denominator) with a 512 bit intermediate value
+pub fn mul_div_ceil(a: U256, b: U256, denominator: U256) -> Option<U256> {
+    if denominator.is_zero() {
+        return None;
+    }
+    let product = u256_to_u512(a).checked_mul(u256_to_u512(b))?;
+    let (quotient, remainder) = product.div_mod(u256_to_u512(denominator));
+    let quotient = u512_to_u256(quotient)?;
+    if remainder.is_zero() {
+        Some(quotient)
+    } else {
+        quotient.checked_add(U256::one())
+    }
+}
+
+/// Calculate ceil(a / b)
+pub fn div_ceil(a: U256, b: U256) -> Option<U256> {
+    if b.is_zero() {
+        return None;
+    }
+    let (quotient, remainder) = a.div_mod(b);
+    if remainder.is_zero() {
+        Some(quotient)
+    } else {
+        quotient.checked_add(U256::one())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn mul_div_rounding() {
+        let seven = U256::from(7u64);
+        let two = U256::from(2u64);
+        let three = U256::from(3u64);
+        assert_eq!(mul_div_floor(seven, two, three).unwrap(), U256::from(4u64));
+        assert_eq!(mul_div_ceil(seven, two, three).unwrap(), U256::from(5u64));
+        assert_eq!(mul_div_ceil(three, two, three).unwrap(), two);
+        assert!(mul_div_floor(seven, two, U256::zero()).is_none());
+    }
+
+    #[test]
+    fn mul_div_wide_intermediate() {
+        // (2^255 * 4) / 8 does not fit in 256 bits before the division
+        let big = U256::one() << 255;
+        let result = mul_div_floor(big, U256::from(4u64), U256::from(8u64)).unwrap();
+        assert_eq!(result, U256::one() << 254);
+        // the result itself overflows
+        assert!(mul_div_floor(big, U256::from(4u64), U256::one()).is_none());
+    }
+}
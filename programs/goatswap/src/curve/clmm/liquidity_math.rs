@@ -0,0 +1,45 @@
+//! Liquidity arithmetic
+
+use super::tick_math::{MAX_TICK, MIN_TICK};
+use crate::error::ErrorCode;
+use anchor_lang::prelude::*;
+
+/// Add a signed liquidity delta to liquidity
+pub fn add_delta(liquidity: u128, delta: i128) -> Result<u128> {
+    if delta < 0 {
+        liquidity.checked_sub(delta.unsigned_abs())
+    } else {
+        liquidity.checked_add(delta.unsigned_abs())
+    }
+    .ok_or_else(|| error!(ErrorCode::LiquidityOverflow))
+}
+
+/// The maximum gross liquidity a single tick can reference, chosen so that the
+/// liquidity of all ticks in range can never overflow u128
+pub fn max_liquidity_per_tick(tick_spacing: u16) -> u128 {
+    let tick_spacing = i32::from(tick_spacing);
+    let min_tick = (MIN_TICK / tick_spacing) * tick_spacing;
+    let max_tick = (MAX_TICK / tick_spacing) * tick_spacing;
+    let num_ticks = ((max_tick - min_tick) / tick_spacing) as u128 + 1;
+    u128::MAX / num_ticks
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn add_liquidity_delta() {
+        assert_eq!(add_delta(10, 5).unwrap(), 15);
+        assert_eq!(add_delta(10, -5).unwrap(), 5);
+        assert!(add_delta(10, -11).is_err());
+        assert!(add_delta(u128::MAX, 1).is_err());
+        assert_eq!(add_delta(u128::MAX, i128::MIN).unwrap(), u128::MAX / 2);
+    }
+
+    #[test]
+    fn max_liquidity_per_tick_by_spacing() {
+        assert_eq!(max_liquidity_per_tick(1), u128::MAX / 887273);
+        assert!(max_liquidity_per_tick(60) > max_liquidity_per_tick(10));
+    }
+}
@@ -0,0 +1,16 @@
+//! Concentrated liquidity math, prices are stored as Q64.64 square roots
+
+pub mod full_math;
+pub mod liquidity_math;
+pub mod sqrt_price_math;
+pub mod swap_math;
+pub mod tick_math;
+
+pub use full_math::*;
+pub use liquidity_math::*;
+pub use sqrt_price_math::*;
+pub use swap_math::*;
+pub use tick_math::*;
+
+/// 2^64, the fixed point resolution of sqrt prices and fee growths
+pub const Q64: u128 = 1u128 << 64;
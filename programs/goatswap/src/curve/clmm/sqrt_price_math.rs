@@ -0,0 +1,252 @@
+//! Token amounts and sqrt price movements for a given liquidity
+
+use super::full_math::{div_ceil, mul_div_ceil, mul_div_floor};
+use super::Q64;
+use crate::error::ErrorCode;
+use crate::utils::U256;
+use anchor_lang::prelude::*;
+
+fn u256_to_u64(value: U256) -> Option<u64> {
+    u64::try_from(value).ok()
+}
+
+fn u256_to_u128(value: U256) -> Option<u128> {
+    u128::try_from(value).ok()
+}
+
+/// Gets the next sqrt price given a delta of token_0, always rounding up so the
+/// pool never gives out more than it should.
+///
+/// liquidity * sqrt_price / (liquidity +- amount * sqrt_price)
+pub fn get_next_sqrt_price_from_amount_0_rounding_up(
+    sqrt_price_x64: u128,
+    liquidity: u128,
+    amount: u64,
+    add: bool,
+) -> Result<u128> {
+    if amount == 0 {
+        return Ok(sqrt_price_x64);
+    }
+    let numerator = U256::from(liquidity) << 64;
+    let product = U256::from(amount) * U256::from(sqrt_price_x64);
+    let denominator = if add {
+        numerator.checked_add(product)
+    } else {
+        numerator.checked_sub(product).filter(|d| !d.is_zero())
+    }
+    .ok_or(ErrorCode::SqrtPriceLimitOverflow)?;
+
+    mul_div_ceil(numerator, U256::from(sqrt_price_x64), denominator)
+        .and_then(u256_to_u128)
+        .ok_or_else(|| error!(ErrorCode::SqrtPriceLimitOverflow))
+}
+
+/// Gets the next sqrt price given a delta of token_1, always rounding down so the
+/// pool never gives out more than it should.
+///
+/// sqrt_price +- amount / liquidity
+pub fn get_next_sqrt_price_from_amount_1_rounding_down(
+    sqrt_price_x64: u128,
+    liquidity: u128,
+    amount: u64,
+    add: bool,
+) -> Result<u128> {
+    let sqrt_price = U256::from(sqrt_price_x64);
+    let next = if add {
+        let quotient = (U256::from(amount) << 64) / U256::from(liquidity);
+        sqrt_price.checked_add(quotient)
+    } else {
+        let quotient = div_ceil(U256::from(amount) << 64, U256::from(liquidity))
+            .ok_or(ErrorCode::SqrtPriceLimitOverflow)?;
+        sqrt_price.checked_sub(quotient).filter(|p| !p.is_zero())
+    };
+    next.and_then(u256_to_u128)
+        .ok_or_else(|| error!(ErrorCode::SqrtPriceLimitOverflow))
+}
+
+/// Gets the next sqrt price after swapping in `amount_in` of the input token
+pub fn get_next_sqrt_price_from_input(
+    sqrt_price_x64: u128,
+    liquidity: u128,
+    amount_in: u64,
+    zero_for_one: bool,
+) -> Result<u128> {
+    require!(
+        sqrt_price_x64 > 0 && liquidity > 0,
+        ErrorCode::SqrtPriceLimitOverflow
+    );
+    if zero_for_one {
+        get_next_sqrt_price_from_amount_0_rounding_up(sqrt_price_x64, liquidity, amount_in, true)
+    } else {
+        get_next_sqrt_price_from_amount_1_rounding_down(sqrt_price_x64, liquidity, amount_in, true)
+    }
+}
+
+/// Gets the next sqrt price after swapping out `amount_out` of the output token
+pub fn get_next_sqrt_price_from_output(
+    sqrt_price_x64: u128,
+    liquidity: u128,
+    amount_out: u64,
+    zero_for_one: bool,
+) -> Result<u128> {
+    require!(
+        sqrt_price_x64 > 0 && liquidity > 0,
+        ErrorCode::SqrtPriceLimitOverflow
+    );
+    if zero_for_one {
+        get_next_sqrt_price_from_amount_1_rounding_down(
+            sqrt_price_x64,
+            liquidity,
+            amount_out,
+            false,
+        )
+    } else {
+        get_next_sqrt_price_from_amount_0_rounding_up(sqrt_price_x64, liquidity, amount_out, false)
+    }
+}
+
+/// Amount of token_0 between two prices for the given liquidity, `None` if it
+/// does not fit in u64.
+///
+/// liquidity * (sqrt_b - sqrt_a) / (sqrt_a * sqrt_b)
+pub fn get_amount_0_delta_unsigned(
+    mut sqrt_ratio_a_x64: u128,
+    mut sqrt_ratio_b_x64: u128,
+    liquidity: u128,
+    round_up: bool,
+) -> Option<u64> {
+    if sqrt_ratio_a_x64 > sqrt_ratio_b_x64 {
+        std::mem::swap(&mut sqrt_ratio_a_x64, &mut sqrt_ratio_b_x64);
+    }
+    if sqrt_ratio_a_x64 == 0 {
+        return None;
+    }
+    let numerator_1 = U256::from(liquidity) << 64;
+    let numerator_2 = U256::from(sqrt_ratio_b_x64 - sqrt_ratio_a_x64);
+    let result = if round_up {
+        div_ceil(
+            mul_div_ceil(numerator_1, numerator_2, U256::from(sqrt_ratio_b_x64))?,
+            U256::from(sqrt_ratio_a_x64),
+        )?
+    } else {
+        mul_div_floor(numerator_1, numerator_2, U256::from(sqrt_ratio_b_x64))?
+            / U256::from(sqrt_ratio_a_x64)
+    };
+    u256_to_u64(result)
+}
+
+/// Amount of token_1 between two prices for the given liquidity, `None` if it
+/// does not fit in u64.
+///
+/// liquidity * (sqrt_b - sqrt_a)
+pub fn get_amount_1_delta_unsigned(
+    mut sqrt_ratio_a_x64: u128,
+    mut sqrt_ratio_b_x64: u128,
+    liquidity: u128,
+    round_up: bool,
+) -> Option<u64> {
+    if sqrt_ratio_a_x64 > sqrt_ratio_b_x64 {
+        std::mem::swap(&mut sqrt_ratio_a_x64, &mut sqrt_ratio_b_x64);
+    }
+    let liquidity = U256::from(liquidity);
+    let delta = U256::from(sqrt_ratio_b_x64 - sqrt_ratio_a_x64);
+    let result = if round_up {
+        mul_div_ceil(liquidity, delta, U256::from(Q64))?
+    } else {
+        mul_div_floor(liquidity, delta, U256::from(Q64))?
+    };
+    u256_to_u64(result)
+}
+
+/// Amount of token_0 owed to (or by) the pool for a liquidity change, rounding
+/// in the pool's favour
+pub fn get_amount_0_delta_signed(
+    sqrt_ratio_a_x64: u128,
+    sqrt_ratio_b_x64: u128,
+    liquidity_delta: i128,
+) -> Result<u64> {
+    get_amount_0_delta_unsigned(
+        sqrt_ratio_a_x64,
+        sqrt_ratio_b_x64,
+        liquidity_delta.unsigned_abs(),
+        liquidity_delta > 0,
+    )
+    .ok_or_else(|| error!(ErrorCode::MaxTokenOverflow))
+}
+
+/// Amount of token_1 owed to (or by) the pool for a liquidity change, rounding
+/// in the pool's favour
+pub fn get_amount_1_delta_signed(
+    sqrt_ratio_a_x64: u128,
+    sqrt_ratio_b_x64: u128,
+    liquidity_delta: i128,
+) -> Result<u64> {
+    get_amount_1_delta_unsigned(
+        sqrt_ratio_a_x64,
+        sqrt_ratio_b_x64,
+        liquidity_delta.unsigned_abs(),
+        liquidity_delta > 0,
+    )
+    .ok_or_else(|| error!(ErrorCode::MaxTokenOverflow))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::curve::clmm::tick_math::get_sqrt_price_at_tick;
+
+    #[test]
+    fn amount_deltas_at_unit_prices() {
+        // between price 1 and price 4 (sqrt 1 and 2), with liquidity 1_000
+        let sqrt_1 = Q64;
+        let sqrt_2 = 2 * Q64;
+        assert_eq!(
+            get_amount_0_delta_unsigned(sqrt_1, sqrt_2, 1_000, false).unwrap(),
+            500
+        );
+        assert_eq!(
+            get_amount_1_delta_unsigned(sqrt_1, sqrt_2, 1_000, false).unwrap(),
+            1_000
+        );
+        // argument order does not matter
+        assert_eq!(
+            get_amount_1_delta_unsigned(sqrt_2, sqrt_1, 1_000, true).unwrap(),
+            1_000
+        );
+    }
+
+    #[test]
+    fn amount_delta_rounding() {
+        let sqrt_a = get_sqrt_price_at_tick(-10).unwrap();
+        let sqrt_b = get_sqrt_price_at_tick(17).unwrap();
+        let liquidity = 123_456_789u128;
+        let down = get_amount_0_delta_unsigned(sqrt_a, sqrt_b, liquidity, false).unwrap();
+        let up = get_amount_0_delta_unsigned(sqrt_a, sqrt_b, liquidity, true).unwrap();
+        assert_eq!(up, down + 1);
+        let down = get_amount_1_delta_unsigned(sqrt_a, sqrt_b, liquidity, false).unwrap();
+        let up = get_amount_1_delta_unsigned(sqrt_a, sqrt_b, liquidity, true).unwrap();
+        assert_eq!(up, down + 1);
+        assert!(get_amount_1_delta_unsigned(sqrt_a, sqrt_b, u128::MAX, false).is_none());
+    }
+
+    #[test]
+    fn next_sqrt_price_from_input_and_output() {
+        let liquidity = 1_000_000_000u128;
+        let sqrt_price = Q64;
+        // selling token_0 lowers the price, selling token_1 raises it
+        let down = get_next_sqrt_price_from_input(sqrt_price, liquidity, 1_000, true).unwrap();
+        let up = get_next_sqrt_price_from_input(sqrt_price, liquidity, 1_000, false).unwrap();
+        assert!(down < sqrt_price && up > sqrt_price);
+
+        // the amount needed to move the price back covers the amount swapped in
+        let amount_0 = get_amount_0_delta_unsigned(down, sqrt_price, liquidity, true).unwrap();
+        assert!(amount_0 <= 1_000);
+        let amount_1 = get_amount_1_delta_unsigned(sqrt_price, up, liquidity, true).unwrap();
+        assert!(amount_1 <= 1_000);
+
+        // taking out the whole token_1 side is impossible
+        assert!(get_next_sqrt_price_from_output(Q64, 1_000, 1_000, true).is_err());
+        let next = get_next_sqrt_price_from_output(sqrt_price, liquidity, 1_000, true).unwrap();
+        assert!(get_amount_1_delta_unsigned(next, sqrt_price, liquidity, false).unwrap() >= 1_000);
+    }
+}
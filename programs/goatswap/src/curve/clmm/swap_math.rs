@@ -0,0 +1,262 @@
+//! Computes the result of swapping within a single tick range
+
+use super::full_math::mul_div_ceil;
+use super::sqrt_price_math::*;
+use crate::curve::fees::FEE_RATE_DENOMINATOR_VALUE;
+use crate::utils::U256;
+use anchor_lang::prelude::*;
+
+/// Result of a swap step
+#[derive(Debug, Default, PartialEq)]
+pub struct SwapStep {
+    /// The price after swapping the amount in/out, not to exceed the target price
+    pub sqrt_price_next_x64: u128,
+    /// The amount to be swapped in, of either token_0 or token_1, excluding fees
+    pub amount_in: u64,
+    /// The amount to be received, of either token_0 or token_1
+    pub amount_out: u64,
+    /// The amount of input that will be taken as a fee
+    pub fee_amount: u64,
+}
+
+/// Computes the result of swapping some amount in, or amount out, given the
+/// parameters of the swap.
+///
+/// The fee, plus the amount in, will never exceed the amount remaining if the
+/// swap's amount remaining is the input.
+pub fn compute_swap_step(
+    sqrt_price_current_x64: u128,
+    sqrt_price_target_x64: u128,
+    liquidity: u128,
+    amount_remaining: u64,
+    fee_rate: u64,
+    is_base_input: bool,
+    zero_for_one: bool,
+) -> Result<SwapStep> {
+    let fee_denominator = FEE_RATE_DENOMINATOR_VALUE;
+    let mut step = SwapStep::default();
+
+    // amount needed to reach the target price, `None` if it does not fit in u64
+    let amount_to_target = if is_base_input {
+        if zero_for_one {
+            get_amount_0_delta_unsigned(
+                sqrt_price_target_x64,
+                sqrt_price_current_x64,
+                liquidity,
+                true,
+            )
+        } else {
+            get_amount_1_delta_unsigned(
+                sqrt_price_current_x64,
+                sqrt_price_target_x64,
+                liquidity,
+                true,
+            )
+        }
+    } else if zero_for_one {
+        get_amount_1_delta_unsigned(
+            sqrt_price_target_x64,
+            sqrt_price_current_x64,
+            liquidity,
+            false,
+        )
+    } else {
+        get_amount_0_delta_unsigned(
+            sqrt_price_current_x64,
+            sqrt_price_target_x64,
+            liquidity,
+            false,
+        )
+    };
+
+    if is_base_input {
+        let amount_remaining_less_fee = (u128::from(amount_remaining)
+            * u128::from(fee_denominator - fee_rate)
+            / u128::from(fee_denominator)) as u64;
+        step.sqrt_price_next_x64 = match amount_to_target {
+            Some(amount_in) if amount_remaining_less_fee >= amount_in => sqrt_price_target_x64,
+            _ => get_next_sqrt_price_from_input(
+                sqrt_price_current_x64,
+                liquidity,
+                amount_remaining_less_fee,
+                zero_for_one,
+            )?,
+        };
+    } else {
+        step.sqrt_price_next_x64 = match amount_to_target {
+            Some(amount_out) if amount_remaining >= amount_out => sqrt_price_target_x64,
+            _ => get_next_sqrt_price_from_output(
+                sqrt_price_current_x64,
+                liquidity,
+                amount_remaining,
+                zero_for_one,
+            )?,
+        };
+    }
+
+    let max = sqrt_price_target_x64 == step.sqrt_price_next_x64;
+
+    // get the input/output amounts
+    let overflow = || error!(crate::error::ErrorCode::MaxTokenOverflow);
+    if zero_for_one {
+        step.amount_in = match amount_to_target {
+            Some(amount) if max && is_base_input => amount,
+            _ => get_amount_0_delta_unsigned(
+                step.sqrt_price_next_x64,
+                sqrt_price_current_x64,
+                liquidity,
+                true,
+            )
+            .ok_or_else(overflow)?,
+        };
+        step.amount_out = match amount_to_target {
+            Some(amount) if max && !is_base_input => amount,
+            _ => get_amount_1_delta_unsigned(
+                step.sqrt_price_next_x64,
+                sqrt_price_current_x64,
+                liquidity,
+                false,
+            )
+            .ok_or_else(overflow)?,
+        };
+    } else {
+        step.amount_in = match amount_to_target {
+            Some(amount) if max && is_base_input => amount,
+            _ => get_amount_1_delta_unsigned(
+                sqrt_price_current_x64,
+                step.sqrt_price_next_x64,
+                liquidity,
+                true,
+            )
+            .ok_or_else(overflow)?,
+        };
+        step.amount_out = match amount_to_target {
+            Some(amount) if max && !is_base_input => amount,
+            _ => get_amount_0_delta_unsigned(
+                sqrt_price_current_x64,
+                step.sqrt_price_next_x64,
+                liquidity,
+                false,
+            )
+            .ok_or_else(overflow)?,
+        };
+    }
+
+    // cap the output amount to not exceed the remaining output amount
+    if !is_base_input && step.amount_out > amount_remaining {
+        step.amount_out = amount_remaining;
+    }
+
+    step.fee_amount = if is_base_input && step.sqrt_price_next_x64 != sqrt_price_target_x64 {
+        // we didn't reach the target, so take the remainder of the maximum input as fee
+        amount_remaining - step.amount_in
+    } else {
+        let fee = mul_div_ceil(
+            U256::from(step.amount_in),
+            U256::from(fee_rate),
+            U256::from(fee_denominator - fee_rate),
+        )
+        .ok_or_else(overflow)?;
+        u64::try_from(fee).map_err(|_| overflow())?
+    };
+
+    Ok(step)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::curve::clmm::{get_sqrt_price_at_tick, Q64};
+    use proptest::prelude::*;
+
+    #[test]
+    fn exact_input_capped_at_target() {
+        let price = Q64;
+        let target = get_sqrt_price_at_tick(100).unwrap();
+        let step = compute_swap_step(
+            price,
+            target,
+            2_000_000_000,
+            1_000_000_000,
+            3000,
+            true,
+            false,
+        )
+        .unwrap();
+        assert_eq!(step.sqrt_price_next_x64, target);
+        assert!(step.amount_in + step.fee_amount < 1_000_000_000);
+        assert!(step.amount_out > 0);
+    }
+
+    #[test]
+    fn exact_input_fully_spent() {
+        let price = Q64;
+        let target = get_sqrt_price_at_tick(-1000).unwrap();
+        let step =
+            compute_swap_step(price, target, 2_000_000_000, 1_000_000, 3000, true, true).unwrap();
+        assert!(step.sqrt_price_next_x64 > target);
+        assert_eq!(step.amount_in + step.fee_amount, 1_000_000);
+    }
+
+    #[test]
+    fn exact_output_fully_received() {
+        let price = Q64;
+        let target = get_sqrt_price_at_tick(1000).unwrap();
+        let step =
+            compute_swap_step(price, target, 2_000_000_000, 1_000_000, 3000, false, false).unwrap();
+        assert!(step.sqrt_price_next_x64 < target);
+        assert_eq!(step.amount_out, 1_000_000);
+        assert!(step.amount_in > step.amount_out);
+    }
+
+    #[test]
+    fn zero_liquidity_moves_to_target() {
+        let target = get_sqrt_price_at_tick(-60).unwrap();
+        let step = compute_swap_step(Q64, target, 0, 1_000, 3000, true, true).unwrap();
+        assert_eq!(step.sqrt_price_next_x64, target);
+        assert_eq!(
+            step,
+            SwapStep {
+                sqrt_price_next_x64: target,
+                ..Default::default()
+            }
+        );
+    }
+
+    proptest! {
+        #[test]
+        fn swap_step_never_exceeds_remaining(
+            tick in -50000i32..50000,
+            target_offset in 1i32..5000,
+            liquidity in 1u64..u64::MAX,
+            amount_remaining in 1u64..u64::MAX / 2,
+            fee_rate in 0u64..100_000,
+            is_base_input: bool,
+            zero_for_one: bool,
+        ) {
+            let price = get_sqrt_price_at_tick(tick).unwrap();
+            let target_tick = if zero_for_one { tick - target_offset } else { tick + target_offset };
+            let target = get_sqrt_price_at_tick(target_tick).unwrap();
+            if let Ok(step) = compute_swap_step(
+                price,
+                target,
+                u128::from(liquidity),
+                amount_remaining,
+                fee_rate,
+                is_base_input,
+                zero_for_one,
+            ) {
+                if is_base_input {
+                    prop_assert!(step.amount_in as u128 + step.fee_amount as u128 <= amount_remaining as u128);
+                } else {
+                    prop_assert!(step.amount_out <= amount_remaining);
+                }
+                if zero_for_one {
+                    prop_assert!(step.sqrt_price_next_x64 <= price && step.sqrt_price_next_x64 >= target);
+                } else {
+                    prop_assert!(step.sqrt_price_next_x64 >= price && step.sqrt_price_next_x64 <= target);
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,169 @@
+//! Conversions between ticks and Q64.64 sqrt prices, where price(tick) = 1.0001^tick
+
+use crate::error::ErrorCode;
+use crate::utils::U128;
+use anchor_lang::prelude::*;
+
+/// The minimum tick, sqrt price at this tick is MIN_SQRT_PRICE_X64
+pub const MIN_TICK: i32 = -443636;
+/// The maximum tick, sqrt price at this tick is MAX_SQRT_PRICE_X64
+pub const MAX_TICK: i32 = -MIN_TICK;
+
+/// The minimum value that can be returned from `get_sqrt_price_at_tick`
+pub const MIN_SQRT_PRICE_X64: u128 = 4295048016;
+/// The maximum value that can be returned from `get_sqrt_price_at_tick`
+pub const MAX_SQRT_PRICE_X64: u128 = 79226673521066979257578248091;
+
+/// floor(2^64 / 1.0001^(2^i / 2)) for i in 0..19
+const SQRT_RATIO_FACTORS: [u64; 19] = [
+    0xfffcb933bd6fad37,
+    0xfff97272373d4132,
+    0xfff2e50f5f656932,
+    0xffe5caca7e10e4e6,
+    0xffcb9843d60f6159,
+    0xff973b41fa98c081,
+    0xff2ea16466c96a38,
+    0xfe5dee046a99a2a8,
+    0xfcbe86c7900a88ae,
+    0xf987a7253ac41317,
+    0xf3392b0822b70005,
+    0xe7159475a2c29b74,
+    0xd097f3bdfd2022b8,
+    0xa9f746462d870fdf,
+    0x70d869a156d2a1b8,
+    0x31be135f97d08fd9,
+    0x09aa508b5b7a84e1,
+    0x005d6af8dedb8119,
+    0x00002216e584f5fa,
+];
+
+/// 2^32 / log2(sqrt(1.0001)), converts a Q32.32 log2 into a Q64.64 log base sqrt(1.0001)
+const LOG_SQRT_10001_FACTOR: i128 = 59543866431248;
+
+/// Number of fractional bits computed for log2 when searching a tick
+const LOG2_PRECISION_BITS: u32 = 16;
+
+/// Calculates 1.0001^(tick/2) as a Q64.64 number
+pub fn get_sqrt_price_at_tick(tick: i32) -> Result<u128> {
+    let abs_tick = tick.unsigned_abs();
+    require!(abs_tick <= MAX_TICK as u32, ErrorCode::InvalidTickIndex);
+
+    let mut ratio = if abs_tick & 0x1 != 0 {
+        U128([SQRT_RATIO_FACTORS[0], 0])
+    } else {
+        U128([0, 1])
+    };
+    for (i, factor) in SQRT_RATIO_FACTORS.iter().enumerate().skip(1) {
+        if abs_tick & (1 << i) != 0 {
+            ratio = (ratio * U128([*factor, 0])) >> 64;
+        }
+    }
+
+    // the factors are for negative ticks, invert for positive ones
+    if tick > 0 {
+        ratio = U128::MAX / ratio;
+    }
+    Ok(ratio.as_u128())
+}
+
+/// Calculates the greatest tick such that `get_sqrt_price_at_tick(tick) <= sqrt_price_x64`
+pub fn get_tick_at_sqrt_price(sqrt_price_x64: u128) -> Result<i32> {
+    require!(
+        (MIN_SQRT_PRICE_X64..MAX_SQRT_PRICE_X64).contains(&sqrt_price_x64),
+        ErrorCode::SqrtPriceLimitOverflow
+    );
+
+    // integer part of log2, the price is a Q64.64 number
+    let msb = 127 - sqrt_price_x64.leading_zeros();
+    let mut log2_x32 = (i128::from(msb) - 64) << 32;
+
+    // normalize to [1, 2) with 63 fractional bits, then refine the fractional
+    // part of log2 one bit per squaring
+    let mut r = if msb >= 64 {
+        sqrt_price_x64 >> (msb - 63)
+    } else {
+        sqrt_price_x64 << (63 - msb)
+    };
+    let mut bit: i128 = 1 << 31;
+    for _ in 0..LOG2_PRECISION_BITS {
+        r = (r * r) >> 63;
+        let is_r_more_than_two = (r >> 64) as u32;
+        r >>= is_r_more_than_two;
+        log2_x32 |= bit * i128::from(is_r_more_than_two);
+        bit >>= 1;
+    }
+
+    // the estimate is within one tick, fix it up against the exact prices
+    let log_sqrt_10001_x64 = log2_x32 * LOG_SQRT_10001_FACTOR;
+    let mut tick = ((log_sqrt_10001_x64 >> 64) as i32).clamp(MIN_TICK, MAX_TICK);
+    while tick > MIN_TICK && get_sqrt_price_at_tick(tick)? > sqrt_price_x64 {
+        tick -= 1;
+    }
+    while tick < MAX_TICK && get_sqrt_price_at_tick(tick + 1)? <= sqrt_price_x64 {
+        tick += 1;
+    }
+    Ok(tick)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use proptest::prelude::*;
+
+    #[test]
+    fn sqrt_price_at_boundary_ticks() {
+        assert_eq!(
+            get_sqrt_price_at_tick(MIN_TICK).unwrap(),
+            MIN_SQRT_PRICE_X64
+        );
+        assert_eq!(
+            get_sqrt_price_at_tick(MAX_TICK).unwrap(),
+            MAX_SQRT_PRICE_X64
+        );
+        assert_eq!(get_sqrt_price_at_tick(0).unwrap(), 1u128 << 64);
+        assert!(get_sqrt_price_at_tick(MIN_TICK - 1).is_err());
+        assert!(get_sqrt_price_at_tick(MAX_TICK + 1).is_err());
+    }
+
+    #[test]
+    fn sqrt_price_matches_float() {
+        for tick in [-400000, -50000, -100, -1, 1, 100, 50000, 400000] {
+            let expected = 1.0001f64.powf(f64::from(tick) / 2.0) * 2f64.powi(64);
+            let actual = get_sqrt_price_at_tick(tick).unwrap() as f64;
+            assert!((actual - expected).abs() / expected < 1e-10);
+        }
+    }
+
+    #[test]
+    fn tick_at_boundary_sqrt_prices() {
+        assert_eq!(
+            get_tick_at_sqrt_price(MIN_SQRT_PRICE_X64).unwrap(),
+            MIN_TICK
+        );
+        assert_eq!(
+            get_tick_at_sqrt_price(MAX_SQRT_PRICE_X64 - 1).unwrap(),
+            MAX_TICK - 1
+        );
+        assert!(get_tick_at_sqrt_price(MIN_SQRT_PRICE_X64 - 1).is_err());
+        assert!(get_tick_at_sqrt_price(MAX_SQRT_PRICE_X64).is_err());
+    }
+
+    proptest! {
+        #[test]
+        fn tick_round_trip(tick in MIN_TICK..MAX_TICK) {
+            let sqrt_price = get_sqrt_price_at_tick(tick).unwrap();
+            prop_assert_eq!(get_tick_at_sqrt_price(sqrt_price).unwrap(), tick);
+            prop_assert!(get_sqrt_price_at_tick(tick + 1).unwrap() > sqrt_price);
+            if sqrt_price + 1 < get_sqrt_price_at_tick(tick + 1).unwrap() {
+                prop_assert_eq!(get_tick_at_sqrt_price(sqrt_price + 1).unwrap(), tick);
+            }
+        }
+
+        #[test]
+        fn tick_at_any_sqrt_price(sqrt_price in MIN_SQRT_PRICE_X64..MAX_SQRT_PRICE_X64) {
+            let tick = get_tick_at_sqrt_price(sqrt_price).unwrap();
+            prop_assert!(get_sqrt_price_at_tick(tick).unwrap() <= sqrt_price);
+            prop_assert!(get_sqrt_price_at_tick(tick + 1).unwrap() > sqrt_price);
+        }
+    }
+}
@@ -1,8 +1,8 @@
 //! The Uniswap invariantConstantProductCurve::
 
 use crate::{
-    curve::calculator::{RoundDirection, TradingTokenResult},
-    utils::CheckedCeilDiv,
+    curve::calculator::{RoundDirection, SwapCurve, TradeDirection, TradingTokenResult},
+    utils::{CheckedCeilDiv, U256},
 };
 
 /// ConstantProductCurve struct implementing CurveCalculator
@@ -25,8 +25,7 @@ impl ConstantProductCurve {
         // delta_y = (delta_x * y) / (x + delta_x)
         let numerator = source_amount.checked_mul(swap_destination_amount).unwrap();
         let denominator = swap_source_amount.checked_add(source_amount).unwrap();
-        let destinsation_amount_swapped = numerator.checked_div(denominator).unwrap();
-        destinsation_amount_swapped
+        numerator.checked_div(denominator).unwrap()
     }
 
     pub fn swap_base_output_without_fees(
@@ -92,6 +91,66 @@ impl ConstantProductCurve {
     }
 }
 
+impl SwapCurve for ConstantProductCurve {
+    fn swap_base_input_without_fees(
+        &self,
+        source_amount: u128,
+        swap_source_amount: u128,
+        swap_destination_amount: u128,
+        _trade_direction: TradeDirection,
+    ) -> Option<u128> {
+        Some(ConstantProductCurve::swap_base_input_without_fees(
+            source_amount,
+            swap_source_amount,
+            swap_destination_amount,
+        ))
+    }
+
+    fn swap_base_output_without_fees(
+        &self,
+        destination_amount: u128,
+        swap_source_amount: u128,
+        swap_destination_amount: u128,
+        _trade_direction: TradeDirection,
+    ) -> Option<u128> {
+        Some(ConstantProductCurve::swap_base_output_without_fees(
+            destination_amount,
+            swap_source_amount,
+            swap_destination_amount,
+        ))
+    }
+
+    fn lp_tokens_to_trading_tokens(
+        &self,
+        lp_token_amount: u128,
+        lp_token_supply: u128,
+        swap_token_0_amount: u128,
+        swap_token_1_amount: u128,
+        round_direction: RoundDirection,
+    ) -> Option<TradingTokenResult> {
+        ConstantProductCurve::lp_tokens_to_trading_tokens(
+            lp_token_amount,
+            lp_token_supply,
+            swap_token_0_amount,
+            swap_token_1_amount,
+            round_direction,
+        )
+    }
+
+    /// x * y must not decrease
+    fn check_invariant(
+        &self,
+        swap_source_amount: u128,
+        swap_destination_amount: u128,
+        new_swap_source_amount: u128,
+        new_swap_destination_amount: u128,
+        _trade_direction: TradeDirection,
+    ) -> bool {
+        U256::from(new_swap_source_amount) * U256::from(new_swap_destination_amount)
+            >= U256::from(swap_source_amount) * U256::from(swap_destination_amount)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use {
@@ -161,19 +220,18 @@ mod tests {
         expected_destination_amount_swapped: u128,
     ) {
         let invariant = swap_source_amount * swap_destination_amount;
-        let result = ConstantProductCurve::swap_base_input_without_fees(
+        let destination_amount_swapped = ConstantProductCurve::swap_base_input_without_fees(
             source_amount,
             swap_source_amount,
             swap_destination_amount,
-        )
-        .unwrap();
-        assert_eq!(result.source_amount_swapped, expected_source_amount_swapped);
+        );
+        assert_eq!(source_amount, expected_source_amount_swapped);
         assert_eq!(
-            result.destination_amount_swapped,
+            destination_amount_swapped,
             expected_destination_amount_swapped
         );
-        let new_invariant = (swap_source_amount + result.source_amount_swapped)
-            * (swap_destination_amount - result.destination_amount_swapped);
+        let new_invariant = (swap_source_amount + source_amount)
+            * (swap_destination_amount - destination_amount_swapped);
         assert!(new_invariant >= invariant);
     }
 
@@ -219,6 +277,33 @@ mod tests {
         }
     }
 
+    #[test]
+    fn check_invariant_rejects_overpaying_swap() {
+        let curve = ConstantProductCurve;
+        let amount_out = SwapCurve::swap_base_input_without_fees(
+            &curve,
+            10,
+            20_000,
+            30_000,
+            TradeDirection::ZeroForOne,
+        )
+        .unwrap();
+        assert!(curve.check_invariant(
+            20_000,
+            30_000,
+            20_010,
+            30_000 - amount_out,
+            TradeDirection::ZeroForOne
+        ));
+        assert!(!curve.check_invariant(
+            20_000,
+            30_000,
+            20_010,
+            30_000 - amount_out - 1,
+            TradeDirection::ZeroForOne
+        ));
+    }
+
     proptest! {
         #[test]
         fn curve_value_does_not_decrease_from_swap(
@@ -252,6 +337,7 @@ mod tests {
             prop_assume!(pool_token_amount * swap_token_a_amount / pool_token_supply >= 1);
             prop_assume!(pool_token_amount * swap_token_b_amount / pool_token_supply >= 1);
             check_pool_value_from_deposit(
+                &ConstantProductCurve,
                 pool_token_amount,
                 pool_token_supply,
                 swap_token_a_amount,
@@ -276,6 +362,7 @@ mod tests {
             prop_assume!(pool_token_amount * swap_token_a_amount / pool_token_supply >= 1);
             prop_assume!(pool_token_amount * swap_token_b_amount / pool_token_supply >= 1);
             check_pool_value_from_withdraw(
+                &ConstantProductCurve,
                 pool_token_amount,
                 pool_token_supply,
                 swap_token_a_amount,
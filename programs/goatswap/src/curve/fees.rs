@@ -6,7 +6,7 @@ pub struct Fees {}
 
 fn ceil_div(token_amount: u128, fee_numerator: u128, fee_denominator: u128) -> Option<u128> {
     token_amount
-        .checked_mul(u128::from(fee_numerator))
+        .checked_mul(fee_numerator)
         .unwrap()
         .checked_add(fee_denominator)?
         .checked_sub(1)?
@@ -15,11 +15,9 @@ fn ceil_div(token_amount: u128, fee_numerator: u128, fee_denominator: u128) -> O
 
 /// Helper function for calculating swap fee
 pub fn floor_div(token_amount: u128, fee_numerator: u128, fee_denominator: u128) -> Option<u128> {
-    Some(
-        token_amount
-            .checked_mul(fee_numerator)?
-            .checked_div(fee_denominator)?,
-    )
+    token_amount
+        .checked_mul(fee_numerator)?
+        .checked_div(fee_denominator)
 }
 
 impl Fees {
@@ -50,6 +48,24 @@ impl Fees {
         )
     }
 
+    /// Calculate the referrer's share of the trading fee in trading tokens
+    pub fn referral_fee(amount: u128, referral_fee_rate: u64) -> Option<u128> {
+        floor_div(
+            amount,
+            u128::from(referral_fee_rate),
+            u128::from(FEE_RATE_DENOMINATOR_VALUE),
+        )
+    }
+
+    /// Calculate the discount on the trading fee in trading tokens
+    pub fn fee_discount(amount: u128, fee_discount_rate: u64) -> Option<u128> {
+        floor_div(
+            amount,
+            u128::from(fee_discount_rate),
+            u128::from(FEE_RATE_DENOMINATOR_VALUE),
+        )
+    }
+
     pub fn calculate_pre_fee_amount(post_fee_amount: u128, trade_fee_rate: u64) -> Option<u128> {
         if trade_fee_rate == 0 {
             Some(post_fee_amount)
@@ -0,0 +1,330 @@
+//! The Curve StableSwap invariant for two tokens
+//!
+//! A * n^n * (x + y) + D = A * D * n^n + D^(n+1) / (n^n * x * y), with n = 2
+
+use crate::curve::calculator::{RoundDirection, SwapCurve, TradeDirection, TradingTokenResult};
+use crate::curve::constant_product::ConstantProductCurve;
+use crate::utils::U256;
+
+/// Minimum amplification coefficient
+pub const MIN_AMP: u64 = 1;
+/// Maximum amplification coefficient
+pub const MAX_AMP: u64 = 1_000_000;
+/// Maximum factor the amplification coefficient can change by in a single ramp
+pub const MAX_AMP_CHANGE: u64 = 10;
+/// Minimum duration of an amplification ramp, in seconds
+pub const MIN_RAMP_DURATION: u64 = 86400;
+
+/// Number of tokens in a pool
+const N_COINS: u64 = 2;
+/// Maximum number of Newton iterations
+const MAX_ITERATIONS: u8 = 255;
+
+/// StableSwapCurve struct implementing the StableSwap invariant
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct StableSwapCurve {
+    /// Amplification coefficient
+    pub amp: u64,
+}
+
+/// Amplification coefficient at `now` for a linear ramp from `initial_amp` to `target_amp`
+pub fn current_amp(
+    initial_amp: u64,
+    target_amp: u64,
+    ramp_start_time: u64,
+    ramp_stop_time: u64,
+    now: u64,
+) -> u64 {
+    if now >= ramp_stop_time || ramp_stop_time <= ramp_start_time {
+        return target_amp;
+    }
+    let elapsed = u128::from(now.saturating_sub(ramp_start_time));
+    let duration = u128::from(ramp_stop_time - ramp_start_time);
+    if target_amp > initial_amp {
+        let delta = u128::from(target_amp - initial_amp) * elapsed / duration;
+        initial_amp + delta as u64
+    } else {
+        let delta = u128::from(initial_amp - target_amp) * elapsed / duration;
+        initial_amp - delta as u64
+    }
+}
+
+impl StableSwapCurve {
+    pub fn new(amp: u64) -> Self {
+        Self { amp }
+    }
+
+    /// A * n^n, the leverage applied to the constant sum part of the invariant
+    fn leverage(&self) -> U256 {
+        U256::from(self.amp) * U256::from(N_COINS * N_COINS)
+    }
+
+    /// Computes the invariant D for the given token amounts
+    pub fn compute_d(&self, amount_0: u128, amount_1: u128) -> Option<u128> {
+        let sum = U256::from(amount_0).checked_add(U256::from(amount_1))?;
+        if sum.is_zero() {
+            return Some(0);
+        }
+        if amount_0 == 0 || amount_1 == 0 {
+            return None;
+        }
+        let leverage = self.leverage();
+        let n_coins = U256::from(N_COINS);
+        let mut d = sum;
+        for _ in 0..MAX_ITERATIONS {
+            // d_p = D^(n+1) / (n^n * x * y)
+            let d_p = d
+                .checked_mul(d)?
+                .checked_div(U256::from(amount_0) * n_coins)?
+                .checked_mul(d)?
+                .checked_div(U256::from(amount_1) * n_coins)?;
+            let d_prev = d;
+            // D = (Ann * S + n * D_p) * D / ((Ann - 1) * D + (n + 1) * D_p)
+            let numerator = leverage
+                .checked_mul(sum)?
+                .checked_add(d_p.checked_mul(n_coins)?)?
+                .checked_mul(d)?;
+            let denominator = leverage
+                .checked_sub(U256::one())?
+                .checked_mul(d)?
+                .checked_add(d_p.checked_mul(n_coins + 1)?)?;
+            d = numerator.checked_div(denominator)?;
+            if d.abs_diff(d_prev) <= U256::one() {
+                break;
+            }
+        }
+        u128::try_from(d).ok()
+    }
+
+    /// Computes the amount of the other token that keeps the invariant at `d`
+    /// when one token amount is `amount`
+    pub fn compute_y(&self, amount: u128, d: u128) -> Option<u128> {
+        if amount == 0 {
+            return None;
+        }
+        let leverage = self.leverage();
+        let n_coins = U256::from(N_COINS);
+        let d = U256::from(d);
+        // c = D^(n+1) / (n^n * x * Ann)
+        let c = d
+            .checked_mul(d)?
+            .checked_div(U256::from(amount) * n_coins)?
+            .checked_mul(d)?
+            .checked_div(leverage.checked_mul(n_coins)?)?;
+        // b = x + D / Ann
+        let b = U256::from(amount).checked_add(d.checked_div(leverage)?)?;
+
+        // y = (y^2 + c) / (2y + b - D)
+        let mut y = d;
+        for _ in 0..MAX_ITERATIONS {
+            let y_prev = y;
+            let numerator = y.checked_mul(y)?.checked_add(c)?;
+            let denominator = y.checked_mul(n_coins)?.checked_add(b)?.checked_sub(d)?;
+            y = numerator.checked_div(denominator)?;
+            if y.abs_diff(y_prev) <= U256::one() {
+                break;
+            }
+        }
+        u128::try_from(y).ok()
+    }
+}
+
+impl SwapCurve for StableSwapCurve {
+    fn swap_base_input_without_fees(
+        &self,
+        source_amount: u128,
+        swap_source_amount: u128,
+        swap_destination_amount: u128,
+        _trade_direction: TradeDirection,
+    ) -> Option<u128> {
+        let d = self.compute_d(swap_source_amount, swap_destination_amount)?;
+        let new_swap_source_amount = swap_source_amount.checked_add(source_amount)?;
+        // y is only known up to one unit, round it up before taking it out of the pool
+        let new_swap_destination_amount =
+            self.compute_y(new_swap_source_amount, d)?.checked_add(1)?;
+        Some(swap_destination_amount.saturating_sub(new_swap_destination_amount))
+    }
+
+    fn swap_base_output_without_fees(
+        &self,
+        destination_amount: u128,
+        swap_source_amount: u128,
+        swap_destination_amount: u128,
+        _trade_direction: TradeDirection,
+    ) -> Option<u128> {
+        let d = self.compute_d(swap_source_amount, swap_destination_amount)?;
+        let new_swap_destination_amount =
+            swap_destination_amount.checked_sub(destination_amount)?;
+        let new_swap_source_amount = self
+            .compute_y(new_swap_destination_amount, d)?
+            .checked_add(1)?;
+        new_swap_source_amount.checked_sub(swap_source_amount)
+    }
+
+    /// Deposits and withdrawals keep the pool balance, so the lp tokens map to
+    /// the trading tokens by the same ratio as the constant product curve
+    fn lp_tokens_to_trading_tokens(
+        &self,
+        lp_token_amount: u128,
+        lp_token_supply: u128,
+        swap_token_0_amount: u128,
+        swap_token_1_amount: u128,
+        round_direction: RoundDirection,
+    ) -> Option<TradingTokenResult> {
+        ConstantProductCurve::lp_tokens_to_trading_tokens(
+            lp_token_amount,
+            lp_token_supply,
+            swap_token_0_amount,
+            swap_token_1_amount,
+            round_direction,
+        )
+    }
+
+    /// The ratio of the partial derivatives of the invariant,
+    /// (4·L·x·y + D³/x) / (4·L·x·y + D³/y) with L the leverage
+    fn spot_price(&self, token_0_amount: u128, token_1_amount: u128) -> Option<(U256, U256)> {
+        if token_0_amount == 0 || token_1_amount == 0 {
+            return None;
+        }
+        let d = U256::from(self.compute_d(token_0_amount, token_1_amount)?);
+        let d_cubed = d.checked_mul(d)?.checked_mul(d)?;
+        let leverage_term = self
+            .leverage()
+            .checked_mul(U256::from(N_COINS * N_COINS))?
+            .checked_mul(U256::from(token_0_amount))?
+            .checked_mul(U256::from(token_1_amount))?;
+        Some((
+            leverage_term.checked_add(d_cubed / U256::from(token_0_amount))?,
+            leverage_term.checked_add(d_cubed / U256::from(token_1_amount))?,
+        ))
+    }
+
+    /// D must not decrease
+    fn check_invariant(
+        &self,
+        swap_source_amount: u128,
+        swap_destination_amount: u128,
+        new_swap_source_amount: u128,
+        new_swap_destination_amount: u128,
+        _trade_direction: TradeDirection,
+    ) -> bool {
+        match (
+            self.compute_d(swap_source_amount, swap_destination_amount),
+            self.compute_d(new_swap_source_amount, new_swap_destination_amount),
+        ) {
+            (Some(d), Some(new_d)) => new_d >= d,
+            _ => false,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::curve::calculator::spot_price_x32;
+    use proptest::prelude::*;
+
+    #[test]
+    fn amp_ramp() {
+        assert_eq!(current_amp(100, 200, 1000, 2000, 500), 100);
+        assert_eq!(current_amp(100, 200, 1000, 2000, 1500), 150);
+        assert_eq!(current_amp(100, 200, 1000, 2000, 2500), 200);
+        assert_eq!(current_amp(200, 100, 1000, 2000, 1250), 175);
+        // no ramp scheduled
+        assert_eq!(current_amp(100, 100, 0, 0, 1500), 100);
+    }
+
+    #[test]
+    fn balanced_pool_invariant_is_sum() {
+        let curve = StableSwapCurve::new(100);
+        assert_eq!(curve.compute_d(0, 0), Some(0));
+        let d = curve.compute_d(1_000_000, 1_000_000).unwrap();
+        assert!(d.abs_diff(2_000_000) <= 1);
+    }
+
+    #[test]
+    fn lower_slippage_than_constant_product() {
+        let curve = StableSwapCurve::new(100);
+        let stable_out = curve
+            .swap_base_input_without_fees(100_000, 1_000_000, 1_000_000, TradeDirection::ZeroForOne)
+            .unwrap();
+        let cp_out =
+            ConstantProductCurve::swap_base_input_without_fees(100_000, 1_000_000, 1_000_000);
+        assert!(stable_out > cp_out);
+        assert!(stable_out < 100_000);
+        assert!(stable_out > 99_000);
+    }
+
+    #[test]
+    fn spot_price_between_one_and_constant_product() {
+        let curve = StableSwapCurve::new(100);
+        assert_eq!(
+            spot_price_x32(&curve, 1_000_000, 1_000_000),
+            Some((1 << 32, 1 << 32))
+        );
+        // token_0 is scarce, so it is worth more than token_1, but much
+        // less so than on a constant product curve
+        let (price_0, price_1) = spot_price_x32(&curve, 500_000, 1_500_000).unwrap();
+        assert!(price_0 > 1 << 32 && price_0 < 3 << 32);
+        assert!(price_1 < 1 << 32);
+    }
+
+    #[test]
+    fn cannot_drain_destination() {
+        let curve = StableSwapCurve::new(100);
+        assert!(curve
+            .swap_base_output_without_fees(
+                1_000_000,
+                1_000_000,
+                1_000_000,
+                TradeDirection::ZeroForOne
+            )
+            .is_none());
+        let out = curve
+            .swap_base_input_without_fees(
+                u64::MAX.into(),
+                1_000_000,
+                1_000_000,
+                TradeDirection::ZeroForOne,
+            )
+            .unwrap();
+        assert!(out < 1_000_000);
+    }
+
+    proptest! {
+        #[test]
+        fn swap_does_not_decrease_invariant(
+            amp in MIN_AMP..=10_000u64,
+            source_amount in 1..u32::MAX as u128,
+            swap_source_amount in 1_000..u64::MAX as u128,
+            swap_destination_amount in 1_000..u64::MAX as u128,
+        ) {
+            let curve = StableSwapCurve::new(amp);
+            let d_before = curve.compute_d(swap_source_amount, swap_destination_amount).unwrap();
+            let amount_out = curve
+                .swap_base_input_without_fees(source_amount, swap_source_amount, swap_destination_amount, TradeDirection::ZeroForOne)
+                .unwrap();
+            let d_after = curve
+                .compute_d(swap_source_amount + source_amount, swap_destination_amount - amount_out)
+                .unwrap();
+            prop_assert!(d_after >= d_before);
+        }
+
+        #[test]
+        fn swap_base_output_covers_swap_base_input(
+            amp in MIN_AMP..=10_000u64,
+            destination_amount in 1..u32::MAX as u128,
+            swap_source_amount in 1_000_000..u64::MAX as u128,
+            swap_destination_amount in u32::MAX as u128..u64::MAX as u128,
+        ) {
+            let curve = StableSwapCurve::new(amp);
+            let source_amount = curve
+                .swap_base_output_without_fees(destination_amount, swap_source_amount, swap_destination_amount, TradeDirection::ZeroForOne)
+                .unwrap();
+            let amount_out = curve
+                .swap_base_input_without_fees(source_amount, swap_source_amount, swap_destination_amount, TradeDirection::ZeroForOne)
+                .unwrap();
+            prop_assert!(amount_out + 2 >= destination_amount);
+        }
+    }
+}
@@ -0,0 +1,442 @@
+//! The Balancer weighted invariant for two tokens
+//!
+//! x^w0 * y^w1 = k, with w0 + w1 = 1
+
+use crate::curve::calculator::{RoundDirection, SwapCurve, TradeDirection, TradingTokenResult};
+use crate::curve::constant_product::ConstantProductCurve;
+use crate::utils::U256;
+
+/// Denominator of the pool weights, weight_0 + weight_1 = WEIGHT_DENOMINATOR
+pub const WEIGHT_DENOMINATOR: u64 = 1_000_000;
+/// Minimum weight of either token, 1%
+pub const MIN_WEIGHT: u64 = 10_000;
+
+/// 1.0 in Q64.64
+const ONE: u128 = 1 << 64;
+/// ln(2) in Q64.64
+const LN_2: u128 = 12786308645202655659;
+
+/// Weight at `now` for a linear shift from `start_weight` at `start_time` to
+/// `end_weight` at `end_time`
+pub fn current_weight(
+    start_weight: u64,
+    end_weight: u64,
+    start_time: u64,
+    end_time: u64,
+    now: u64,
+) -> u64 {
+    if now >= end_time || end_time <= start_time {
+        return end_weight;
+    }
+    let elapsed = u128::from(now.saturating_sub(start_time));
+    let duration = u128::from(end_time - start_time);
+    if end_weight > start_weight {
+        let delta = u128::from(end_weight - start_weight) * elapsed / duration;
+        start_weight + delta as u64
+    } else {
+        let delta = u128::from(start_weight - end_weight) * elapsed / duration;
+        start_weight - delta as u64
+    }
+}
+
+/// WeightedCurve struct implementing the weighted invariant
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct WeightedCurve {
+    /// Weight of token_0, over WEIGHT_DENOMINATOR
+    pub weight_0: u64,
+    /// Weight of token_1, over WEIGHT_DENOMINATOR
+    pub weight_1: u64,
+}
+
+impl WeightedCurve {
+    pub fn new(weight_0: u64) -> Self {
+        Self {
+            weight_0,
+            weight_1: WEIGHT_DENOMINATOR.saturating_sub(weight_0),
+        }
+    }
+
+    /// Weights of the source and destination tokens of a trade
+    fn weights(&self, trade_direction: TradeDirection) -> (u128, u128) {
+        match trade_direction {
+            TradeDirection::ZeroForOne => (self.weight_0.into(), self.weight_1.into()),
+            TradeDirection::OneForZero => (self.weight_1.into(), self.weight_0.into()),
+        }
+    }
+
+    fn check_invariant_inner(
+        &self,
+        swap_source_amount: u128,
+        swap_destination_amount: u128,
+        new_swap_source_amount: u128,
+        new_swap_destination_amount: u128,
+        trade_direction: TradeDirection,
+    ) -> Option<bool> {
+        if new_swap_source_amount < swap_source_amount {
+            return Some(false);
+        }
+        let (weight_in, weight_out) = self.weights(trade_direction);
+        // y' / y >= (x / x')^(w_in / w_out), with everything rounded towards accepting
+        let base = div_q64(
+            swap_source_amount,
+            new_swap_source_amount,
+            RoundDirection::Floor,
+        )?;
+        let exponent = div_q64(weight_in, weight_out, RoundDirection::Ceiling)?;
+        let ratio = pow(base, exponent)?;
+        let ratio = ratio.saturating_sub(pow_error(ratio));
+        Some(
+            U256::from(new_swap_destination_amount) << 64
+                >= U256::from(swap_destination_amount) * U256::from(ratio),
+        )
+    }
+}
+
+impl SwapCurve for WeightedCurve {
+    fn swap_base_input_without_fees(
+        &self,
+        source_amount: u128,
+        swap_source_amount: u128,
+        swap_destination_amount: u128,
+        trade_direction: TradeDirection,
+    ) -> Option<u128> {
+        let (weight_in, weight_out) = self.weights(trade_direction);
+        let new_swap_source_amount = swap_source_amount.checked_add(source_amount)?;
+        // y' = y * (x / x')^(w_in / w_out)
+        let base = div_q64(
+            swap_source_amount,
+            new_swap_source_amount,
+            RoundDirection::Floor,
+        )?;
+        let exponent = div_q64(weight_in, weight_out, RoundDirection::Floor)?;
+        let ratio = pow(base, exponent)?;
+        let ratio = ratio.checked_add(pow_error(ratio))?;
+        let new_swap_destination_amount =
+            mul_q64(swap_destination_amount, ratio, RoundDirection::Ceiling)?;
+        Some(swap_destination_amount.saturating_sub(new_swap_destination_amount))
+    }
+
+    fn swap_base_output_without_fees(
+        &self,
+        destination_amount: u128,
+        swap_source_amount: u128,
+        swap_destination_amount: u128,
+        trade_direction: TradeDirection,
+    ) -> Option<u128> {
+        let (weight_in, weight_out) = self.weights(trade_direction);
+        let new_swap_destination_amount =
+            swap_destination_amount.checked_sub(destination_amount)?;
+        // x' = x * (y / y')^(w_out / w_in)
+        let base = div_q64(
+            swap_destination_amount,
+            new_swap_destination_amount,
+            RoundDirection::Ceiling,
+        )?;
+        let exponent = div_q64(weight_out, weight_in, RoundDirection::Ceiling)?;
+        let ratio = pow(base, exponent)?;
+        let ratio = ratio.checked_add(pow_error(ratio))?;
+        let new_swap_source_amount = mul_q64(swap_source_amount, ratio, RoundDirection::Ceiling)?;
+        new_swap_source_amount.checked_sub(swap_source_amount)
+    }
+
+    /// Deposits and withdrawals take both tokens in the pool ratio, which keeps
+    /// the price, so the lp tokens map to the trading tokens by that same ratio
+    fn lp_tokens_to_trading_tokens(
+        &self,
+        lp_token_amount: u128,
+        lp_token_supply: u128,
+        swap_token_0_amount: u128,
+        swap_token_1_amount: u128,
+        round_direction: RoundDirection,
+    ) -> Option<TradingTokenResult> {
+        ConstantProductCurve::lp_tokens_to_trading_tokens(
+            lp_token_amount,
+            lp_token_supply,
+            swap_token_0_amount,
+            swap_token_1_amount,
+            round_direction,
+        )
+    }
+
+    /// (y / w1) / (x / w0)
+    fn spot_price(&self, token_0_amount: u128, token_1_amount: u128) -> Option<(U256, U256)> {
+        Some((
+            U256::from(token_1_amount).checked_mul(U256::from(self.weight_0))?,
+            U256::from(token_0_amount).checked_mul(U256::from(self.weight_1))?,
+        ))
+    }
+
+    /// x^w0 * y^w1 must not decrease
+    fn check_invariant(
+        &self,
+        swap_source_amount: u128,
+        swap_destination_amount: u128,
+        new_swap_source_amount: u128,
+        new_swap_destination_amount: u128,
+        trade_direction: TradeDirection,
+    ) -> bool {
+        self.check_invariant_inner(
+            swap_source_amount,
+            swap_destination_amount,
+            new_swap_source_amount,
+            new_swap_destination_amount,
+            trade_direction,
+        )
+        .unwrap_or(false)
+    }
+
+    /// The invariant x^w0 * y^w1, which is sqrt(x * y) for a 50/50 pool
+    fn initial_lp_tokens(&self, token_0_amount: u128, token_1_amount: u128) -> Option<u128> {
+        let log_0 = log2(token_0_amount.checked_mul(ONE)?)?;
+        let log_1 = log2(token_1_amount.checked_mul(ONE)?)?;
+        if log_0 < 0 || log_1 < 0 {
+            return None;
+        }
+        let weighted_log = (U256::from(log_0 as u128) * U256::from(self.weight_0)
+            + U256::from(log_1 as u128) * U256::from(self.weight_1))
+            / U256::from(WEIGHT_DENOMINATOR);
+        let invariant = exp2(i128::try_from(u128::try_from(weighted_log).ok()?).ok()?)?;
+        Some(invariant >> 64)
+    }
+}
+
+/// Error bound used around `pow`, far above its actual error
+fn pow_error(value: u128) -> u128 {
+    (value >> 32) + 4
+}
+
+/// a / b in Q64.64
+fn div_q64(a: u128, b: u128, round_direction: RoundDirection) -> Option<u128> {
+    let numerator = U256::from(a) << 64;
+    let denominator = U256::from(b);
+    let mut result = numerator.checked_div(denominator)?;
+    if round_direction == RoundDirection::Ceiling && !(numerator % denominator).is_zero() {
+        result = result.checked_add(U256::one())?;
+    }
+    u128::try_from(result).ok()
+}
+
+/// amount * ratio, with ratio in Q64.64
+fn mul_q64(amount: u128, ratio: u128, round_direction: RoundDirection) -> Option<u128> {
+    let mut product = U256::from(amount).checked_mul(U256::from(ratio))?;
+    if round_direction == RoundDirection::Ceiling {
+        product = product.checked_add(U256::from(ONE - 1))?;
+    }
+    u128::try_from(product >> 64).ok()
+}
+
+/// log2 of a positive Q64.64 number, as a signed Q64.64 number
+fn log2(x: u128) -> Option<i128> {
+    if x == 0 {
+        return None;
+    }
+    let msb = 127 - x.leading_zeros() as i128;
+    let mut result = (msb - 64) << 64;
+    // normalize to [1, 2) in Q1.63, so that squares fit in u128
+    let mut y = if msb >= 63 {
+        x >> (msb - 63)
+    } else {
+        x << (63 - msb)
+    };
+    // each squaring of y in [1, 2) gives the next binary digit of its log
+    let mut bit: i128 = 1 << 63;
+    while bit > 0 {
+        y = (y * y) >> 63;
+        if y >= 1 << 64 {
+            y >>= 1;
+            result += bit;
+        }
+        bit >>= 1;
+    }
+    Some(result)
+}
+
+/// 2^x of a signed Q64.64 number, as a Q64.64 number
+fn exp2(x: i128) -> Option<u128> {
+    let integer = x >> 64;
+    let fraction = (x - (integer << 64)) as u128;
+    // 2^f = e^(f * ln(2)), summing the Taylor series until terms vanish
+    let t = (fraction * LN_2) >> 64;
+    let mut term = ONE;
+    let mut sum = ONE;
+    let mut n = 1;
+    while term > 0 {
+        term = ((term * t) >> 64) / n;
+        sum += term;
+        n += 1;
+    }
+    if integer >= 0 {
+        if integer >= i128::from(sum.leading_zeros()) {
+            return None;
+        }
+        Some(sum << integer)
+    } else if integer <= -128 {
+        Some(0)
+    } else {
+        Some(sum >> -integer)
+    }
+}
+
+/// base^exponent, both in Q64.64
+fn pow(base: u128, exponent: u128) -> Option<u128> {
+    if base == 0 {
+        return Some(0);
+    }
+    let log = log2(base)?;
+    let product = (U256::from(log.unsigned_abs()) * U256::from(exponent)) >> 64;
+    let product = i128::try_from(u128::try_from(product).ok()?).ok()?;
+    exp2(if log < 0 { -product } else { product })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::curve::calculator::spot_price_x32;
+    use proptest::prelude::*;
+
+    fn to_f64(x: u128) -> f64 {
+        x as f64 / ONE as f64
+    }
+
+    #[test]
+    fn weight_shift() {
+        assert_eq!(current_weight(900_000, 500_000, 1000, 2000, 500), 900_000);
+        assert_eq!(current_weight(900_000, 500_000, 1000, 2000, 1250), 800_000);
+        assert_eq!(current_weight(900_000, 500_000, 1000, 2000, 2000), 500_000);
+        assert_eq!(current_weight(100_000, 500_000, 1000, 2000, 1500), 300_000);
+        // static weights
+        assert_eq!(current_weight(800_000, 800_000, 0, 0, 1500), 800_000);
+    }
+
+    #[test]
+    fn log2_and_exp2() {
+        assert_eq!(log2(ONE), Some(0));
+        assert_eq!(log2(ONE * 8), Some(3 << 64));
+        assert_eq!(log2(ONE / 4), Some(-2 << 64));
+        assert_eq!(exp2(0), Some(ONE));
+        assert_eq!(exp2(-1 << 64), Some(ONE / 2));
+        assert!(exp2(64 << 64).is_none());
+        let sqrt_2 = exp2(1 << 63).unwrap();
+        assert!((to_f64(sqrt_2) - std::f64::consts::SQRT_2).abs() < 1e-15);
+    }
+
+    #[test]
+    fn even_weights_match_constant_product() {
+        let curve = WeightedCurve::new(WEIGHT_DENOMINATOR / 2);
+        let weighted_out = curve
+            .swap_base_input_without_fees(100_000, 1_000_000, 2_000_000, TradeDirection::ZeroForOne)
+            .unwrap();
+        let cp_out =
+            ConstantProductCurve::swap_base_input_without_fees(100_000, 1_000_000, 2_000_000);
+        assert!(weighted_out <= cp_out);
+        assert!(cp_out - weighted_out <= 1);
+        assert_eq!(
+            curve.initial_lp_tokens(4_000_000, 1_000_000),
+            Some(1_999_999)
+        );
+    }
+
+    #[test]
+    fn eighty_twenty_pool() {
+        // 80/20 pool priced at 1:1, so reserves are 4:1
+        let curve = WeightedCurve::new(800_000);
+        let zero_for_one = curve
+            .swap_base_input_without_fees(10_000, 4_000_000, 1_000_000, TradeDirection::ZeroForOne)
+            .unwrap();
+        let one_for_zero = curve
+            .swap_base_input_without_fees(10_000, 1_000_000, 4_000_000, TradeDirection::OneForZero)
+            .unwrap();
+        // 1e6 * (1 - (4e6 / 4.01e6)^4) = 9937.81
+        assert_eq!(zero_for_one, 9937);
+        // 4e6 * (1 - (1e6 / 1.01e6)^0.25) = 9937.96
+        assert_eq!(one_for_zero, 9937);
+        assert_eq!(
+            curve.initial_lp_tokens(4_000_000, 1_000_000),
+            Some(3_031_433)
+        );
+    }
+
+    #[test]
+    fn eighty_twenty_spot_price() {
+        let curve = WeightedCurve::new(800_000);
+        assert_eq!(
+            spot_price_x32(&curve, 4_000_000, 1_000_000),
+            Some((1 << 32, 1 << 32))
+        );
+    }
+
+    proptest! {
+        #[test]
+        fn swap_base_input_keeps_invariant(
+            weight_0 in MIN_WEIGHT..=WEIGHT_DENOMINATOR - MIN_WEIGHT,
+            source_amount in 1..u64::MAX as u128,
+            swap_source_amount in 1..u64::MAX as u128,
+            swap_destination_amount in 1..u64::MAX as u128,
+            zero_for_one in any::<bool>(),
+        ) {
+            let curve = WeightedCurve::new(weight_0);
+            let trade_direction = if zero_for_one {
+                TradeDirection::ZeroForOne
+            } else {
+                TradeDirection::OneForZero
+            };
+            let amount_out = curve
+                .swap_base_input_without_fees(
+                    source_amount,
+                    swap_source_amount,
+                    swap_destination_amount,
+                    trade_direction,
+                )
+                .unwrap();
+            prop_assert!(amount_out < swap_destination_amount);
+            prop_assert!(curve.check_invariant(
+                swap_source_amount,
+                swap_destination_amount,
+                swap_source_amount + source_amount,
+                swap_destination_amount - amount_out,
+                trade_direction,
+            ));
+        }
+
+        #[test]
+        fn swap_base_output_keeps_invariant(
+            weight_0 in MIN_WEIGHT..=WEIGHT_DENOMINATOR - MIN_WEIGHT,
+            destination_amount in 1..u32::MAX as u128,
+            swap_source_amount in 1..u32::MAX as u128,
+            swap_destination_amount in u32::MAX as u128..u64::MAX as u128,
+            zero_for_one in any::<bool>(),
+        ) {
+            let curve = WeightedCurve::new(weight_0);
+            let trade_direction = if zero_for_one {
+                TradeDirection::ZeroForOne
+            } else {
+                TradeDirection::OneForZero
+            };
+            let source_amount = curve
+                .swap_base_output_without_fees(
+                    destination_amount,
+                    swap_source_amount,
+                    swap_destination_amount,
+                    trade_direction,
+                )
+                .unwrap();
+            prop_assert!(curve.check_invariant(
+                swap_source_amount,
+                swap_destination_amount,
+                swap_source_amount + source_amount,
+                swap_destination_amount - destination_amount,
+                trade_direction,
+            ));
+        }
+
+        #[test]
+        fn pow_matches_float(
+            base in 1..ONE,
+            exponent in ONE / 100..ONE * 99,
+        ) {
+            let expected = to_f64(base).powf(to_f64(exponent));
+            let actual = to_f64(pow(base, exponent).unwrap());
+            prop_assert!((actual - expected).abs() <= expected * 1e-12 + 1e-18);
+        }
+    }
+}
@@ -36,4 +36,64 @@ pub enum ErrorCode {
     NoPendingFee,
     #[msg("Tax disabled")]
     TaxDisabled,
+    #[msg("Invalid pool type for this instruction")]
+    InvalidPoolType,
+    #[msg("Tick out of range or not aligned to tick spacing")]
+    InvalidTickIndex,
+    #[msg("Invalid tick array account")]
+    InvalidTickArray,
+    #[msg("Not enough tick array accounts to complete the swap")]
+    NotEnoughTickArrayAccount,
+    #[msg("Square root price limit overflow")]
+    SqrtPriceLimitOverflow,
+    #[msg("Liquidity overflow")]
+    LiquidityOverflow,
+    #[msg("Max token overflow")]
+    MaxTokenOverflow,
+    #[msg("Position still has liquidity or unclaimed fees")]
+    ClosePositionErr,
+    #[msg("Invalid curve parameters")]
+    InvalidCurveParams,
+    #[msg("Invalid amplification coefficient ramp")]
+    InvalidAmpRamp,
+    #[msg("Swap would decrease the curve invariant")]
+    InvariantViolated,
+    #[msg("Bonding curve has completed")]
+    BondingCurveComplete,
+    #[msg("Bonding curve has not completed")]
+    BondingCurveNotComplete,
+    #[msg("Observations do not cover the requested window")]
+    ObservationNotFound,
+    #[msg("Swap exceeds the max transaction amount")]
+    ExceededMaxTransaction,
+    #[msg("Swap exceeds the max wallet amount")]
+    ExceededMaxWallet,
+    #[msg("Invalid tax recipients")]
+    InvalidTaxRecipients,
+    #[msg("Tax burn is not enabled")]
+    TaxBurnNotEnabled,
+    #[msg("Tax rate exceeds the max tax rate")]
+    ExceededMaxTaxRate,
+    #[msg("No pending tax change")]
+    NoPendingTaxChange,
+    #[msg("Tax change is still timelocked")]
+    TaxChangeNotReady,
+    #[msg("Invalid fee discount tiers")]
+    InvalidFeeDiscountTiers,
+    #[msg("Unstake exceeds the staked amount")]
+    ExceededStakedAmount,
+    #[msg("Invalid fee buckets")]
+    InvalidFeeBuckets,
+    #[msg("The fee schedule of the amm config is missing")]
+    MissingFeeSchedule,
+    #[msg("Creator fee rate exceeds the max creator fee rate")]
+    ExceededMaxCreatorFeeRate,
+    #[msg("Recipient is not the associated token account of the fee owner")]
+    InvalidFeeRecipient,
+    #[msg("A payer can not refer its own swap")]
+    InvalidReferrer,
+    #[msg("The pair has a bonding curve, its pool is created by graduation")]
+    PairHasBondingCurve,
+    #[msg("The pair already has a pool")]
+    PairHasPool,
 }
@@ -0,0 +1,201 @@
+use crate::error::ErrorCode;
+use crate::states::*;
+use crate::utils::token::*;
+use anchor_lang::prelude::*;
+use anchor_spl::token::Token;
+use anchor_spl::token_interface::Mint;
+use anchor_spl::token_interface::Token2022;
+use anchor_spl::token_interface::TokenAccount;
+
+#[derive(Accounts)]
+pub struct CollectFees<'info> {
+    /// Only admin or the owner of the bucket can collect its fees, only the
+    /// pool creator the fees of the creator bucket
+    pub owner: Signer<'info>,
+
+    /// CHECK: pool vault and lp mint authority
+    #[account(
+        seeds = [
+            crate::AUTH_SEED.as_bytes(),
+        ],
+        bump,
+    )]
+    pub authority: UncheckedAccount<'info>,
+
+    /// Pool state stores accumulated fee amounts
+    #[account(mut)]
+    pub pool_state: AccountLoader<'info, PoolState>,
+
+    /// Amm config account stores the protocol and fund owners
+    #[account(address = pool_state.load()?.amm_config)]
+    pub amm_config: Account<'info, AmmConfig>,
+
+    /// The address that holds pool tokens for token_0
+    #[account(
+        mut,
+        constraint = token_0_vault.key() == pool_state.load()?.token_0_vault
+    )]
+    pub token_0_vault: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    /// The address that holds pool tokens for token_1
+    #[account(
+        mut,
+        constraint = token_1_vault.key() == pool_state.load()?.token_1_vault
+    )]
+    pub token_1_vault: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    /// The mint of token_0 vault
+    #[account(
+        address = token_0_vault.mint
+    )]
+    pub vault_0_mint: Box<InterfaceAccount<'info, Mint>>,
+
+    /// The mint of token_1 vault
+    #[account(
+        address = token_1_vault.mint
+    )]
+    pub vault_1_mint: Box<InterfaceAccount<'info, Mint>>,
+
+    /// The address that receives the collected token_0 fees
+    #[account(mut)]
+    pub recipient_token_0_account: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    /// The address that receives the collected token_1 fees
+    #[account(mut)]
+    pub recipient_token_1_account: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    /// The SPL program to perform token transfers
+    pub token_program: Program<'info, Token>,
+
+    /// The SPL program 2022 to perform token transfers
+    pub token_program_2022: Program<'info, Token2022>,
+
+    /// Fee schedule of the amm config, holds the owners of the buckets after
+    /// the protocol and fund buckets
+    #[account(
+        seeds = [
+            FEE_SCHEDULE_SEED.as_bytes(),
+            amm_config.key().as_ref(),
+        ],
+        bump = fee_schedule.bump,
+    )]
+    pub fee_schedule: Option<Box<Account<'info, FeeSchedule>>>,
+}
+
+pub fn collect_fees(
+    ctx: Context<CollectFees>,
+    bucket: u8,
+    amount_0_requested: u64,
+    amount_1_requested: u64,
+) -> Result<()> {
+    let bucket_owner = fee_bucket_owner(
+        &ctx.accounts.pool_state,
+        &ctx.accounts.amm_config,
+        ctx.accounts
+            .fee_schedule
+            .as_deref()
+            .map(|fee_schedule| &**fee_schedule),
+        bucket,
+    )?;
+    let owner = ctx.accounts.owner.key();
+    require!(
+        owner == bucket_owner || (owner == crate::admin::id() && bucket != CREATOR_FEE_BUCKET),
+        ErrorCode::InvalidOwner
+    );
+
+    collect_bucket_fees(
+        &ctx.accounts.pool_state,
+        ctx.accounts.authority.to_account_info(),
+        &ctx.accounts.token_0_vault,
+        &ctx.accounts.token_1_vault,
+        &ctx.accounts.vault_0_mint,
+        &ctx.accounts.vault_1_mint,
+        &ctx.accounts.recipient_token_0_account,
+        &ctx.accounts.recipient_token_1_account,
+        &ctx.accounts.token_program,
+        &ctx.accounts.token_program_2022,
+        bucket,
+        amount_0_requested,
+        amount_1_requested,
+    )
+}
+
+/// Owner of a fee bucket of the pool
+pub fn fee_bucket_owner(
+    pool_state: &AccountLoader<PoolState>,
+    amm_config: &AmmConfig,
+    fee_schedule: Option<&FeeSchedule>,
+    bucket: u8,
+) -> Result<Pubkey> {
+    Ok(pool_state
+        .load()?
+        .fee_bucket_owner(amm_config, fee_schedule, bucket)
+        .ok_or(ErrorCode::InvalidFeeBuckets)?)
+}
+
+/// Pay out up to the requested amounts of the fees accrued to a bucket
+pub fn collect_bucket_fees<'info>(
+    pool_state: &AccountLoader<'info, PoolState>,
+    authority: AccountInfo<'info>,
+    token_0_vault: &InterfaceAccount<'info, TokenAccount>,
+    token_1_vault: &InterfaceAccount<'info, TokenAccount>,
+    vault_0_mint: &InterfaceAccount<'info, Mint>,
+    vault_1_mint: &InterfaceAccount<'info, Mint>,
+    recipient_token_0_account: &InterfaceAccount<'info, TokenAccount>,
+    recipient_token_1_account: &InterfaceAccount<'info, TokenAccount>,
+    token_program: &Program<'info, Token>,
+    token_program_2022: &Program<'info, Token2022>,
+    bucket: u8,
+    amount_0_requested: u64,
+    amount_1_requested: u64,
+) -> Result<()> {
+    let amount_0: u64;
+    let amount_1: u64;
+    let auth_bump: u8;
+    {
+        let mut pool_state = pool_state.load_mut()?;
+
+        (amount_0, amount_1) =
+            pool_state.take_bucket_fees(bucket, amount_0_requested, amount_1_requested);
+
+        require!(amount_0 > 0 || amount_1 > 0, ErrorCode::NoPendingFee);
+
+        auth_bump = pool_state.auth_bump;
+    }
+
+    if amount_0 > 0 {
+        transfer_from_pool_vault_to_user(
+            authority.clone(),
+            token_0_vault.to_account_info(),
+            recipient_token_0_account.to_account_info(),
+            vault_0_mint.to_account_info(),
+            if vault_0_mint.to_account_info().owner == token_program.key {
+                token_program.to_account_info()
+            } else {
+                token_program_2022.to_account_info()
+            },
+            amount_0,
+            vault_0_mint.decimals,
+            &[&[crate::AUTH_SEED.as_bytes(), &[auth_bump]]],
+        )?;
+    }
+
+    if amount_1 > 0 {
+        transfer_from_pool_vault_to_user(
+            authority,
+            token_1_vault.to_account_info(),
+            recipient_token_1_account.to_account_info(),
+            vault_1_mint.to_account_info(),
+            if vault_1_mint.to_account_info().owner == token_program.key {
+                token_program.to_account_info()
+            } else {
+                token_program_2022.to_account_info()
+            },
+            amount_1,
+            vault_1_mint.decimals,
+            &[&[crate::AUTH_SEED.as_bytes(), &[auth_bump]]],
+        )?;
+    }
+
+    Ok(())
+}
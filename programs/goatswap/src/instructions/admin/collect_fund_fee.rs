@@ -1,6 +1,6 @@
 use crate::error::ErrorCode;
+use crate::instructions::collect_bucket_fees;
 use crate::states::*;
-use crate::utils::token::*;
 use anchor_lang::prelude::*;
 use anchor_spl::token::Token;
 use anchor_spl::token_interface::Mint;
@@ -75,54 +75,19 @@ pub fn collect_fund_fee(
     amount_0_requested: u64,
     amount_1_requested: u64,
 ) -> Result<()> {
-    let amount_0: u64;
-    let amount_1: u64;
-    let auth_bump: u8;
-    {
-        let mut pool_state = ctx.accounts.pool_state.load_mut()?;
-        amount_0 = amount_0_requested.min(pool_state.fund_fees_token_0);
-        amount_1 = amount_1_requested.min(pool_state.fund_fees_token_1);
-
-        require!(amount_0 > 0 || amount_1 > 0, ErrorCode::NoPendingFee);
-
-        pool_state.fund_fees_token_0 = pool_state.fund_fees_token_0.checked_sub(amount_0).unwrap();
-        pool_state.fund_fees_token_1 = pool_state.fund_fees_token_1.checked_sub(amount_1).unwrap();
-        auth_bump = pool_state.auth_bump;
-    }
-
-    if amount_0 > 0 {
-        transfer_from_pool_vault_to_user(
-            ctx.accounts.authority.to_account_info(),
-            ctx.accounts.token_0_vault.to_account_info(),
-            ctx.accounts.recipient_token_0_account.to_account_info(),
-            ctx.accounts.vault_0_mint.to_account_info(),
-            if ctx.accounts.vault_0_mint.to_account_info().owner == ctx.accounts.token_program.key {
-                ctx.accounts.token_program.to_account_info()
-            } else {
-                ctx.accounts.token_program_2022.to_account_info()
-            },
-            amount_0,
-            ctx.accounts.vault_0_mint.decimals,
-            &[&[crate::AUTH_SEED.as_bytes(), &[auth_bump]]],
-        )?;
-    }
-
-    if amount_1 > 0 {
-        transfer_from_pool_vault_to_user(
-            ctx.accounts.authority.to_account_info(),
-            ctx.accounts.token_1_vault.to_account_info(),
-            ctx.accounts.recipient_token_1_account.to_account_info(),
-            ctx.accounts.vault_1_mint.to_account_info(),
-            if ctx.accounts.vault_1_mint.to_account_info().owner == ctx.accounts.token_program.key {
-                ctx.accounts.token_program.to_account_info()
-            } else {
-                ctx.accounts.token_program_2022.to_account_info()
-            },
-            amount_1,
-            ctx.accounts.vault_1_mint.decimals,
-            &[&[crate::AUTH_SEED.as_bytes(), &[auth_bump]]],
-        )?;
-    }
-
-    Ok(())
+    collect_bucket_fees(
+        &ctx.accounts.pool_state,
+        ctx.accounts.authority.to_account_info(),
+        &ctx.accounts.token_0_vault,
+        &ctx.accounts.token_1_vault,
+        &ctx.accounts.vault_0_mint,
+        &ctx.accounts.vault_1_mint,
+        &ctx.accounts.recipient_token_0_account,
+        &ctx.accounts.recipient_token_1_account,
+        &ctx.accounts.token_program,
+        &ctx.accounts.token_program_2022,
+        FUND_FEE_BUCKET,
+        amount_0_requested,
+        amount_1_requested,
+    )
 }
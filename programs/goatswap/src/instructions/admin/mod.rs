@@ -12,3 +12,15 @@ pub use collect_protocol_fee::*;
 
 pub mod collect_fund_fee;
 pub use collect_fund_fee::*;
+
+pub mod collect_fees;
+pub use collect_fees::*;
+
+pub mod sweep_fees;
+pub use sweep_fees::*;
+
+pub mod update_fee_schedule;
+pub use update_fee_schedule::*;
+
+pub mod ramp_amp;
+pub use ramp_amp::*;
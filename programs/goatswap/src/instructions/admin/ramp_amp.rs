@@ -0,0 +1,76 @@
+use super::UpdatePoolStatus;
+use crate::curve::stable_swap::*;
+use crate::error::ErrorCode;
+use crate::states::*;
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program;
+
+pub fn ramp_amp(
+    ctx: Context<UpdatePoolStatus>,
+    target_amp: u64,
+    ramp_stop_time: u64,
+) -> Result<()> {
+    let block_timestamp = solana_program::clock::Clock::get()?.unix_timestamp as u64;
+    let mut pool_state = ctx.accounts.pool_state.load_mut()?;
+    require_eq!(
+        pool_state.curve_type,
+        CurveType::StableSwap as u8,
+        ErrorCode::InvalidPoolType
+    );
+    require!(
+        (MIN_AMP..=MAX_AMP).contains(&target_amp),
+        ErrorCode::InvalidCurveParams
+    );
+    // a new ramp can only start a while after the previous one, and must last long enough
+    require!(
+        block_timestamp >= pool_state.amp_ramp_start_time + MIN_RAMP_DURATION
+            && ramp_stop_time >= block_timestamp + MIN_RAMP_DURATION,
+        ErrorCode::InvalidAmpRamp
+    );
+
+    let current_amp = pool_state.amp(block_timestamp);
+    if target_amp > current_amp {
+        require_gte!(
+            current_amp * MAX_AMP_CHANGE,
+            target_amp,
+            ErrorCode::InvalidAmpRamp
+        );
+    } else {
+        require_gte!(
+            target_amp * MAX_AMP_CHANGE,
+            current_amp,
+            ErrorCode::InvalidAmpRamp
+        );
+    }
+
+    #[cfg(feature = "enable-log")]
+    msg!(
+        "ramp_amp, current_amp:{}, target_amp:{}, ramp_stop_time:{}",
+        current_amp,
+        target_amp,
+        ramp_stop_time
+    );
+
+    pool_state.amp_initial = current_amp;
+    pool_state.amp_target = target_amp;
+    pool_state.amp_ramp_start_time = block_timestamp;
+    pool_state.amp_ramp_stop_time = ramp_stop_time;
+    Ok(())
+}
+
+pub fn stop_ramp_amp(ctx: Context<UpdatePoolStatus>) -> Result<()> {
+    let block_timestamp = solana_program::clock::Clock::get()?.unix_timestamp as u64;
+    let mut pool_state = ctx.accounts.pool_state.load_mut()?;
+    require_eq!(
+        pool_state.curve_type,
+        CurveType::StableSwap as u8,
+        ErrorCode::InvalidPoolType
+    );
+
+    let current_amp = pool_state.amp(block_timestamp);
+    pool_state.amp_initial = current_amp;
+    pool_state.amp_target = current_amp;
+    pool_state.amp_ramp_start_time = block_timestamp;
+    pool_state.amp_ramp_stop_time = block_timestamp;
+    Ok(())
+}
@@ -0,0 +1,133 @@
+use crate::error::ErrorCode;
+use crate::instructions::collect_bucket_fees;
+use crate::states::*;
+use anchor_lang::prelude::*;
+use anchor_spl::token::Token;
+use anchor_spl::token_interface::Mint;
+use anchor_spl::token_interface::Token2022;
+use anchor_spl::token_interface::TokenAccount;
+
+#[derive(Accounts)]
+pub struct SweepFees<'info> {
+    /// CHECK: pool vault and lp mint authority
+    #[account(
+        seeds = [
+            crate::AUTH_SEED.as_bytes(),
+        ],
+        bump,
+    )]
+    pub authority: UncheckedAccount<'info>,
+
+    /// Pool state stores accumulated fee amounts
+    #[account(mut)]
+    pub pool_state: AccountLoader<'info, PoolState>,
+
+    /// Amm config account stores the protocol and fund owners
+    #[account(address = pool_state.load()?.amm_config)]
+    pub amm_config: Account<'info, AmmConfig>,
+
+    /// The address that holds pool tokens for token_0
+    #[account(
+        mut,
+        constraint = token_0_vault.key() == pool_state.load()?.token_0_vault
+    )]
+    pub token_0_vault: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    /// The address that holds pool tokens for token_1
+    #[account(
+        mut,
+        constraint = token_1_vault.key() == pool_state.load()?.token_1_vault
+    )]
+    pub token_1_vault: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    /// The mint of token_0 vault
+    #[account(
+        address = token_0_vault.mint
+    )]
+    pub vault_0_mint: Box<InterfaceAccount<'info, Mint>>,
+
+    /// The mint of token_1 vault
+    #[account(
+        address = token_1_vault.mint
+    )]
+    pub vault_1_mint: Box<InterfaceAccount<'info, Mint>>,
+
+    /// The token_0 associated token account of the bucket owner
+    #[account(mut)]
+    pub recipient_token_0_account: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    /// The token_1 associated token account of the bucket owner
+    #[account(mut)]
+    pub recipient_token_1_account: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    /// The SPL program to perform token transfers
+    pub token_program: Program<'info, Token>,
+
+    /// The SPL program 2022 to perform token transfers
+    pub token_program_2022: Program<'info, Token2022>,
+
+    /// Fee schedule of the amm config, holds the owners of the buckets after
+    /// the protocol and fund buckets
+    #[account(
+        seeds = [
+            FEE_SCHEDULE_SEED.as_bytes(),
+            amm_config.key().as_ref(),
+        ],
+        bump = fee_schedule.bump,
+    )]
+    pub fee_schedule: Option<Box<Account<'info, FeeSchedule>>>,
+}
+
+pub fn sweep_fees(ctx: Context<SweepFees>, bucket: u8) -> Result<()> {
+    let fee_schedule = ctx
+        .accounts
+        .fee_schedule
+        .as_deref()
+        .map(|fee_schedule| &**fee_schedule);
+
+    // anyone can sweep, so the fees only go to the owner's own accounts
+    {
+        let pool_state = ctx.accounts.pool_state.load()?;
+        for (recipient, mint) in [
+            (
+                &ctx.accounts.recipient_token_0_account,
+                &ctx.accounts.vault_0_mint,
+            ),
+            (
+                &ctx.accounts.recipient_token_1_account,
+                &ctx.accounts.vault_1_mint,
+            ),
+        ] {
+            let fee_recipient = pool_state
+                .fee_recipient(
+                    &ctx.accounts.amm_config,
+                    fee_schedule,
+                    bucket,
+                    &mint.key(),
+                    mint.to_account_info().owner,
+                )
+                .ok_or(ErrorCode::InvalidFeeBuckets)?;
+            require_keys_eq!(
+                recipient.key(),
+                fee_recipient,
+                ErrorCode::InvalidFeeRecipient
+            );
+        }
+    }
+
+    collect_bucket_fees(
+        &ctx.accounts.pool_state,
+        ctx.accounts.authority.to_account_info(),
+        &ctx.accounts.token_0_vault,
+        &ctx.accounts.token_1_vault,
+        &ctx.accounts.vault_0_mint,
+        &ctx.accounts.vault_1_mint,
+        &ctx.accounts.recipient_token_0_account,
+        &ctx.accounts.recipient_token_1_account,
+        &ctx.accounts.token_program,
+        &ctx.accounts.token_program_2022,
+        bucket,
+        u64::MAX,
+        u64::MAX,
+    )
+}
@@ -30,16 +30,44 @@ pub fn update_amm_config(ctx: Context<UpdateAmmConfig>, param: u8, value: u64) -
             set_new_fund_owner(amm_config, new_fund_owner)?;
         }
         Some(5) => amm_config.create_pool_fee = value,
-        Some(6) => amm_config.disable_create_pool = if value == 0 { false } else { true },
+        Some(6) => amm_config.disable_create_pool = value != 0,
+        Some(7) => update_max_tax_rate(amm_config, value),
+        Some(8) => amm_config.tax_increase_delay = value,
+        Some(9) => update_referral_fee_rate(amm_config, value),
+        Some(10) => update_max_creator_fee_rate(amm_config, value),
         _ => return err!(ErrorCode::InvalidInput),
     }
 
     Ok(())
 }
 
+pub fn update_fee_discount(
+    ctx: Context<UpdateAmmConfig>,
+    fee_discount_mint: Pubkey,
+    tiers: Vec<FeeDiscountTier>,
+) -> Result<()> {
+    require!(
+        tiers.len() <= MAX_FEE_DISCOUNT_TIERS
+            && tiers
+                .iter()
+                .all(|tier| tier.discount_rate <= FEE_RATE_DENOMINATOR_VALUE),
+        ErrorCode::InvalidFeeDiscountTiers
+    );
+
+    let amm_config = &mut ctx.accounts.amm_config;
+    amm_config.fee_discount_mint = fee_discount_mint;
+    amm_config.fee_discount_tiers = [FeeDiscountTier::default(); MAX_FEE_DISCOUNT_TIERS];
+    amm_config.fee_discount_tiers[..tiers.len()].copy_from_slice(&tiers);
+
+    Ok(())
+}
+
 fn update_protocol_fee_rate(amm_config: &mut Account<AmmConfig>, protocol_fee_rate: u64) {
     assert!(protocol_fee_rate <= FEE_RATE_DENOMINATOR_VALUE);
-    assert!(protocol_fee_rate + amm_config.fund_fee_rate <= FEE_RATE_DENOMINATOR_VALUE);
+    assert!(
+        amm_config.fee_share_rate() - amm_config.protocol_fee_rate + protocol_fee_rate
+            <= FEE_RATE_DENOMINATOR_VALUE
+    );
     amm_config.protocol_fee_rate = protocol_fee_rate;
 }
 
@@ -50,10 +78,36 @@ fn update_trade_fee_rate(amm_config: &mut Account<AmmConfig>, trade_fee_rate: u6
 
 fn update_fund_fee_rate(amm_config: &mut Account<AmmConfig>, fund_fee_rate: u64) {
     assert!(fund_fee_rate <= FEE_RATE_DENOMINATOR_VALUE);
-    assert!(fund_fee_rate + amm_config.protocol_fee_rate <= FEE_RATE_DENOMINATOR_VALUE);
+    assert!(
+        amm_config.fee_share_rate() - amm_config.fund_fee_rate + fund_fee_rate
+            <= FEE_RATE_DENOMINATOR_VALUE
+    );
     amm_config.fund_fee_rate = fund_fee_rate;
 }
 
+fn update_referral_fee_rate(amm_config: &mut Account<AmmConfig>, referral_fee_rate: u64) {
+    assert!(referral_fee_rate <= FEE_RATE_DENOMINATOR_VALUE);
+    assert!(
+        amm_config.fee_share_rate() - amm_config.referral_fee_rate + referral_fee_rate
+            <= FEE_RATE_DENOMINATOR_VALUE
+    );
+    amm_config.referral_fee_rate = referral_fee_rate;
+}
+
+fn update_max_creator_fee_rate(amm_config: &mut Account<AmmConfig>, max_creator_fee_rate: u64) {
+    assert!(max_creator_fee_rate <= FEE_RATE_DENOMINATOR_VALUE);
+    assert!(
+        amm_config.fee_share_rate() - amm_config.max_creator_fee_rate + max_creator_fee_rate
+            <= FEE_RATE_DENOMINATOR_VALUE
+    );
+    amm_config.max_creator_fee_rate = max_creator_fee_rate;
+}
+
+fn update_max_tax_rate(amm_config: &mut Account<AmmConfig>, max_tax_rate: u64) {
+    assert!(max_tax_rate <= FEE_RATE_DENOMINATOR_VALUE);
+    amm_config.max_tax_rate = max_tax_rate;
+}
+
 fn set_new_protocol_owner(amm_config: &mut Account<AmmConfig>, new_owner: Pubkey) -> Result<()> {
     require_keys_neq!(new_owner, Pubkey::default());
     #[cfg(feature = "enable-log")]
@@ -0,0 +1,99 @@
+use crate::curve::fees::FEE_RATE_DENOMINATOR_VALUE;
+use crate::error::ErrorCode;
+use crate::states::*;
+use anchor_lang::prelude::*;
+
+#[derive(Accounts)]
+pub struct UpdateFeeSchedule<'info> {
+    /// The amm config admin, pays for the fee schedule account
+    #[account(
+        mut,
+        address = crate::admin::id() @ ErrorCode::InvalidOwner
+    )]
+    pub owner: Signer<'info>,
+
+    /// Amm config account the buckets belong to
+    #[account(mut)]
+    pub amm_config: Account<'info, AmmConfig>,
+
+    /// Initialize an account to store the buckets if needed
+    #[account(
+        init_if_needed,
+        seeds = [
+            FEE_SCHEDULE_SEED.as_bytes(),
+            amm_config.key().as_ref(),
+        ],
+        bump,
+        payer = owner,
+        space = FeeSchedule::LEN
+    )]
+    pub fee_schedule: Box<Account<'info, FeeSchedule>>,
+
+    pub system_program: Program<'info, System>,
+}
+
+pub fn update_fee_schedule(ctx: Context<UpdateFeeSchedule>, buckets: Vec<FeeBucket>) -> Result<()> {
+    let amm_config = &mut ctx.accounts.amm_config;
+    let fee_schedule_rate =
+        FeeSchedule::total_fee_rate(&buckets).ok_or(ErrorCode::InvalidFeeBuckets)?;
+    require!(
+        buckets.len() <= MAX_SCHEDULED_FEE_BUCKETS
+            && amm_config.fee_share_rate() - amm_config.fee_schedule_rate + fee_schedule_rate
+                <= FEE_RATE_DENOMINATOR_VALUE,
+        ErrorCode::InvalidFeeBuckets
+    );
+
+    let fee_schedule = &mut ctx.accounts.fee_schedule;
+    require!(
+        fee_schedule.keeps_buckets(&buckets),
+        ErrorCode::InvalidFeeBuckets
+    );
+    fee_schedule.bump = ctx.bumps.fee_schedule;
+    fee_schedule.amm_config = amm_config.key();
+    fee_schedule.set(&buckets);
+    amm_config.fee_schedule_rate = fee_schedule_rate;
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct TransferFeeBucket<'info> {
+    /// The current owner of the bucket
+    pub owner: Signer<'info>,
+
+    /// Amm config account the buckets belong to
+    pub amm_config: Account<'info, AmmConfig>,
+
+    /// Fee schedule holding the bucket
+    #[account(
+        mut,
+        seeds = [
+            FEE_SCHEDULE_SEED.as_bytes(),
+            amm_config.key().as_ref(),
+        ],
+        bump = fee_schedule.bump,
+    )]
+    pub fee_schedule: Box<Account<'info, FeeSchedule>>,
+}
+
+/// Hand a bucket of the fee schedule, and the fees pools owe it, to a new owner
+pub fn transfer_fee_bucket(
+    ctx: Context<TransferFeeBucket>,
+    bucket: u8,
+    new_owner: Pubkey,
+) -> Result<()> {
+    let fee_schedule = &mut ctx.accounts.fee_schedule;
+    let index = usize::from(bucket.checked_sub(2).ok_or(ErrorCode::InvalidFeeBuckets)?);
+    require!(
+        index < fee_schedule.buckets().len(),
+        ErrorCode::InvalidFeeBuckets
+    );
+    require_keys_eq!(
+        fee_schedule.buckets[index].owner,
+        ctx.accounts.owner.key(),
+        ErrorCode::InvalidOwner
+    );
+    fee_schedule.buckets[index].owner = new_owner;
+
+    Ok(())
+}
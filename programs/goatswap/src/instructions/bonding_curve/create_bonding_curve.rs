@@ -0,0 +1,220 @@
+use crate::curve::FEE_RATE_DENOMINATOR_VALUE;
+use crate::error::ErrorCode;
+use crate::states::*;
+use crate::utils::*;
+use anchor_lang::{accounts::interface_account::InterfaceAccount, prelude::*};
+use anchor_spl::token_interface::{Mint, TokenAccount, TokenInterface};
+
+#[derive(Accounts)]
+pub struct CreateBondingCurve<'info> {
+    /// Address creating the sale and providing the tokens sold
+    #[account(mut)]
+    pub creator: Signer<'info>,
+
+    /// Which config the graduated pool belongs to.
+    pub amm_config: Box<Account<'info, AmmConfig>>,
+
+    /// CHECK: bonding curve vault authority
+    #[account(
+        seeds = [
+            crate::AUTH_SEED.as_bytes(),
+        ],
+        bump,
+    )]
+    pub authority: UncheckedAccount<'info>,
+
+    /// Initialize an account to store the bonding curve state
+    #[account(
+        init,
+        seeds = [
+            BONDING_CURVE_SEED.as_bytes(),
+            amm_config.key().as_ref(),
+            token_mint.key().as_ref(),
+            quote_mint.key().as_ref(),
+        ],
+        bump,
+        payer = creator,
+        space = BondingCurveState::LEN
+    )]
+    pub bonding_curve: Box<Account<'info, BondingCurveState>>,
+
+    /// The mint of the token sold
+    #[account(
+        constraint = token_mint.key() != quote_mint.key(),
+        mint::token_program = token_mint_program,
+    )]
+    pub token_mint: Box<InterfaceAccount<'info, Mint>>,
+
+    /// The mint of the token paid
+    #[account(
+        mint::token_program = quote_mint_program,
+    )]
+    pub quote_mint: Box<InterfaceAccount<'info, Mint>>,
+
+    /// CHECK: the pool of the pair under the config, which graduation creates
+    /// so it must not exist
+    pub pool_state: UncheckedAccount<'info>,
+
+    /// creator token account, paying the tokens for sale and for the pool
+    #[account(
+        mut,
+        token::mint = token_mint,
+        token::authority = creator,
+    )]
+    pub creator_token: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    /// CHECK: Token vault of the bonding curve
+    #[account(
+        mut,
+        seeds = [
+            BONDING_CURVE_VAULT_SEED.as_bytes(),
+            bonding_curve.key().as_ref(),
+            token_mint.key().as_ref()
+        ],
+        bump,
+    )]
+    pub token_vault: UncheckedAccount<'info>,
+
+    /// CHECK: Quote vault of the bonding curve
+    #[account(
+        mut,
+        seeds = [
+            BONDING_CURVE_VAULT_SEED.as_bytes(),
+            bonding_curve.key().as_ref(),
+            quote_mint.key().as_ref()
+        ],
+        bump,
+    )]
+    pub quote_vault: UncheckedAccount<'info>,
+
+    /// Spl token program or token program 2022
+    pub token_mint_program: Interface<'info, TokenInterface>,
+    /// Spl token program or token program 2022
+    pub quote_mint_program: Interface<'info, TokenInterface>,
+    /// To create a new program account
+    pub system_program: Program<'info, System>,
+    /// Sysvar for program account
+    pub rent: Sysvar<'info, Rent>,
+}
+
+pub fn create_bonding_curve(
+    ctx: Context<CreateBondingCurve>,
+    sale_token_amount: u64,
+    pool_token_amount: u64,
+    virtual_token_reserves: u64,
+    virtual_quote_reserves: u64,
+    graduation_quote_amount: u64,
+    tax_use_token_0: bool,
+    in_tax_rate: u64,
+    out_tax_rate: u64,
+    burn_lp: bool,
+) -> Result<()> {
+    if !(is_supported_mint(&ctx.accounts.token_mint).unwrap()
+        && is_supported_mint(&ctx.accounts.quote_mint).unwrap())
+    {
+        return err!(ErrorCode::NotSupportMint);
+    }
+
+    if ctx.accounts.amm_config.disable_create_pool {
+        return err!(ErrorCode::NotApproved);
+    }
+
+    BondingCurveState::check_pair_has_no_pool(
+        &ctx.accounts.amm_config.key(),
+        &ctx.accounts.token_mint.key(),
+        &ctx.accounts.quote_mint.key(),
+        &ctx.accounts.pool_state,
+    )?;
+
+    require!(
+        sale_token_amount > 0
+            && pool_token_amount > 0
+            && virtual_token_reserves > sale_token_amount
+            && virtual_quote_reserves > 0
+            && graduation_quote_amount > 0,
+        ErrorCode::InvalidInput
+    );
+
+    // check tax config
+    assert!(in_tax_rate <= FEE_RATE_DENOMINATOR_VALUE);
+    assert!(out_tax_rate <= FEE_RATE_DENOMINATOR_VALUE);
+    ctx.accounts.amm_config.check_tax_rate(in_tax_rate)?;
+    ctx.accounts.amm_config.check_tax_rate(out_tax_rate)?;
+
+    // get tax mint, in the order of the graduated pool
+    let token_is_token_0 = ctx.accounts.token_mint.key() < ctx.accounts.quote_mint.key();
+    let tax_mint = if tax_use_token_0 == token_is_token_0 {
+        ctx.accounts.token_mint.key()
+    } else {
+        ctx.accounts.quote_mint.key()
+    };
+
+    let bonding_curve_key = ctx.accounts.bonding_curve.key();
+    create_token_account(
+        &ctx.accounts.authority.to_account_info(),
+        &ctx.accounts.creator.to_account_info(),
+        &ctx.accounts.token_vault.to_account_info(),
+        &ctx.accounts.token_mint.to_account_info(),
+        &ctx.accounts.system_program.to_account_info(),
+        &ctx.accounts.token_mint_program.to_account_info(),
+        &[&[
+            BONDING_CURVE_VAULT_SEED.as_bytes(),
+            bonding_curve_key.as_ref(),
+            ctx.accounts.token_mint.key().as_ref(),
+            &[ctx.bumps.token_vault][..],
+        ][..]],
+    )?;
+
+    create_token_account(
+        &ctx.accounts.authority.to_account_info(),
+        &ctx.accounts.creator.to_account_info(),
+        &ctx.accounts.quote_vault.to_account_info(),
+        &ctx.accounts.quote_mint.to_account_info(),
+        &ctx.accounts.system_program.to_account_info(),
+        &ctx.accounts.quote_mint_program.to_account_info(),
+        &[&[
+            BONDING_CURVE_VAULT_SEED.as_bytes(),
+            bonding_curve_key.as_ref(),
+            ctx.accounts.quote_mint.key().as_ref(),
+            &[ctx.bumps.quote_vault][..],
+        ][..]],
+    )?;
+
+    // calculate token amount with transfer fee
+    let token_amount = sale_token_amount.checked_add(pool_token_amount).unwrap();
+    let transfer_token_amount = {
+        let transfer_fee =
+            get_transfer_inverse_fee(&ctx.accounts.token_mint.to_account_info(), token_amount)?;
+        token_amount.checked_add(transfer_fee).unwrap()
+    };
+
+    transfer_from_user_to_pool_vault(
+        ctx.accounts.creator.to_account_info(),
+        ctx.accounts.creator_token.to_account_info(),
+        ctx.accounts.token_vault.to_account_info(),
+        ctx.accounts.token_mint.to_account_info(),
+        ctx.accounts.token_mint_program.to_account_info(),
+        transfer_token_amount,
+        ctx.accounts.token_mint.decimals,
+    )?;
+
+    let bonding_curve = &mut ctx.accounts.bonding_curve;
+    bonding_curve.bump = ctx.bumps.bonding_curve;
+    bonding_curve.burn_lp = burn_lp;
+    bonding_curve.creator = ctx.accounts.creator.key();
+    bonding_curve.amm_config = ctx.accounts.amm_config.key();
+    bonding_curve.token_mint = ctx.accounts.token_mint.key();
+    bonding_curve.quote_mint = ctx.accounts.quote_mint.key();
+    bonding_curve.token_vault = ctx.accounts.token_vault.key();
+    bonding_curve.quote_vault = ctx.accounts.quote_vault.key();
+    bonding_curve.virtual_token_reserves = virtual_token_reserves;
+    bonding_curve.virtual_quote_reserves = virtual_quote_reserves;
+    bonding_curve.real_token_reserves = sale_token_amount;
+    bonding_curve.pool_token_amount = pool_token_amount;
+    bonding_curve.graduation_quote_amount = graduation_quote_amount;
+    bonding_curve.tax_mint = tax_mint;
+    bonding_curve.in_tax_rate = in_tax_rate;
+    bonding_curve.out_tax_rate = out_tax_rate;
+
+    Ok(())
+}
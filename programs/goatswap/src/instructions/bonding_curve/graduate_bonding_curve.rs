@@ -0,0 +1,360 @@
+use crate::error::ErrorCode;
+use crate::instructions::{
+    create_pool_vault, initial_liquidity, initialize_pool_observation, pool_vault_amounts,
+};
+use crate::states::*;
+use crate::utils::*;
+use anchor_lang::{
+    accounts::interface_account::InterfaceAccount, prelude::*, solana_program::clock,
+};
+use anchor_spl::{
+    token::Token,
+    token_interface::{Mint, TokenAccount, TokenInterface},
+};
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct GraduateBondingCurve<'info> {
+    /// Address paying to create the pool. Can be anyone
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    /// Which config the pool belongs to.
+    #[account(address = bonding_curve.amm_config)]
+    pub amm_config: Box<Account<'info, AmmConfig>>,
+
+    /// CHECK: pool vault and lp mint authority
+    #[account(
+        seeds = [
+            crate::AUTH_SEED.as_bytes(),
+        ],
+        bump,
+    )]
+    pub authority: UncheckedAccount<'info>,
+
+    /// The completed bonding curve
+    #[account(mut)]
+    pub bonding_curve: Box<Account<'info, BondingCurveState>>,
+
+    /// Initialize an account to store the pool state
+    #[account(
+        init,
+        seeds = [
+            POOL_SEED.as_bytes(),
+            amm_config.key().as_ref(),
+            token_0_mint.key().as_ref(),
+            token_1_mint.key().as_ref(),
+        ],
+        bump,
+        payer = payer,
+        space = PoolState::LEN
+    )]
+    pub pool_state: AccountLoader<'info, PoolState>,
+
+    /// Token_0 mint, the key must smaller then token_1 mint.
+    #[account(
+        constraint = token_0_mint.key() < token_1_mint.key(),
+        constraint = token_0_mint.key() == bonding_curve.token_mint || token_0_mint.key() == bonding_curve.quote_mint,
+        mint::token_program = token_0_program,
+    )]
+    pub token_0_mint: Box<InterfaceAccount<'info, Mint>>,
+
+    /// Token_1 mint, the key must grater then token_0 mint.
+    #[account(
+        constraint = token_1_mint.key() == bonding_curve.token_mint || token_1_mint.key() == bonding_curve.quote_mint,
+        mint::token_program = token_1_program,
+    )]
+    pub token_1_mint: Box<InterfaceAccount<'info, Mint>>,
+
+    /// pool lp mint
+    #[account(
+        init,
+        seeds = [
+            POOL_LP_MINT_SEED.as_bytes(),
+            pool_state.key().as_ref(),
+        ],
+        bump,
+        mint::decimals = if token_0_mint.decimals >= token_1_mint.decimals{
+            token_0_mint.decimals
+        }else{
+            token_1_mint.decimals
+        },
+        mint::authority = authority,
+        payer = payer,
+        mint::token_program = token_program,
+    )]
+    pub lp_mint: Box<InterfaceAccount<'info, Mint>>,
+
+    /// Holds the lp tokens of the graduated pool, owned by the program
+    #[account(
+        init,
+        seeds = [
+            BONDING_CURVE_LP_LOCK_SEED.as_bytes(),
+            bonding_curve.key().as_ref(),
+        ],
+        bump,
+        token::mint = lp_mint,
+        token::authority = authority,
+        payer = payer,
+        token::token_program = token_program,
+    )]
+    pub lp_lock: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    /// The token vault of the bonding curve
+    #[account(
+        mut,
+        address = bonding_curve.token_vault
+    )]
+    pub bonding_curve_token_vault: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    /// The quote vault of the bonding curve
+    #[account(
+        mut,
+        address = bonding_curve.quote_vault
+    )]
+    pub bonding_curve_quote_vault: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    /// CHECK: Token_0 vault for the pool
+    #[account(
+        mut,
+        seeds = [
+            POOL_VAULT_SEED.as_bytes(),
+            pool_state.key().as_ref(),
+            token_0_mint.key().as_ref()
+        ],
+        bump,
+    )]
+    pub token_0_vault: UncheckedAccount<'info>,
+
+    /// CHECK: Token_1 vault for the pool
+    #[account(
+        mut,
+        seeds = [
+            POOL_VAULT_SEED.as_bytes(),
+            pool_state.key().as_ref(),
+            token_1_mint.key().as_ref()
+        ],
+        bump,
+    )]
+    pub token_1_vault: UncheckedAccount<'info>,
+
+    /// an account to store oracle observations
+    #[account(
+        init,
+        seeds = [
+            OBSERVATION_SEED.as_bytes(),
+            pool_state.key().as_ref(),
+        ],
+        bump,
+        payer = payer,
+        space = ObservationState::LEN
+    )]
+    pub observation_state: AccountLoader<'info, ObservationState>,
+
+    /// Program to create mint account and mint tokens
+    pub token_program: Program<'info, Token>,
+    /// Spl token program or token program 2022
+    pub token_0_program: Interface<'info, TokenInterface>,
+    /// Spl token program or token program 2022
+    pub token_1_program: Interface<'info, TokenInterface>,
+    /// To create a new program account
+    pub system_program: Program<'info, System>,
+    /// Sysvar for program account
+    pub rent: Sysvar<'info, Rent>,
+}
+
+pub fn graduate_bonding_curve(ctx: Context<GraduateBondingCurve>) -> Result<()> {
+    let bonding_curve = &ctx.accounts.bonding_curve;
+    require!(bonding_curve.complete, ErrorCode::BondingCurveNotComplete);
+    require!(!bonding_curve.graduated, ErrorCode::NotApproved);
+
+    if ctx.accounts.amm_config.disable_create_pool {
+        return err!(ErrorCode::NotApproved);
+    }
+
+    let block_timestamp = clock::Clock::get()?.unix_timestamp as u64;
+    let open_time = block_timestamp + 1;
+
+    // everything left in the curve seeds the pool, together with the taxes
+    // collected on the curve which stay claimable by the tax authority
+    let token_is_token_0 = ctx.accounts.token_0_mint.key() == bonding_curve.token_mint;
+    let (curve_vault_0, curve_vault_1, curve_tax_0, curve_tax_1) = if token_is_token_0 {
+        (
+            &ctx.accounts.bonding_curve_token_vault,
+            &ctx.accounts.bonding_curve_quote_vault,
+            bonding_curve.tax_amount_token,
+            bonding_curve.tax_amount_quote,
+        )
+    } else {
+        (
+            &ctx.accounts.bonding_curve_quote_vault,
+            &ctx.accounts.bonding_curve_token_vault,
+            bonding_curve.tax_amount_quote,
+            bonding_curve.tax_amount_token,
+        )
+    };
+
+    // due to stack/heap limitations, we have to create redundant new accounts ourselves.
+    create_pool_vault(
+        &ctx.accounts.authority.to_account_info(),
+        &ctx.accounts.payer.to_account_info(),
+        &ctx.accounts.pool_state.key(),
+        &ctx.accounts.token_0_vault.to_account_info(),
+        &ctx.accounts.token_0_mint.to_account_info(),
+        &ctx.accounts.token_0_program.to_account_info(),
+        &ctx.accounts.system_program.to_account_info(),
+        ctx.bumps.token_0_vault,
+    )?;
+    create_pool_vault(
+        &ctx.accounts.authority.to_account_info(),
+        &ctx.accounts.payer.to_account_info(),
+        &ctx.accounts.pool_state.key(),
+        &ctx.accounts.token_1_vault.to_account_info(),
+        &ctx.accounts.token_1_mint.to_account_info(),
+        &ctx.accounts.token_1_program.to_account_info(),
+        &ctx.accounts.system_program.to_account_info(),
+        ctx.bumps.token_1_vault,
+    )?;
+
+    let pool_state = &mut ctx.accounts.pool_state.load_init()?;
+
+    transfer_from_pool_vault_to_user(
+        ctx.accounts.authority.to_account_info(),
+        curve_vault_0.to_account_info(),
+        ctx.accounts.token_0_vault.to_account_info(),
+        ctx.accounts.token_0_mint.to_account_info(),
+        ctx.accounts.token_0_program.to_account_info(),
+        curve_vault_0.amount,
+        ctx.accounts.token_0_mint.decimals,
+        &[&[crate::AUTH_SEED.as_bytes(), &[ctx.bumps.authority]]],
+    )?;
+
+    transfer_from_pool_vault_to_user(
+        ctx.accounts.authority.to_account_info(),
+        curve_vault_1.to_account_info(),
+        ctx.accounts.token_1_vault.to_account_info(),
+        ctx.accounts.token_1_mint.to_account_info(),
+        ctx.accounts.token_1_program.to_account_info(),
+        curve_vault_1.amount,
+        ctx.accounts.token_1_mint.decimals,
+        &[&[crate::AUTH_SEED.as_bytes(), &[ctx.bumps.authority]]],
+    )?;
+
+    let (vault_amount_0, vault_amount_1) = pool_vault_amounts(
+        &ctx.accounts.token_0_vault.to_account_info(),
+        &ctx.accounts.token_1_vault.to_account_info(),
+    )?;
+    let tax_amount_0 = std::cmp::min(curve_tax_0, vault_amount_0);
+    let tax_amount_1 = std::cmp::min(curve_tax_1, vault_amount_1);
+    let amount_0 = vault_amount_0 - tax_amount_0;
+    let amount_1 = vault_amount_1 - tax_amount_1;
+
+    let curve_params = CurveParams::ConstantProduct;
+    let (liquidity, lp_amount) = initial_liquidity(
+        &curve_params,
+        amount_0,
+        amount_1,
+        ctx.accounts.lp_mint.decimals,
+    )?;
+
+    token::token_mint_to(
+        ctx.accounts.authority.to_account_info(),
+        ctx.accounts.token_program.to_account_info(),
+        ctx.accounts.lp_mint.to_account_info(),
+        ctx.accounts.lp_lock.to_account_info(),
+        lp_amount,
+        &[&[crate::AUTH_SEED.as_bytes(), &[ctx.bumps.authority]]],
+    )?;
+
+    let bonding_curve = &ctx.accounts.bonding_curve;
+    if bonding_curve.burn_lp {
+        token_burn(
+            ctx.accounts.authority.to_account_info(),
+            ctx.accounts.token_program.to_account_info(),
+            ctx.accounts.lp_mint.to_account_info(),
+            ctx.accounts.lp_lock.to_account_info(),
+            lp_amount,
+            &[&[crate::AUTH_SEED.as_bytes(), &[ctx.bumps.authority]]],
+        )?;
+    }
+
+    let tax_mint = bonding_curve.tax_mint;
+    let in_tax_rate = bonding_curve.in_tax_rate;
+    let out_tax_rate = bonding_curve.out_tax_rate;
+    let creator = bonding_curve.creator;
+    let lp_burnt = bonding_curve.burn_lp;
+
+    pool_state.initialize(
+        ctx.bumps.authority,
+        liquidity,
+        open_time,
+        creator,
+        ctx.accounts.amm_config.key(),
+        ctx.accounts.token_0_vault.key(),
+        ctx.accounts.token_1_vault.key(),
+        &ctx.accounts.token_0_mint,
+        &ctx.accounts.token_1_mint,
+        &ctx.accounts.lp_mint,
+        ctx.accounts.observation_state.key(),
+        tax_mint,
+        in_tax_rate,
+        out_tax_rate,
+        0,
+        curve_params,
+    );
+    pool_state.tax_amount_0 = tax_amount_0;
+    pool_state.tax_amount_1 = tax_amount_1;
+
+    initialize_pool_observation(
+        &ctx.accounts.observation_state,
+        ctx.accounts.pool_state.key(),
+        pool_state,
+        amount_0,
+        amount_1,
+        block_timestamp,
+    )?;
+
+    let pool_id = ctx.accounts.pool_state.key();
+    let bonding_curve_id = ctx.accounts.bonding_curve.key();
+    let bonding_curve = &mut ctx.accounts.bonding_curve;
+    bonding_curve.graduated = true;
+    bonding_curve.pool_id = pool_id;
+
+    emit_cpi!(LpChangeEvent {
+        pool_id,
+        amount_lp: liquidity,
+        amount_0,
+        amount_1,
+        reserve_0: amount_0,
+        reserve_1: amount_1,
+        change_type: 0,
+        tax_amount: 0,
+    });
+
+    emit_cpi!(TaxConfigUpdatedEvent {
+        pool_id,
+        tax_mint,
+        tax_authority: creator,
+        in_tax_rate,
+        out_tax_rate,
+        quote_in_tax_rate: 0,
+        quote_out_tax_rate: 0,
+        tax_disabled: false,
+    });
+
+    let (token_amount, quote_amount) = if token_is_token_0 {
+        (amount_0, amount_1)
+    } else {
+        (amount_1, amount_0)
+    };
+    emit_cpi!(BondingCurveGraduatedEvent {
+        bonding_curve: bonding_curve_id,
+        pool_id,
+        token_amount,
+        quote_amount,
+        amount_lp: liquidity,
+        lp_burnt,
+    });
+
+    Ok(())
+}
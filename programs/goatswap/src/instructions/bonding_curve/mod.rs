@@ -0,0 +1,7 @@
+pub mod create_bonding_curve;
+pub mod graduate_bonding_curve;
+pub mod swap_bonding_curve;
+
+pub use create_bonding_curve::*;
+pub use graduate_bonding_curve::*;
+pub use swap_bonding_curve::*;
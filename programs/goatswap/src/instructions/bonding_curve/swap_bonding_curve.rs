@@ -0,0 +1,233 @@
+use crate::curve::fees::Fees;
+use crate::error::ErrorCode;
+use crate::states::*;
+use crate::utils::tax_amount;
+use crate::utils::token::*;
+use anchor_lang::prelude::*;
+use anchor_spl::token_interface::{Mint, TokenAccount, TokenInterface};
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct SwapBondingCurve<'info> {
+    /// The user performing the swap
+    pub payer: Signer<'info>,
+
+    /// CHECK: bonding curve vault authority
+    #[account(
+        seeds = [
+            crate::AUTH_SEED.as_bytes(),
+        ],
+        bump,
+    )]
+    pub authority: UncheckedAccount<'info>,
+
+    /// The bonding curve traded on
+    #[account(mut)]
+    pub bonding_curve: Box<Account<'info, BondingCurveState>>,
+
+    /// The user token account for input token
+    #[account(mut, token::authority = payer, token::mint = input_token_mint)]
+    pub input_token_account: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    /// The user token account for output token
+    #[account(mut, token::authority = payer, token::mint = output_token_mint)]
+    pub output_token_account: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    /// The vault token account for input token
+    #[account(
+        mut,
+        constraint = input_vault.key() == bonding_curve.token_vault || input_vault.key() == bonding_curve.quote_vault
+    )]
+    pub input_vault: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    /// The vault token account for output token
+    #[account(
+        mut,
+        constraint = output_vault.key() == bonding_curve.token_vault || output_vault.key() == bonding_curve.quote_vault
+    )]
+    pub output_vault: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    /// SPL program for input token transfers
+    pub input_token_program: Interface<'info, TokenInterface>,
+
+    /// SPL program for output token transfers
+    pub output_token_program: Interface<'info, TokenInterface>,
+
+    /// The mint of input token
+    #[account(
+        address = input_vault.mint,
+        token::token_program = input_token_program
+    )]
+    pub input_token_mint: Box<InterfaceAccount<'info, Mint>>,
+
+    /// The mint of output token
+    #[account(
+        address = output_vault.mint,
+        token::token_program = output_token_program
+    )]
+    pub output_token_mint: Box<InterfaceAccount<'info, Mint>>,
+}
+
+pub fn swap_bonding_curve(
+    ctx: Context<SwapBondingCurve>,
+    amount_in: u64,
+    minimum_amount_out: u64,
+) -> Result<()> {
+    let bonding_curve_id = ctx.accounts.bonding_curve.key();
+    let bonding_curve = &mut ctx.accounts.bonding_curve;
+    require!(!bonding_curve.complete, ErrorCode::BondingCurveComplete);
+
+    let is_buy = if ctx.accounts.input_vault.key() == bonding_curve.quote_vault
+        && ctx.accounts.output_vault.key() == bonding_curve.token_vault
+    {
+        true
+    } else if ctx.accounts.input_vault.key() == bonding_curve.token_vault
+        && ctx.accounts.output_vault.key() == bonding_curve.quote_vault
+    {
+        false
+    } else {
+        return err!(ErrorCode::InvalidVault);
+    };
+
+    let transfer_fee =
+        get_transfer_fee(&ctx.accounts.input_token_mint.to_account_info(), amount_in)?;
+
+    // Take transfer fees into account for actual amount transferred in
+    let amount_in_without_transfer_fee = amount_in.saturating_sub(transfer_fee);
+
+    // check in|out tax
+    let has_out_tax = bonding_curve.out_tax_rate > 0
+        && bonding_curve.tax_mint == ctx.accounts.output_token_mint.key();
+    let has_in_tax = bonding_curve.in_tax_rate > 0
+        && bonding_curve.tax_mint == ctx.accounts.input_token_mint.key();
+
+    let in_tax = if has_in_tax {
+        let in_tax = tax_amount(amount_in_without_transfer_fee, bonding_curve.in_tax_rate).unwrap();
+        u64::try_from(in_tax).unwrap()
+    } else {
+        0
+    };
+
+    let actual_amount_in = amount_in_without_transfer_fee.saturating_sub(in_tax);
+    require_gt!(actual_amount_in, 0);
+
+    let (amount_in_swapped, amount_out) = bonding_curve
+        .swap_base_input(actual_amount_in, is_buy)
+        .ok_or(ErrorCode::ZeroTradingTokens)?;
+
+    // a swap capped by the real reserves only pays for what it gets
+    let (in_tax, input_transfer_amount) = if amount_in_swapped == actual_amount_in {
+        (in_tax, amount_in)
+    } else {
+        let amount_in_with_tax = if has_in_tax {
+            u64::try_from(
+                Fees::calculate_pre_fee_amount(
+                    u128::from(amount_in_swapped),
+                    bonding_curve.in_tax_rate,
+                )
+                .ok_or(ErrorCode::TaxAmountCalculationFailed)?,
+            )
+            .unwrap()
+        } else {
+            amount_in_swapped
+        };
+        let transfer_fee = get_transfer_inverse_fee(
+            &ctx.accounts.input_token_mint.to_account_info(),
+            amount_in_with_tax,
+        )?;
+        (
+            amount_in_with_tax - amount_in_swapped,
+            amount_in_with_tax.checked_add(transfer_fee).unwrap(),
+        )
+    };
+
+    // calculate amount out with tax
+    let out_tax = if has_out_tax {
+        u64::try_from(
+            tax_amount(amount_out, bonding_curve.out_tax_rate)
+                .ok_or(ErrorCode::TaxAmountCalculationFailed)?,
+        )
+        .unwrap()
+    } else {
+        0
+    };
+
+    // check minimum amount out
+    let output_transfer_amount = {
+        let amount_out = amount_out.checked_sub(out_tax).unwrap();
+        let transfer_fee = get_transfer_fee(
+            &ctx.accounts.output_token_mint.to_account_info(),
+            amount_out,
+        )?;
+        let amount_received = amount_out.checked_sub(transfer_fee).unwrap();
+        require_gt!(amount_received, 0);
+        require_gte!(
+            amount_received,
+            minimum_amount_out,
+            ErrorCode::ExceededSlippage
+        );
+        amount_out
+    };
+
+    bonding_curve
+        .apply_swap(amount_in_swapped, amount_out, is_buy)
+        .ok_or(ErrorCode::ZeroTradingTokens)?;
+
+    // update tax in vault
+    let tax_amount = if has_in_tax { in_tax } else { out_tax };
+    if bonding_curve.tax_mint == bonding_curve.token_mint {
+        bonding_curve.tax_amount_token = bonding_curve
+            .tax_amount_token
+            .checked_add(tax_amount)
+            .unwrap();
+    } else {
+        bonding_curve.tax_amount_quote = bonding_curve
+            .tax_amount_quote
+            .checked_add(tax_amount)
+            .unwrap();
+    }
+
+    #[cfg(feature = "enable-log")]
+    msg!(
+        "amount_in:{}, amount_out:{}, tax_amount:{}, complete:{}",
+        amount_in_swapped,
+        amount_out,
+        tax_amount,
+        bonding_curve.complete
+    );
+
+    transfer_from_user_to_pool_vault(
+        ctx.accounts.payer.to_account_info(),
+        ctx.accounts.input_token_account.to_account_info(),
+        ctx.accounts.input_vault.to_account_info(),
+        ctx.accounts.input_token_mint.to_account_info(),
+        ctx.accounts.input_token_program.to_account_info(),
+        input_transfer_amount,
+        ctx.accounts.input_token_mint.decimals,
+    )?;
+
+    transfer_from_pool_vault_to_user(
+        ctx.accounts.authority.to_account_info(),
+        ctx.accounts.output_vault.to_account_info(),
+        ctx.accounts.output_token_account.to_account_info(),
+        ctx.accounts.output_token_mint.to_account_info(),
+        ctx.accounts.output_token_program.to_account_info(),
+        output_transfer_amount,
+        ctx.accounts.output_token_mint.decimals,
+        &[&[crate::AUTH_SEED.as_bytes(), &[ctx.bumps.authority]]],
+    )?;
+
+    let bonding_curve = &ctx.accounts.bonding_curve;
+    emit_cpi!(SwapEvent {
+        pool_id: bonding_curve_id,
+        token_in: ctx.accounts.input_token_account.mint,
+        token_out: ctx.accounts.output_token_account.mint,
+        amount_in: amount_in_swapped,
+        amount_out,
+        reserve_0: bonding_curve.real_token_reserves,
+        reserve_1: bonding_curve.real_quote_reserves,
+        referral_fee: 0,
+    });
+
+    Ok(())
+}
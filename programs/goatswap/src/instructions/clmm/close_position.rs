@@ -0,0 +1,36 @@
+use crate::error::ErrorCode;
+use crate::states::*;
+use anchor_lang::prelude::*;
+
+#[derive(Accounts)]
+pub struct ClosePosition<'info> {
+    /// The position owner, receives the rent of the position account
+    #[account(mut)]
+    pub owner: Signer<'info>,
+
+    pub pool_state: AccountLoader<'info, PoolState>,
+
+    /// The position to close, all liquidity and fees must have been withdrawn
+    #[account(
+        mut,
+        seeds = [
+            POSITION_SEED.as_bytes(),
+            pool_state.key().as_ref(),
+            owner.key().as_ref(),
+            &position.tick_lower_index.to_be_bytes(),
+            &position.tick_upper_index.to_be_bytes(),
+        ],
+        bump = position.bump,
+        close = owner,
+        constraint = position.liquidity == 0
+            && position.token_fees_owed_0 == 0
+            && position.token_fees_owed_1 == 0 @ ErrorCode::ClosePositionErr
+    )]
+    pub position: Box<Account<'info, PositionState>>,
+
+    pub system_program: Program<'info, System>,
+}
+
+pub fn close_position(_ctx: Context<ClosePosition>) -> Result<()> {
+    Ok(())
+}
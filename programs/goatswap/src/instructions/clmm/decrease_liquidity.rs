@@ -0,0 +1,210 @@
+use super::modify_position::*;
+use crate::error::ErrorCode;
+use crate::states::*;
+use crate::utils::token::*;
+use anchor_lang::prelude::*;
+use anchor_spl::token::Token;
+use anchor_spl::token_interface::{Mint, Token2022, TokenAccount};
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct DecreaseLiquidity<'info> {
+    /// The position owner
+    pub owner: Signer<'info>,
+
+    /// CHECK: pool vault authority
+    #[account(
+        seeds = [
+            crate::AUTH_SEED.as_bytes(),
+        ],
+        bump,
+    )]
+    pub authority: UncheckedAccount<'info>,
+
+    pub pool_state: AccountLoader<'info, PoolState>,
+
+    /// The price and liquidity state of the pool
+    #[account(
+        mut,
+        seeds = [
+            CLMM_STATE_SEED.as_bytes(),
+            pool_state.key().as_ref(),
+        ],
+        bump,
+    )]
+    pub clmm_state: AccountLoader<'info, ClmmState>,
+
+    /// The position to remove liquidity from
+    #[account(
+        mut,
+        seeds = [
+            POSITION_SEED.as_bytes(),
+            pool_state.key().as_ref(),
+            owner.key().as_ref(),
+            &position.tick_lower_index.to_be_bytes(),
+            &position.tick_upper_index.to_be_bytes(),
+        ],
+        bump = position.bump,
+    )]
+    pub position: Box<Account<'info, PositionState>>,
+
+    /// The tick array containing the lower tick of the position
+    #[account(
+        mut,
+        constraint = tick_array_lower.load()?.pool_id == pool_state.key() @ ErrorCode::InvalidTickArray
+    )]
+    pub tick_array_lower: AccountLoader<'info, TickArrayState>,
+
+    /// The tick array containing the upper tick of the position, can be the same as `tick_array_lower`
+    #[account(
+        mut,
+        constraint = tick_array_upper.load()?.pool_id == pool_state.key() @ ErrorCode::InvalidTickArray
+    )]
+    pub tick_array_upper: AccountLoader<'info, TickArrayState>,
+
+    /// The token account for receive token_0
+    #[account(
+        mut,
+        token::mint = token_0_vault.mint,
+    )]
+    pub token_0_account: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    /// The token account for receive token_1
+    #[account(
+        mut,
+        token::mint = token_1_vault.mint,
+    )]
+    pub token_1_account: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    /// The address that holds pool tokens for token_0
+    #[account(
+        mut,
+        constraint = token_0_vault.key() == pool_state.load()?.token_0_vault
+    )]
+    pub token_0_vault: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    /// The address that holds pool tokens for token_1
+    #[account(
+        mut,
+        constraint = token_1_vault.key() == pool_state.load()?.token_1_vault
+    )]
+    pub token_1_vault: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    /// The mint of token_0 vault
+    #[account(
+        address = token_0_vault.mint
+    )]
+    pub vault_0_mint: Box<InterfaceAccount<'info, Mint>>,
+
+    /// The mint of token_1 vault
+    #[account(
+        address = token_1_vault.mint
+    )]
+    pub vault_1_mint: Box<InterfaceAccount<'info, Mint>>,
+
+    /// token Program
+    pub token_program: Program<'info, Token>,
+
+    /// Token program 2022
+    pub token_program_2022: Program<'info, Token2022>,
+}
+
+/// Removes liquidity from a position and pays out the tokens together with
+/// all the fees owed to the position. `liquidity` can be 0 to only collect fees.
+pub fn decrease_liquidity(
+    ctx: Context<DecreaseLiquidity>,
+    liquidity: u128,
+    amount_0_min: u64,
+    amount_1_min: u64,
+) -> Result<()> {
+    let pool_id = ctx.accounts.pool_state.key();
+    let auth_bump = {
+        let pool_state = ctx.accounts.pool_state.load()?;
+        if !pool_state.get_status_by_bit(PoolStatusBitIndex::Withdraw) {
+            return err!(ErrorCode::NotApproved);
+        }
+        pool_state.auth_bump
+    };
+    require_gte!(ctx.accounts.position.liquidity, liquidity);
+    let liquidity_delta = i128::try_from(liquidity)
+        .map_err(|_| ErrorCode::LiquidityOverflow)?
+        .checked_neg()
+        .unwrap();
+
+    let clmm_state = &mut ctx.accounts.clmm_state.load_mut()?;
+    let position = &mut ctx.accounts.position;
+    let (amount_0, amount_1) = modify_position(
+        liquidity_delta,
+        clmm_state,
+        position,
+        &ctx.accounts.tick_array_lower,
+        &ctx.accounts.tick_array_upper,
+    )?;
+
+    let fees_0 = position.token_fees_owed_0;
+    let fees_1 = position.token_fees_owed_1;
+    position.token_fees_owed_0 = 0;
+    position.token_fees_owed_1 = 0;
+
+    let token_0_amount = amount_0.checked_add(fees_0).unwrap();
+    let receive_token_0_amount = {
+        let transfer_fee =
+            get_transfer_fee(&ctx.accounts.vault_0_mint.to_account_info(), amount_0)?;
+        amount_0.checked_sub(transfer_fee).unwrap()
+    };
+    let token_1_amount = amount_1.checked_add(fees_1).unwrap();
+    let receive_token_1_amount = {
+        let transfer_fee =
+            get_transfer_fee(&ctx.accounts.vault_1_mint.to_account_info(), amount_1)?;
+        amount_1.checked_sub(transfer_fee).unwrap()
+    };
+
+    if receive_token_0_amount < amount_0_min || receive_token_1_amount < amount_1_min {
+        return Err(ErrorCode::ExceededSlippage.into());
+    }
+
+    transfer_from_pool_vault_to_user(
+        ctx.accounts.authority.to_account_info(),
+        ctx.accounts.token_0_vault.to_account_info(),
+        ctx.accounts.token_0_account.to_account_info(),
+        ctx.accounts.vault_0_mint.to_account_info(),
+        if ctx.accounts.vault_0_mint.to_account_info().owner == ctx.accounts.token_program.key {
+            ctx.accounts.token_program.to_account_info()
+        } else {
+            ctx.accounts.token_program_2022.to_account_info()
+        },
+        token_0_amount,
+        ctx.accounts.vault_0_mint.decimals,
+        &[&[crate::AUTH_SEED.as_bytes(), &[auth_bump]]],
+    )?;
+
+    transfer_from_pool_vault_to_user(
+        ctx.accounts.authority.to_account_info(),
+        ctx.accounts.token_1_vault.to_account_info(),
+        ctx.accounts.token_1_account.to_account_info(),
+        ctx.accounts.vault_1_mint.to_account_info(),
+        if ctx.accounts.vault_1_mint.to_account_info().owner == ctx.accounts.token_program.key {
+            ctx.accounts.token_program.to_account_info()
+        } else {
+            ctx.accounts.token_program_2022.to_account_info()
+        },
+        token_1_amount,
+        ctx.accounts.vault_1_mint.decimals,
+        &[&[crate::AUTH_SEED.as_bytes(), &[auth_bump]]],
+    )?;
+
+    emit_cpi!(PositionChangeEvent {
+        pool_id,
+        position: ctx.accounts.position.key(),
+        tick_lower_index: ctx.accounts.position.tick_lower_index,
+        tick_upper_index: ctx.accounts.position.tick_upper_index,
+        liquidity,
+        amount_0,
+        amount_1,
+        fees_0,
+        fees_1,
+        change_type: 2,
+    });
+
+    Ok(())
+}
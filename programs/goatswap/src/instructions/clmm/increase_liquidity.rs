@@ -0,0 +1,160 @@
+use super::modify_position::*;
+use crate::error::ErrorCode;
+use crate::states::*;
+use anchor_lang::prelude::*;
+use anchor_spl::token::Token;
+use anchor_spl::token_interface::{Mint, Token2022, TokenAccount};
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct IncreaseLiquidity<'info> {
+    /// The position owner, provides the liquidity
+    pub owner: Signer<'info>,
+
+    pub pool_state: AccountLoader<'info, PoolState>,
+
+    /// The price and liquidity state of the pool
+    #[account(
+        mut,
+        seeds = [
+            CLMM_STATE_SEED.as_bytes(),
+            pool_state.key().as_ref(),
+        ],
+        bump,
+    )]
+    pub clmm_state: AccountLoader<'info, ClmmState>,
+
+    /// The position to add liquidity to
+    #[account(
+        mut,
+        seeds = [
+            POSITION_SEED.as_bytes(),
+            pool_state.key().as_ref(),
+            owner.key().as_ref(),
+            &position.tick_lower_index.to_be_bytes(),
+            &position.tick_upper_index.to_be_bytes(),
+        ],
+        bump = position.bump,
+    )]
+    pub position: Box<Account<'info, PositionState>>,
+
+    /// The tick array containing the lower tick of the position
+    #[account(
+        mut,
+        constraint = tick_array_lower.load()?.pool_id == pool_state.key() @ ErrorCode::InvalidTickArray
+    )]
+    pub tick_array_lower: AccountLoader<'info, TickArrayState>,
+
+    /// The tick array containing the upper tick of the position, can be the same as `tick_array_lower`
+    #[account(
+        mut,
+        constraint = tick_array_upper.load()?.pool_id == pool_state.key() @ ErrorCode::InvalidTickArray
+    )]
+    pub tick_array_upper: AccountLoader<'info, TickArrayState>,
+
+    /// The payer's token account for token_0
+    #[account(
+        mut,
+        token::mint = token_0_vault.mint,
+        token::authority = owner
+    )]
+    pub token_0_account: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    /// The payer's token account for token_1
+    #[account(
+        mut,
+        token::mint = token_1_vault.mint,
+        token::authority = owner
+    )]
+    pub token_1_account: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    /// The address that holds pool tokens for token_0
+    #[account(
+        mut,
+        constraint = token_0_vault.key() == pool_state.load()?.token_0_vault
+    )]
+    pub token_0_vault: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    /// The address that holds pool tokens for token_1
+    #[account(
+        mut,
+        constraint = token_1_vault.key() == pool_state.load()?.token_1_vault
+    )]
+    pub token_1_vault: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    /// The mint of token_0 vault
+    #[account(
+        address = token_0_vault.mint
+    )]
+    pub vault_0_mint: Box<InterfaceAccount<'info, Mint>>,
+
+    /// The mint of token_1 vault
+    #[account(
+        address = token_1_vault.mint
+    )]
+    pub vault_1_mint: Box<InterfaceAccount<'info, Mint>>,
+
+    /// token Program
+    pub token_program: Program<'info, Token>,
+
+    /// Token program 2022
+    pub token_program_2022: Program<'info, Token2022>,
+}
+
+pub fn increase_liquidity(
+    ctx: Context<IncreaseLiquidity>,
+    liquidity: u128,
+    amount_0_max: u64,
+    amount_1_max: u64,
+) -> Result<()> {
+    let pool_id = ctx.accounts.pool_state.key();
+    {
+        let pool_state = ctx.accounts.pool_state.load()?;
+        if !pool_state.get_status_by_bit(PoolStatusBitIndex::Deposit) {
+            return err!(ErrorCode::NotApproved);
+        }
+    }
+    require_gt!(liquidity, 0);
+    let liquidity_delta = i128::try_from(liquidity).map_err(|_| ErrorCode::LiquidityOverflow)?;
+
+    let clmm_state = &mut ctx.accounts.clmm_state.load_mut()?;
+    let position = &mut ctx.accounts.position;
+    let (amount_0, amount_1) = modify_position(
+        liquidity_delta,
+        clmm_state,
+        position,
+        &ctx.accounts.tick_array_lower,
+        &ctx.accounts.tick_array_upper,
+    )?;
+
+    deposit_to_pool_vaults(
+        &ctx.accounts.owner,
+        &ctx.accounts.token_0_account,
+        &ctx.accounts.token_1_account,
+        &ctx.accounts.token_0_vault,
+        &ctx.accounts.token_1_vault,
+        &ctx.accounts.vault_0_mint,
+        &ctx.accounts.vault_1_mint,
+        &ctx.accounts.token_program,
+        &ctx.accounts.token_program_2022,
+        amount_0,
+        amount_1,
+        amount_0_max,
+        amount_1_max,
+    )?;
+
+    emit_cpi!(PositionChangeEvent {
+        pool_id,
+        position: ctx.accounts.position.key(),
+        tick_lower_index: ctx.accounts.position.tick_lower_index,
+        tick_upper_index: ctx.accounts.position.tick_upper_index,
+        liquidity,
+        amount_0,
+        amount_1,
+        fees_0: 0,
+        fees_1: 0,
+        change_type: 1,
+    });
+
+    Ok(())
+}
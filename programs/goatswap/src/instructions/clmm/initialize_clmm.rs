@@ -0,0 +1,249 @@
+use crate::curve::FEE_RATE_DENOMINATOR_VALUE;
+use crate::error::ErrorCode;
+use crate::states::*;
+use crate::utils::*;
+use anchor_lang::{
+    accounts::interface_account::InterfaceAccount,
+    prelude::*,
+    solana_program::{clock, program::invoke, system_instruction},
+};
+use anchor_spl::token_interface::{Mint, TokenInterface};
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct InitializeClmm<'info> {
+    /// Address paying to create the pool. Can be anyone
+    #[account(mut)]
+    pub creator: Signer<'info>,
+
+    /// Which config the pool belongs to.
+    pub amm_config: Box<Account<'info, AmmConfig>>,
+
+    /// CHECK: pool vault authority
+    #[account(
+        seeds = [
+            crate::AUTH_SEED.as_bytes(),
+        ],
+        bump,
+    )]
+    pub authority: UncheckedAccount<'info>,
+
+    /// Initialize an account to store the pool state
+    #[account(
+        init,
+        seeds = [
+            POOL_SEED.as_bytes(),
+            amm_config.key().as_ref(),
+            token_0_mint.key().as_ref(),
+            token_1_mint.key().as_ref(),
+        ],
+        bump,
+        payer = creator,
+        space = PoolState::LEN
+    )]
+    pub pool_state: AccountLoader<'info, PoolState>,
+
+    /// Initialize an account to store the price and liquidity of the pool
+    #[account(
+        init,
+        seeds = [
+            CLMM_STATE_SEED.as_bytes(),
+            pool_state.key().as_ref(),
+        ],
+        bump,
+        payer = creator,
+        space = ClmmState::LEN
+    )]
+    pub clmm_state: AccountLoader<'info, ClmmState>,
+
+    /// Token_0 mint, the key must smaller then token_1 mint.
+    #[account(
+        constraint = token_0_mint.key() < token_1_mint.key(),
+        mint::token_program = token_0_program,
+    )]
+    pub token_0_mint: Box<InterfaceAccount<'info, Mint>>,
+
+    /// Token_1 mint, the key must grater then token_0 mint.
+    #[account(
+        mint::token_program = token_1_program,
+    )]
+    pub token_1_mint: Box<InterfaceAccount<'info, Mint>>,
+
+    /// CHECK: the bonding curve selling token_0 for token_1 under the config,
+    /// which must not exist
+    pub token_0_bonding_curve: UncheckedAccount<'info>,
+
+    /// CHECK: the bonding curve selling token_1 for token_0 under the config,
+    /// which must not exist
+    pub token_1_bonding_curve: UncheckedAccount<'info>,
+
+    /// CHECK: Token_0 vault for the pool
+    #[account(
+        mut,
+        seeds = [
+            POOL_VAULT_SEED.as_bytes(),
+            pool_state.key().as_ref(),
+            token_0_mint.key().as_ref()
+        ],
+        bump,
+    )]
+    pub token_0_vault: UncheckedAccount<'info>,
+
+    /// CHECK: Token_1 vault for the pool
+    #[account(
+        mut,
+        seeds = [
+            POOL_VAULT_SEED.as_bytes(),
+            pool_state.key().as_ref(),
+            token_1_mint.key().as_ref()
+        ],
+        bump,
+    )]
+    pub token_1_vault: UncheckedAccount<'info>,
+
+    /// CHECK: Only used to receive SOL
+    #[account(
+        mut,
+        address= crate::create_pool_fee_reveiver::id(),
+    )]
+    pub create_pool_fee: UncheckedAccount<'info>,
+
+    /// Spl token program or token program 2022
+    pub token_0_program: Interface<'info, TokenInterface>,
+    /// Spl token program or token program 2022
+    pub token_1_program: Interface<'info, TokenInterface>,
+    /// To create a new program account
+    pub system_program: Program<'info, System>,
+    /// Sysvar for program account
+    pub rent: Sysvar<'info, Rent>,
+}
+
+pub fn initialize_clmm(
+    ctx: Context<InitializeClmm>,
+    sqrt_price_x64: u128,
+    tick_spacing: u16,
+    mut open_time: u64,
+    tax_use_token_0: bool,
+    in_tax_rate: u64,
+    out_tax_rate: u64,
+) -> Result<()> {
+    if !(is_supported_mint(&ctx.accounts.token_0_mint).unwrap()
+        && is_supported_mint(&ctx.accounts.token_1_mint).unwrap())
+    {
+        return err!(ErrorCode::NotSupportMint);
+    }
+
+    if ctx.accounts.amm_config.disable_create_pool {
+        return err!(ErrorCode::NotApproved);
+    }
+
+    // a pair with a bonding curve gets its pool at graduation
+    BondingCurveState::check_pair_has_no_curve(
+        &ctx.accounts.amm_config.key(),
+        &ctx.accounts.token_0_mint.key(),
+        &ctx.accounts.token_1_mint.key(),
+        [
+            &ctx.accounts.token_0_bonding_curve.to_account_info(),
+            &ctx.accounts.token_1_bonding_curve.to_account_info(),
+        ],
+    )?;
+
+    require!(tick_spacing > 0, ErrorCode::InvalidTickIndex);
+
+    let block_timestamp = clock::Clock::get()?.unix_timestamp as u64;
+    if open_time <= block_timestamp {
+        open_time = block_timestamp + 1;
+    }
+
+    // check tax config
+    assert!(in_tax_rate <= FEE_RATE_DENOMINATOR_VALUE);
+    assert!(out_tax_rate <= FEE_RATE_DENOMINATOR_VALUE);
+    ctx.accounts.amm_config.check_tax_rate(in_tax_rate)?;
+    ctx.accounts.amm_config.check_tax_rate(out_tax_rate)?;
+
+    // get tax mint
+    let tax_mint = if tax_use_token_0 {
+        ctx.accounts.token_0_mint.key()
+    } else {
+        ctx.accounts.token_1_mint.key()
+    };
+
+    // due to stack/heap limitations, we have to create redundant new accounts ourselves.
+    create_token_account(
+        &ctx.accounts.authority.to_account_info(),
+        &ctx.accounts.creator.to_account_info(),
+        &ctx.accounts.token_0_vault.to_account_info(),
+        &ctx.accounts.token_0_mint.to_account_info(),
+        &ctx.accounts.system_program.to_account_info(),
+        &ctx.accounts.token_0_program.to_account_info(),
+        &[&[
+            POOL_VAULT_SEED.as_bytes(),
+            ctx.accounts.pool_state.key().as_ref(),
+            ctx.accounts.token_0_mint.key().as_ref(),
+            &[ctx.bumps.token_0_vault][..],
+        ][..]],
+    )?;
+
+    create_token_account(
+        &ctx.accounts.authority.to_account_info(),
+        &ctx.accounts.creator.to_account_info(),
+        &ctx.accounts.token_1_vault.to_account_info(),
+        &ctx.accounts.token_1_mint.to_account_info(),
+        &ctx.accounts.system_program.to_account_info(),
+        &ctx.accounts.token_1_program.to_account_info(),
+        &[&[
+            POOL_VAULT_SEED.as_bytes(),
+            ctx.accounts.pool_state.key().as_ref(),
+            ctx.accounts.token_1_mint.key().as_ref(),
+            &[ctx.bumps.token_1_vault][..],
+        ][..]],
+    )?;
+
+    // Charge the fee to create a pool
+    if ctx.accounts.amm_config.create_pool_fee != 0 {
+        invoke(
+            &system_instruction::transfer(
+                ctx.accounts.creator.key,
+                &ctx.accounts.create_pool_fee.key(),
+                ctx.accounts.amm_config.create_pool_fee,
+            ),
+            &[
+                ctx.accounts.creator.to_account_info(),
+                ctx.accounts.create_pool_fee.to_account_info(),
+                ctx.accounts.system_program.to_account_info(),
+            ],
+        )?;
+    }
+
+    let pool_id = ctx.accounts.pool_state.key();
+    let pool_state = &mut ctx.accounts.pool_state.load_init()?;
+    pool_state.initialize_clmm(
+        ctx.bumps.authority,
+        open_time,
+        ctx.accounts.creator.key(),
+        ctx.accounts.amm_config.key(),
+        ctx.accounts.token_0_vault.key(),
+        ctx.accounts.token_1_vault.key(),
+        &ctx.accounts.token_0_mint,
+        &ctx.accounts.token_1_mint,
+        tax_mint,
+        in_tax_rate,
+        out_tax_rate,
+    );
+
+    let clmm_state = &mut ctx.accounts.clmm_state.load_init()?;
+    clmm_state.initialize(ctx.bumps.clmm_state, pool_id, tick_spacing, sqrt_price_x64)?;
+
+    emit_cpi!(TaxConfigUpdatedEvent {
+        pool_id,
+        tax_mint,
+        tax_authority: ctx.accounts.creator.key(),
+        in_tax_rate,
+        out_tax_rate,
+        quote_in_tax_rate: 0,
+        quote_out_tax_rate: 0,
+        tax_disabled: false,
+    });
+
+    Ok(())
+}
@@ -0,0 +1,60 @@
+use crate::error::ErrorCode;
+use crate::states::*;
+use anchor_lang::prelude::*;
+
+#[derive(Accounts)]
+#[instruction(start_tick_index: i32)]
+pub struct InitializeTickArray<'info> {
+    /// Address paying to create the tick array. Can be anyone
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    /// The pool the tick array belongs to
+    pub pool_state: AccountLoader<'info, PoolState>,
+
+    /// The price and liquidity state of the pool
+    #[account(
+        seeds = [
+            CLMM_STATE_SEED.as_bytes(),
+            pool_state.key().as_ref(),
+        ],
+        bump,
+    )]
+    pub clmm_state: AccountLoader<'info, ClmmState>,
+
+    /// Initialize an account to store the ticks from `start_tick_index`
+    #[account(
+        init,
+        seeds = [
+            TICK_ARRAY_SEED.as_bytes(),
+            pool_state.key().as_ref(),
+            &start_tick_index.to_be_bytes(),
+        ],
+        bump,
+        payer = payer,
+        space = TickArrayState::LEN
+    )]
+    pub tick_array: AccountLoader<'info, TickArrayState>,
+
+    /// To create a new program account
+    pub system_program: Program<'info, System>,
+}
+
+pub fn initialize_tick_array(
+    ctx: Context<InitializeTickArray>,
+    start_tick_index: i32,
+) -> Result<()> {
+    let tick_spacing = ctx.accounts.clmm_state.load()?.tick_spacing;
+    require!(
+        TickArrayState::check_is_valid_start_index(start_tick_index, tick_spacing),
+        ErrorCode::InvalidTickArray
+    );
+
+    let tick_array = &mut ctx.accounts.tick_array.load_init()?;
+    tick_array.initialize(
+        ctx.accounts.pool_state.key(),
+        start_tick_index,
+        tick_spacing,
+    );
+    Ok(())
+}
@@ -0,0 +1,17 @@
+pub mod close_position;
+pub mod decrease_liquidity;
+pub mod increase_liquidity;
+pub mod initialize_clmm;
+pub mod initialize_tick_array;
+pub mod modify_position;
+pub mod open_position;
+pub mod swap_clmm;
+
+pub use close_position::*;
+pub use decrease_liquidity::*;
+pub use increase_liquidity::*;
+pub use initialize_clmm::*;
+pub use initialize_tick_array::*;
+pub use modify_position::*;
+pub use open_position::*;
+pub use swap_clmm::*;
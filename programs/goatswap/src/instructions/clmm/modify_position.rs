@@ -0,0 +1,146 @@
+use crate::curve::clmm::*;
+use crate::error::ErrorCode;
+use crate::states::*;
+use crate::utils::token::*;
+use anchor_lang::prelude::*;
+use anchor_spl::token::Token;
+use anchor_spl::token_interface::{Mint, Token2022, TokenAccount};
+
+/// Applies a liquidity change to a position and its boundary ticks, and returns
+/// the token amounts owed to (when adding) or by (when removing) the pool.
+pub fn modify_position(
+    liquidity_delta: i128,
+    clmm_state: &mut ClmmState,
+    position: &mut PositionState,
+    tick_array_lower: &AccountLoader<TickArrayState>,
+    tick_array_upper: &AccountLoader<TickArrayState>,
+) -> Result<(u64, u64)> {
+    let tick_spacing = clmm_state.tick_spacing;
+    let tick_current = clmm_state.tick_current;
+    let fee_growth_global_0_x64 = clmm_state.fee_growth_global_0_x64;
+    let fee_growth_global_1_x64 = clmm_state.fee_growth_global_1_x64;
+    let max_liquidity = max_liquidity_per_tick(tick_spacing);
+    let tick_lower_index = position.tick_lower_index;
+    let tick_upper_index = position.tick_upper_index;
+
+    // the lower and upper tick may live in the same tick array, only borrow one at a time
+    let update_tick = |tick_array: &AccountLoader<TickArrayState>,
+                       tick_index: i32,
+                       upper: bool|
+     -> Result<TickState> {
+        let mut tick_array = tick_array.load_mut()?;
+        let tick_state = tick_array.get_tick_state_mut(tick_index, tick_spacing)?;
+        let flipped = tick_state.update(
+            tick_current,
+            liquidity_delta,
+            fee_growth_global_0_x64,
+            fee_growth_global_1_x64,
+            upper,
+            max_liquidity,
+        )?;
+        let tick_state = *tick_state;
+        if flipped {
+            tick_array.update_initialized_tick_count(liquidity_delta > 0)?;
+        }
+        Ok(tick_state)
+    };
+    let tick_lower = update_tick(tick_array_lower, tick_lower_index, false)?;
+    let tick_upper = update_tick(tick_array_upper, tick_upper_index, true)?;
+
+    let (fee_growth_inside_0_x64, fee_growth_inside_1_x64) = get_fee_growth_inside(
+        &tick_lower,
+        &tick_upper,
+        tick_current,
+        fee_growth_global_0_x64,
+        fee_growth_global_1_x64,
+    );
+    position.update(
+        liquidity_delta,
+        fee_growth_inside_0_x64,
+        fee_growth_inside_1_x64,
+    )?;
+
+    let sqrt_price_lower_x64 = get_sqrt_price_at_tick(tick_lower_index)?;
+    let sqrt_price_upper_x64 = get_sqrt_price_at_tick(tick_upper_index)?;
+    let (amount_0, amount_1) = if tick_current < tick_lower_index {
+        // current tick is below the range, the position is entirely token_0
+        (
+            get_amount_0_delta_signed(sqrt_price_lower_x64, sqrt_price_upper_x64, liquidity_delta)?,
+            0,
+        )
+    } else if tick_current < tick_upper_index {
+        // current tick is inside the range, the in range liquidity changes
+        let sqrt_price_x64 = clmm_state.sqrt_price_x64;
+        clmm_state.liquidity = add_delta(clmm_state.liquidity, liquidity_delta)?;
+        (
+            get_amount_0_delta_signed(sqrt_price_x64, sqrt_price_upper_x64, liquidity_delta)?,
+            get_amount_1_delta_signed(sqrt_price_lower_x64, sqrt_price_x64, liquidity_delta)?,
+        )
+    } else {
+        // current tick is above the range, the position is entirely token_1
+        (
+            0,
+            get_amount_1_delta_signed(sqrt_price_lower_x64, sqrt_price_upper_x64, liquidity_delta)?,
+        )
+    };
+    Ok((amount_0, amount_1))
+}
+
+/// Transfers the amounts required by a liquidity increase into the pool vaults,
+/// on top of any transfer fee, and checks them against the user's maximums
+pub fn deposit_to_pool_vaults<'info>(
+    owner: &Signer<'info>,
+    token_0_account: &InterfaceAccount<'info, TokenAccount>,
+    token_1_account: &InterfaceAccount<'info, TokenAccount>,
+    token_0_vault: &InterfaceAccount<'info, TokenAccount>,
+    token_1_vault: &InterfaceAccount<'info, TokenAccount>,
+    vault_0_mint: &InterfaceAccount<'info, Mint>,
+    vault_1_mint: &InterfaceAccount<'info, Mint>,
+    token_program: &Program<'info, Token>,
+    token_program_2022: &Program<'info, Token2022>,
+    amount_0: u64,
+    amount_1: u64,
+    amount_0_max: u64,
+    amount_1_max: u64,
+) -> Result<()> {
+    let transfer_token_0_amount = {
+        let transfer_fee = get_transfer_inverse_fee(&vault_0_mint.to_account_info(), amount_0)?;
+        amount_0.checked_add(transfer_fee).unwrap()
+    };
+    let transfer_token_1_amount = {
+        let transfer_fee = get_transfer_inverse_fee(&vault_1_mint.to_account_info(), amount_1)?;
+        amount_1.checked_add(transfer_fee).unwrap()
+    };
+
+    if transfer_token_0_amount > amount_0_max || transfer_token_1_amount > amount_1_max {
+        return Err(ErrorCode::ExceededSlippage.into());
+    }
+
+    transfer_from_user_to_pool_vault(
+        owner.to_account_info(),
+        token_0_account.to_account_info(),
+        token_0_vault.to_account_info(),
+        vault_0_mint.to_account_info(),
+        if vault_0_mint.to_account_info().owner == token_program.key {
+            token_program.to_account_info()
+        } else {
+            token_program_2022.to_account_info()
+        },
+        transfer_token_0_amount,
+        vault_0_mint.decimals,
+    )?;
+
+    transfer_from_user_to_pool_vault(
+        owner.to_account_info(),
+        token_1_account.to_account_info(),
+        token_1_vault.to_account_info(),
+        vault_1_mint.to_account_info(),
+        if vault_1_mint.to_account_info().owner == token_program.key {
+            token_program.to_account_info()
+        } else {
+            token_program_2022.to_account_info()
+        },
+        transfer_token_1_amount,
+        vault_1_mint.decimals,
+    )
+}
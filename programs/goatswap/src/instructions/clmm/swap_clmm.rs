@@ -0,0 +1,709 @@
+use crate::curve::clmm::*;
+use crate::curve::fees;
+use crate::error::ErrorCode;
+use crate::states::*;
+use crate::utils::tax_amount;
+use crate::utils::token::*;
+use crate::utils::U256;
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program;
+use anchor_spl::token_interface::{Mint, TokenAccount, TokenInterface};
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct SwapClmm<'info> {
+    /// The user performing the swap
+    pub payer: Signer<'info>,
+
+    /// CHECK: pool vault authority
+    #[account(
+        seeds = [
+            crate::AUTH_SEED.as_bytes(),
+        ],
+        bump,
+    )]
+    pub authority: UncheckedAccount<'info>,
+
+    /// The factory state to read protocol fees
+    #[account(address = pool_state.load()?.amm_config)]
+    pub amm_config: Box<Account<'info, AmmConfig>>,
+
+    /// The program account of the pool in which the swap will be performed
+    #[account(mut)]
+    pub pool_state: AccountLoader<'info, PoolState>,
+
+    /// The price and liquidity state of the pool
+    #[account(
+        mut,
+        seeds = [
+            CLMM_STATE_SEED.as_bytes(),
+            pool_state.key().as_ref(),
+        ],
+        bump,
+    )]
+    pub clmm_state: AccountLoader<'info, ClmmState>,
+
+    /// The user token account for input token
+    #[account(mut, token::authority = payer, token::mint = input_token_mint)]
+    pub input_token_account: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    /// The user token account for output token
+    #[account(mut, token::authority = payer, token::mint = output_token_mint)]
+    pub output_token_account: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    /// The vault token account for input token
+    #[account(
+        mut,
+        constraint = input_vault.key() == pool_state.load()?.token_0_vault || input_vault.key() == pool_state.load()?.token_1_vault
+    )]
+    pub input_vault: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    /// The vault token account for output token
+    #[account(
+        mut,
+        constraint = output_vault.key() == pool_state.load()?.token_0_vault || output_vault.key() == pool_state.load()?.token_1_vault
+    )]
+    pub output_vault: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    /// SPL program for input token transfers
+    pub input_token_program: Interface<'info, TokenInterface>,
+
+    /// SPL program for output token transfers
+    pub output_token_program: Interface<'info, TokenInterface>,
+
+    /// The mint of input token
+    #[account(
+        address = input_vault.mint,
+        token::token_program = input_token_program
+    )]
+    pub input_token_mint: Box<InterfaceAccount<'info, Mint>>,
+
+    /// The mint of output token
+    #[account(
+        address = output_vault.mint,
+        token::token_program = output_token_program
+    )]
+    pub output_token_mint: Box<InterfaceAccount<'info, Mint>>,
+
+    /// Fee schedule of the amm config, needed when it has fee buckets beyond
+    /// the protocol and fund buckets
+    #[account(
+        seeds = [
+            FEE_SCHEDULE_SEED.as_bytes(),
+            amm_config.key().as_ref(),
+        ],
+        bump = fee_schedule.bump,
+    )]
+    pub fee_schedule: Option<Box<Account<'info, FeeSchedule>>>,
+
+    /// The tax exemption of the payer, the swap is not taxed when set
+    #[account(
+        seeds = [
+            TAX_EXEMPTION_SEED.as_bytes(),
+            pool_state.key().as_ref(),
+            payer.key().as_ref(),
+        ],
+        bump = tax_exemption.bump,
+    )]
+    pub tax_exemption: Option<Box<Account<'info, TaxExemption>>>,
+
+    /// The referrer's token account for the input token, receives the
+    /// referral share of the trade fee when set. Its owner can not be the payer
+    #[account(mut, token::mint = input_token_mint)]
+    pub referrer_token_account: Option<Box<InterfaceAccount<'info, TokenAccount>>>,
+    // remaining accounts: the tick arrays to traverse, in swap direction, starting with the
+    // one that contains the current tick. Arrays that were never initialized can be passed
+    // as their uninitialized PDA address.
+}
+
+/// Result of a swap through the ticks of a concentrated liquidity pool
+#[derive(Debug, Default)]
+pub struct ClmmSwapResult {
+    /// Input amount consumed by the swap, including fees
+    pub amount_in: u64,
+    /// Output amount paid out by the swap
+    pub amount_out: u64,
+    /// Total trade fee, in the input token
+    pub fee_amount: u64,
+    /// Part of the trade fee owed to the protocol
+    pub protocol_fee: u64,
+    /// Part of the trade fee owed to the fund
+    pub fund_fee: u64,
+    /// Parts of the trade fee owed to the fee schedule buckets
+    pub bucket_fees: [u64; MAX_SCHEDULED_FEE_BUCKETS],
+    /// Part of the trade fee owed to the pool creator
+    pub creator_fee: u64,
+    /// Part of the trade fee paid to the referrer
+    pub referral_fee: u64,
+}
+
+/// Loads the tick array at `start_tick_index`, returning `None` for a tick array
+/// which has not been initialized yet and therefore holds no liquidity
+fn load_tick_array<'info>(
+    account_info: &'info AccountInfo<'info>,
+    pool_id: &Pubkey,
+    start_tick_index: i32,
+) -> Result<Option<AccountLoader<'info, TickArrayState>>> {
+    if account_info.owner == &solana_program::system_program::ID && account_info.data_is_empty() {
+        let (expected_address, _) = Pubkey::find_program_address(
+            &[
+                TICK_ARRAY_SEED.as_bytes(),
+                pool_id.as_ref(),
+                &start_tick_index.to_be_bytes(),
+            ],
+            &crate::id(),
+        );
+        require_keys_eq!(
+            account_info.key(),
+            expected_address,
+            ErrorCode::InvalidTickArray
+        );
+        return Ok(None);
+    }
+    let tick_array_loader = AccountLoader::<TickArrayState>::try_from(account_info)?;
+    {
+        let tick_array = tick_array_loader.load()?;
+        require!(
+            tick_array.pool_id == *pool_id && { tick_array.start_tick_index } == start_tick_index,
+            ErrorCode::InvalidTickArray
+        );
+    }
+    Ok(Some(tick_array_loader))
+}
+
+/// Swaps through the pool's liquidity, crossing initialized ticks until the
+/// specified amount is consumed or the price limit is reached
+pub fn swap_internal<'info>(
+    clmm_state: &mut ClmmState,
+    pool_id: &Pubkey,
+    tick_array_accounts: &'info [AccountInfo<'info>],
+    amm_config: &AmmConfig,
+    fee_schedule: Option<&FeeSchedule>,
+    creator_fee_rate: u64,
+    referral_fee_rate: u64,
+    amount_specified: u64,
+    sqrt_price_limit_x64: u128,
+    zero_for_one: bool,
+    is_base_input: bool,
+) -> Result<ClmmSwapResult> {
+    require_gt!(amount_specified, 0);
+    let tick_spacing = clmm_state.tick_spacing;
+    let sqrt_price_start_x64 = clmm_state.sqrt_price_x64;
+    let sqrt_price_limit_x64 = if sqrt_price_limit_x64 == 0 {
+        if zero_for_one {
+            MIN_SQRT_PRICE_X64 + 1
+        } else {
+            MAX_SQRT_PRICE_X64 - 1
+        }
+    } else {
+        sqrt_price_limit_x64
+    };
+    if zero_for_one {
+        require!(
+            sqrt_price_limit_x64 < sqrt_price_start_x64
+                && sqrt_price_limit_x64 > MIN_SQRT_PRICE_X64,
+            ErrorCode::SqrtPriceLimitOverflow
+        );
+    } else {
+        require!(
+            sqrt_price_limit_x64 > sqrt_price_start_x64
+                && sqrt_price_limit_x64 < MAX_SQRT_PRICE_X64,
+            ErrorCode::SqrtPriceLimitOverflow
+        );
+    }
+
+    let mut amount_remaining = amount_specified;
+    let mut amount_calculated: u64 = 0;
+    let mut sqrt_price_x64 = sqrt_price_start_x64;
+    let mut tick = clmm_state.tick_current;
+    let mut liquidity = clmm_state.liquidity;
+    let mut fee_growth_global_x64 = if zero_for_one {
+        clmm_state.fee_growth_global_0_x64
+    } else {
+        clmm_state.fee_growth_global_1_x64
+    };
+    let mut result = ClmmSwapResult::default();
+
+    let mut tick_array_accounts = tick_array_accounts.iter();
+    let mut tick_array: Option<AccountLoader<TickArrayState>> = None;
+    let mut tick_array_start_index: Option<i32> = None;
+
+    while amount_remaining != 0 && sqrt_price_x64 != sqrt_price_limit_x64 {
+        // the tick array holding the next tick to look at, in the swap direction
+        let search_start_index = if zero_for_one {
+            TickArrayState::get_array_start_index(tick, tick_spacing)
+        } else {
+            let tick_aligned = tick.div_euclid(i32::from(tick_spacing)) * i32::from(tick_spacing);
+            TickArrayState::get_array_start_index(
+                tick_aligned + i32::from(tick_spacing),
+                tick_spacing,
+            )
+        };
+        if tick_array_start_index != Some(search_start_index) {
+            let account_info = tick_array_accounts
+                .next()
+                .ok_or(ErrorCode::NotEnoughTickArrayAccount)?;
+            tick_array = load_tick_array(account_info, pool_id, search_start_index)?;
+            tick_array_start_index = Some(search_start_index);
+        }
+
+        let next_initialized_tick = match &tick_array {
+            Some(loader) => loader
+                .load_mut()?
+                .next_initialized_tick(tick, tick_spacing, zero_for_one)
+                .map(|tick_state| tick_state.tick),
+            None => None,
+        };
+        // without an initialized tick in this array, step to its boundary
+        let (tick_next, initialized) = match next_initialized_tick {
+            Some(tick_next) => (tick_next, true),
+            None if zero_for_one => (search_start_index, false),
+            None => (
+                search_start_index + TickArrayState::tick_count(tick_spacing)
+                    - i32::from(tick_spacing),
+                false,
+            ),
+        };
+        let tick_next = tick_next.clamp(MIN_TICK, MAX_TICK);
+        let sqrt_price_next_x64 = get_sqrt_price_at_tick(tick_next)?;
+
+        let sqrt_price_target_x64 = if (zero_for_one && sqrt_price_next_x64 < sqrt_price_limit_x64)
+            || (!zero_for_one && sqrt_price_next_x64 > sqrt_price_limit_x64)
+        {
+            sqrt_price_limit_x64
+        } else {
+            sqrt_price_next_x64
+        };
+        let step = compute_swap_step(
+            sqrt_price_x64,
+            sqrt_price_target_x64,
+            liquidity,
+            amount_remaining,
+            amm_config.trade_fee_rate,
+            is_base_input,
+            zero_for_one,
+        )?;
+        let step_sqrt_price_start_x64 = sqrt_price_x64;
+        sqrt_price_x64 = step.sqrt_price_next_x64;
+
+        let step_amount_in = step
+            .amount_in
+            .checked_add(step.fee_amount)
+            .ok_or(ErrorCode::MaxTokenOverflow)?;
+        if is_base_input {
+            amount_remaining = amount_remaining
+                .checked_sub(step_amount_in)
+                .ok_or(ErrorCode::MaxTokenOverflow)?;
+            amount_calculated = amount_calculated
+                .checked_add(step.amount_out)
+                .ok_or(ErrorCode::MaxTokenOverflow)?;
+        } else {
+            amount_remaining = amount_remaining
+                .checked_sub(step.amount_out)
+                .ok_or(ErrorCode::MaxTokenOverflow)?;
+            amount_calculated = amount_calculated
+                .checked_add(step_amount_in)
+                .ok_or(ErrorCode::MaxTokenOverflow)?;
+        }
+
+        // the protocol, fund, fee schedule buckets, pool creator and referrer take their share
+        // of the fee, the rest goes to in range liquidity
+        let protocol_fee =
+            fees::Fees::protocol_fee(u128::from(step.fee_amount), amm_config.protocol_fee_rate)
+                .unwrap() as u64;
+        let fund_fee = fees::Fees::fund_fee(u128::from(step.fee_amount), amm_config.fund_fee_rate)
+            .unwrap() as u64;
+        result.fee_amount += step.fee_amount;
+        result.protocol_fee += protocol_fee;
+        result.fund_fee += fund_fee;
+        let bucket_fees = amm_config.scheduled_fees(fee_schedule, u128::from(step.fee_amount))?;
+        for (result_fee, bucket_fee) in result.bucket_fees.iter_mut().zip(bucket_fees) {
+            *result_fee += bucket_fee;
+        }
+        let creator_fee = fees::floor_div(
+            u128::from(step.fee_amount),
+            u128::from(creator_fee_rate),
+            u128::from(fees::FEE_RATE_DENOMINATOR_VALUE),
+        )
+        .unwrap() as u64;
+        result.creator_fee += creator_fee;
+        let referral_fee = fees::Fees::referral_fee(u128::from(step.fee_amount), referral_fee_rate)
+            .unwrap() as u64;
+        result.referral_fee += referral_fee;
+        let lp_fee = step.fee_amount
+            - protocol_fee
+            - fund_fee
+            - bucket_fees.iter().sum::<u64>()
+            - creator_fee
+            - referral_fee;
+        if liquidity > 0 && lp_fee > 0 {
+            let fee_growth_delta_x64 =
+                mul_div_floor(U256::from(lp_fee), U256::from(Q64), U256::from(liquidity))
+                    .unwrap()
+                    .as_u128();
+            fee_growth_global_x64 = fee_growth_global_x64.wrapping_add(fee_growth_delta_x64);
+        }
+
+        if sqrt_price_x64 == sqrt_price_next_x64 {
+            // the price reached the next tick, cross it if it holds liquidity
+            if initialized {
+                let (fee_growth_global_0_x64, fee_growth_global_1_x64) = if zero_for_one {
+                    (fee_growth_global_x64, clmm_state.fee_growth_global_1_x64)
+                } else {
+                    (clmm_state.fee_growth_global_0_x64, fee_growth_global_x64)
+                };
+                let mut liquidity_net = tick_array
+                    .as_ref()
+                    .unwrap()
+                    .load_mut()?
+                    .get_tick_state_mut(tick_next, tick_spacing)?
+                    .cross(fee_growth_global_0_x64, fee_growth_global_1_x64);
+                // moving left, liquidity_net is subtracted
+                if zero_for_one {
+                    liquidity_net = -liquidity_net;
+                }
+                liquidity = add_delta(liquidity, liquidity_net)?;
+            }
+            tick = if zero_for_one {
+                tick_next - 1
+            } else {
+                tick_next
+            };
+        } else if sqrt_price_x64 != step_sqrt_price_start_x64 {
+            // recompute unless we're on a lower tick boundary (i.e. already transitioned ticks), and haven't moved
+            tick = get_tick_at_sqrt_price(sqrt_price_x64)?;
+        }
+    }
+
+    clmm_state.sqrt_price_x64 = sqrt_price_x64;
+    clmm_state.tick_current = tick;
+    clmm_state.liquidity = liquidity;
+    if zero_for_one {
+        clmm_state.fee_growth_global_0_x64 = fee_growth_global_x64;
+    } else {
+        clmm_state.fee_growth_global_1_x64 = fee_growth_global_x64;
+    }
+
+    let amount_consumed = amount_specified - amount_remaining;
+    (result.amount_in, result.amount_out) = if is_base_input {
+        (amount_consumed, amount_calculated)
+    } else {
+        (amount_calculated, amount_consumed)
+    };
+    Ok(result)
+}
+
+/// Concentrated swaps write no observation and take no dynamic fee
+pub fn swap_clmm<'a, 'b, 'c: 'info, 'info>(
+    ctx: Context<'a, 'b, 'c, 'info, SwapClmm<'info>>,
+    amount: u64,
+    other_amount_threshold: u64,
+    sqrt_price_limit_x64: u128,
+    is_base_input: bool,
+) -> Result<()> {
+    let block_timestamp = solana_program::clock::Clock::get()?.unix_timestamp as u64;
+    let pool_id = ctx.accounts.pool_state.key();
+    let pool_state = &mut ctx.accounts.pool_state.load_mut()?;
+    if !pool_state.get_status_by_bit(PoolStatusBitIndex::Swap)
+        || block_timestamp < pool_state.open_time
+    {
+        return err!(ErrorCode::NotApproved);
+    }
+
+    let zero_for_one = if ctx.accounts.input_vault.key() == pool_state.token_0_vault
+        && ctx.accounts.output_vault.key() == pool_state.token_1_vault
+    {
+        true
+    } else if ctx.accounts.input_vault.key() == pool_state.token_1_vault
+        && ctx.accounts.output_vault.key() == pool_state.token_0_vault
+    {
+        false
+    } else {
+        return err!(ErrorCode::InvalidVault);
+    };
+
+    // check in|out tax
+    let (in_tax_rate, out_tax_rate) = pool_state.payer_tax_rates(
+        block_timestamp,
+        ctx.accounts.input_token_mint.key(),
+        ctx.accounts
+            .tax_exemption
+            .as_deref()
+            .map(|tax_exemption| &**tax_exemption),
+    );
+    let has_out_tax = out_tax_rate > 0;
+    let has_in_tax = in_tax_rate > 0;
+
+    let (input_reserve, output_reserve) = if zero_for_one {
+        pool_state.vault_amount_without_fee(
+            ctx.accounts.input_vault.amount,
+            ctx.accounts.output_vault.amount,
+        )
+    } else {
+        let (output_reserve, input_reserve) = pool_state.vault_amount_without_fee(
+            ctx.accounts.output_vault.amount,
+            ctx.accounts.input_vault.amount,
+        );
+        (input_reserve, output_reserve)
+    };
+
+    // the amount the curve has to swap, after transfer fee and tax
+    let amount_specified = if is_base_input {
+        let transfer_fee =
+            get_transfer_fee(&ctx.accounts.input_token_mint.to_account_info(), amount)?;
+        let amount_in_without_transfer_fee = amount.saturating_sub(transfer_fee);
+        let in_tax = if has_in_tax {
+            u64::try_from(
+                tax_amount(amount_in_without_transfer_fee, in_tax_rate)
+                    .ok_or(ErrorCode::TaxAmountCalculationFailed)?,
+            )
+            .unwrap()
+        } else {
+            0
+        };
+        amount_in_without_transfer_fee.saturating_sub(in_tax)
+    } else {
+        let amount_out_with_tax = if has_out_tax {
+            u64::try_from(
+                fees::Fees::calculate_pre_fee_amount(u128::from(amount), out_tax_rate)
+                    .ok_or(ErrorCode::TaxAmountCalculationFailed)?,
+            )
+            .unwrap()
+        } else {
+            amount
+        };
+        let out_transfer_fee =
+            get_transfer_inverse_fee(&ctx.accounts.output_token_mint.to_account_info(), amount)?;
+        amount_out_with_tax.checked_add(out_transfer_fee).unwrap()
+    };
+    require_gt!(amount_specified, 0);
+
+    let result = {
+        let clmm_state = &mut ctx.accounts.clmm_state.load_mut()?;
+        swap_internal(
+            clmm_state,
+            &pool_id,
+            ctx.remaining_accounts,
+            &ctx.accounts.amm_config,
+            ctx.accounts
+                .fee_schedule
+                .as_deref()
+                .map(|fee_schedule| &**fee_schedule),
+            pool_state
+                .creator_fee_rate
+                .min(ctx.accounts.amm_config.max_creator_fee_rate),
+            ctx.accounts.amm_config.referral_fee_rate(
+                ctx.accounts.payer.key(),
+                ctx.accounts
+                    .referrer_token_account
+                    .as_ref()
+                    .map(|referrer_token_account| referrer_token_account.owner),
+            )?,
+            amount_specified,
+            sqrt_price_limit_x64,
+            zero_for_one,
+            is_base_input,
+        )?
+    };
+    if !is_base_input {
+        // a price limit must not leave the requested output partially filled
+        require_eq!(
+            result.amount_out,
+            amount_specified,
+            ErrorCode::ExceededSlippage
+        );
+    }
+    require_gt!(result.amount_out, 0, ErrorCode::ZeroTradingTokens);
+
+    let (in_tax, input_transfer_amount) = if is_base_input && result.amount_in == amount_specified {
+        // fully filled, charge exactly what the user asked to swap
+        let transfer_fee =
+            get_transfer_fee(&ctx.accounts.input_token_mint.to_account_info(), amount)?;
+        let amount_in_without_transfer_fee = amount.saturating_sub(transfer_fee);
+        (amount_in_without_transfer_fee - result.amount_in, amount)
+    } else {
+        // gross up the consumed input by the in tax and transfer fee
+        let amount_in_with_tax = if has_in_tax {
+            u64::try_from(
+                fees::Fees::calculate_pre_fee_amount(u128::from(result.amount_in), in_tax_rate)
+                    .ok_or(ErrorCode::TaxAmountCalculationFailed)?,
+            )
+            .unwrap()
+        } else {
+            result.amount_in
+        };
+        let transfer_fee = get_transfer_inverse_fee(
+            &ctx.accounts.input_token_mint.to_account_info(),
+            amount_in_with_tax,
+        )?;
+        (
+            amount_in_with_tax - result.amount_in,
+            amount_in_with_tax.checked_add(transfer_fee).unwrap(),
+        )
+    };
+
+    // calculate amount out with tax
+    let out_tax = if has_out_tax {
+        if is_base_input {
+            u64::try_from(
+                tax_amount(result.amount_out, out_tax_rate)
+                    .ok_or(ErrorCode::TaxAmountCalculationFailed)?,
+            )
+            .unwrap()
+        } else {
+            let amount_out_with_tax =
+                fees::Fees::calculate_pre_fee_amount(u128::from(amount), out_tax_rate)
+                    .ok_or(ErrorCode::TaxAmountCalculationFailed)?;
+            u64::try_from(amount_out_with_tax).unwrap() - amount
+        }
+    } else {
+        0
+    };
+    let output_transfer_amount = result.amount_out.checked_sub(out_tax).unwrap();
+
+    // check slippage
+    if is_base_input {
+        let transfer_fee = get_transfer_fee(
+            &ctx.accounts.output_token_mint.to_account_info(),
+            output_transfer_amount,
+        )?;
+        let amount_received = output_transfer_amount.checked_sub(transfer_fee).unwrap();
+        require_gt!(amount_received, 0);
+        require_gte!(
+            amount_received,
+            other_amount_threshold,
+            ErrorCode::ExceededSlippage
+        );
+    } else {
+        require_gte!(
+            other_amount_threshold,
+            input_transfer_amount,
+            ErrorCode::ExceededSlippage
+        );
+    }
+
+    // check trade limits on the taxed token
+    if pool_state.tax_mint == ctx.accounts.input_token_mint.key() {
+        let transfer_fee = get_transfer_fee(
+            &ctx.accounts.input_token_mint.to_account_info(),
+            input_transfer_amount,
+        )?;
+        pool_state.check_max_tx(
+            block_timestamp,
+            input_transfer_amount - transfer_fee,
+            input_reserve,
+        )?;
+    } else {
+        pool_state.check_max_tx(block_timestamp, result.amount_out, output_reserve)?;
+    }
+
+    // update tax in vault, each side accrues its own tax
+    pool_state.add_tax_amount(ctx.accounts.input_token_mint.key(), in_tax);
+    pool_state.add_tax_amount(ctx.accounts.output_token_mint.key(), out_tax);
+
+    #[cfg(feature = "enable-log")]
+    msg!(
+        "amount_in:{}, amount_out:{}, fee_amount:{}, in_tax:{}, out_tax:{}",
+        result.amount_in,
+        result.amount_out,
+        result.fee_amount,
+        in_tax,
+        out_tax,
+    );
+
+    // update fee in vault
+    if zero_for_one {
+        pool_state.protocol_fees_token_0 = pool_state
+            .protocol_fees_token_0
+            .checked_add(result.protocol_fee)
+            .unwrap();
+        pool_state.fund_fees_token_0 = pool_state
+            .fund_fees_token_0
+            .checked_add(result.fund_fee)
+            .unwrap();
+    } else {
+        pool_state.protocol_fees_token_1 = pool_state
+            .protocol_fees_token_1
+            .checked_add(result.protocol_fee)
+            .unwrap();
+        pool_state.fund_fees_token_1 = pool_state
+            .fund_fees_token_1
+            .checked_add(result.fund_fee)
+            .unwrap();
+    }
+    pool_state.add_bucket_fees(zero_for_one, &result.bucket_fees);
+    pool_state.add_creator_fee(zero_for_one, result.creator_fee);
+
+    transfer_from_user_to_pool_vault(
+        ctx.accounts.payer.to_account_info(),
+        ctx.accounts.input_token_account.to_account_info(),
+        ctx.accounts.input_vault.to_account_info(),
+        ctx.accounts.input_token_mint.to_account_info(),
+        ctx.accounts.input_token_program.to_account_info(),
+        input_transfer_amount,
+        ctx.accounts.input_token_mint.decimals,
+    )?;
+
+    transfer_from_pool_vault_to_user(
+        ctx.accounts.authority.to_account_info(),
+        ctx.accounts.output_vault.to_account_info(),
+        ctx.accounts.output_token_account.to_account_info(),
+        ctx.accounts.output_token_mint.to_account_info(),
+        ctx.accounts.output_token_program.to_account_info(),
+        output_transfer_amount,
+        ctx.accounts.output_token_mint.decimals,
+        &[&[crate::AUTH_SEED.as_bytes(), &[pool_state.auth_bump]]],
+    )?;
+
+    // the referral fee comes out of the trade fee left to the liquidity
+    if let Some(referrer_token_account) = &ctx.accounts.referrer_token_account {
+        if result.referral_fee > 0 {
+            transfer_from_pool_vault_to_user(
+                ctx.accounts.authority.to_account_info(),
+                ctx.accounts.input_vault.to_account_info(),
+                referrer_token_account.to_account_info(),
+                ctx.accounts.input_token_mint.to_account_info(),
+                ctx.accounts.input_token_program.to_account_info(),
+                result.referral_fee,
+                ctx.accounts.input_token_mint.decimals,
+                &[&[crate::AUTH_SEED.as_bytes(), &[pool_state.auth_bump]]],
+            )?;
+        }
+    }
+
+    // check the balance a buy of the taxed token leaves
+    if pool_state.tax_mint == ctx.accounts.output_token_mint.key()
+        && pool_state.has_max_wallet(block_timestamp)
+    {
+        ctx.accounts.output_token_account.reload()?;
+        pool_state.check_max_wallet(block_timestamp, ctx.accounts.output_token_account.amount)?;
+    }
+
+    ctx.accounts.input_vault.reload()?;
+    ctx.accounts.output_vault.reload()?;
+
+    let (reserve_0, reserve_1) = if zero_for_one {
+        pool_state.vault_amount_without_fee(
+            ctx.accounts.input_vault.amount,
+            ctx.accounts.output_vault.amount,
+        )
+    } else {
+        pool_state.vault_amount_without_fee(
+            ctx.accounts.output_vault.amount,
+            ctx.accounts.input_vault.amount,
+        )
+    };
+
+    emit_cpi!(SwapEvent {
+        pool_id,
+        token_in: ctx.accounts.input_token_account.mint,
+        token_out: ctx.accounts.output_token_account.mint,
+        amount_in: result.amount_in,
+        amount_out: result.amount_out,
+        reserve_0,
+        reserve_1,
+        referral_fee: result.referral_fee,
+    });
+
+    Ok(())
+}
@@ -0,0 +1,90 @@
+use crate::error::ErrorCode;
+use crate::instructions::collect_bucket_fees;
+use crate::states::*;
+use anchor_lang::prelude::*;
+use anchor_spl::token::Token;
+use anchor_spl::token_interface::Mint;
+use anchor_spl::token_interface::Token2022;
+use anchor_spl::token_interface::TokenAccount;
+
+#[derive(Accounts)]
+pub struct CollectCreatorFee<'info> {
+    /// Only the pool creator can collect the creator fee
+    #[account(constraint = owner.key() == pool_state.load()?.pool_creator @ ErrorCode::InvalidOwner)]
+    pub owner: Signer<'info>,
+
+    /// CHECK: pool vault and lp mint authority
+    #[account(
+        seeds = [
+            crate::AUTH_SEED.as_bytes(),
+        ],
+        bump,
+    )]
+    pub authority: UncheckedAccount<'info>,
+
+    /// Pool state stores accumulated creator fee amount
+    #[account(mut)]
+    pub pool_state: AccountLoader<'info, PoolState>,
+
+    /// The address that holds pool tokens for token_0
+    #[account(
+        mut,
+        constraint = token_0_vault.key() == pool_state.load()?.token_0_vault
+    )]
+    pub token_0_vault: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    /// The address that holds pool tokens for token_1
+    #[account(
+        mut,
+        constraint = token_1_vault.key() == pool_state.load()?.token_1_vault
+    )]
+    pub token_1_vault: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    /// The mint of token_0 vault
+    #[account(
+        address = token_0_vault.mint
+    )]
+    pub vault_0_mint: Box<InterfaceAccount<'info, Mint>>,
+
+    /// The mint of token_1 vault
+    #[account(
+        address = token_1_vault.mint
+    )]
+    pub vault_1_mint: Box<InterfaceAccount<'info, Mint>>,
+
+    /// The address that receives the collected token_0 creator fees
+    #[account(mut)]
+    pub recipient_token_0_account: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    /// The address that receives the collected token_1 creator fees
+    #[account(mut)]
+    pub recipient_token_1_account: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    /// The SPL program to perform token transfers
+    pub token_program: Program<'info, Token>,
+
+    /// The SPL program 2022 to perform token transfers
+    pub token_program_2022: Program<'info, Token2022>,
+}
+
+pub fn collect_creator_fee(
+    ctx: Context<CollectCreatorFee>,
+    amount_0_requested: u64,
+    amount_1_requested: u64,
+) -> Result<()> {
+    collect_bucket_fees(
+        &ctx.accounts.pool_state,
+        ctx.accounts.authority.to_account_info(),
+        &ctx.accounts.token_0_vault,
+        &ctx.accounts.token_1_vault,
+        &ctx.accounts.vault_0_mint,
+        &ctx.accounts.vault_1_mint,
+        &ctx.accounts.recipient_token_0_account,
+        &ctx.accounts.recipient_token_1_account,
+        &ctx.accounts.token_program,
+        &ctx.accounts.token_program_2022,
+        CREATOR_FEE_BUCKET,
+        amount_0_requested,
+        amount_1_requested,
+    )
+}
@@ -10,8 +10,8 @@ use anchor_spl::token_interface::TokenAccount;
 #[event_cpi]
 #[derive(Accounts)]
 pub struct CollectTax<'info> {
-    /// owner of pool
-    #[account(address = pool_state.load()?.pool_creator @ ErrorCode::InvalidOwner)]
+    /// tax authority or tax recipient of pool, anyone else can only crank
+    /// the tax to the tax recipient
     pub owner: Signer<'info>,
 
     #[account(mut)]
@@ -44,25 +44,40 @@ pub struct CollectTax<'info> {
     )]
     pub token_1_vault: Box<InterfaceAccount<'info, TokenAccount>>,
 
-    /// The mint of token_0 vault
+    /// The mint of token_0 vault, burned from in burn mode
     #[account(
+        mut,
         address = token_0_vault.mint
     )]
     pub vault_0_mint: Box<InterfaceAccount<'info, Mint>>,
 
-    /// The mint of token_1 vault
+    /// The mint of token_1 vault, burned from in burn mode
     #[account(
+        mut,
         address = token_1_vault.mint
     )]
     pub vault_1_mint: Box<InterfaceAccount<'info, Mint>>,
 
-    /// The address that receives the collected token_0 tax
+    /// CHECK: the tax recipients of the pool, not initialized when the tax
+    /// goes to a single recipient
+    #[account(
+        seeds = [
+            TAX_RECIPIENTS_SEED.as_bytes(),
+            pool_state.key().as_ref(),
+        ],
+        bump,
+    )]
+    pub tax_recipients: UncheckedAccount<'info>,
+
+    /// The address that receives the collected token_0 tax, only needed when
+    /// token_0 tax is paid out and the pool has no tax recipients
     #[account(mut)]
-    pub recipient_token_0_account: Box<InterfaceAccount<'info, TokenAccount>>,
+    pub recipient_token_0_account: Option<Box<InterfaceAccount<'info, TokenAccount>>>,
 
-    /// The address that receives the collected token_1 tax
+    /// The address that receives the collected token_1 tax, only needed when
+    /// token_1 tax is paid out and the pool has no tax recipients
     #[account(mut)]
-    pub recipient_token_1_account: Box<InterfaceAccount<'info, TokenAccount>>,
+    pub recipient_token_1_account: Option<Box<InterfaceAccount<'info, TokenAccount>>>,
 
     /// The SPL program to perform token transfers
     pub token_program: Program<'info, Token>,
@@ -71,64 +86,309 @@ pub struct CollectTax<'info> {
     pub token_program_2022: Program<'info, Token2022>,
 }
 
-pub fn collect_tax(ctx: Context<CollectTax>) -> Result<()> {
+/// remaining accounts: when the pool has tax recipients, their token_0
+/// accounts if token_0 tax is collected, then their token_1 accounts if token_1
+/// tax is collected, in the order of the recipients
+pub fn collect_tax<'a, 'b, 'c: 'info, 'info>(
+    ctx: Context<'a, 'b, 'c, 'info, CollectTax<'info>>,
+    amount_0_requested: u64,
+    amount_1_requested: u64,
+) -> Result<()> {
     let amount_0: u64;
     let amount_1: u64;
     let auth_bump: u8;
+    let burn_token_0: Option<bool>;
+    let burned_amount: u64;
+    let transfer_amount_0: u64;
+    let transfer_amount_1: u64;
+    let tax_payee: Option<Pubkey>;
     {
         let mut pool_state = ctx.accounts.pool_state.load_mut()?;
 
         require!(!pool_state.tax_disabled, ErrorCode::TaxDisabled);
 
-        amount_0 = pool_state.tax_amount_0;
-        amount_1 = pool_state.tax_amount_1;
+        // a permissionless collection pays the configured recipient only
+        tax_payee = (!pool_state.is_tax_collector(ctx.accounts.owner.key()))
+            .then(|| pool_state.tax_payee());
 
-        require!(amount_0 > 0 || amount_1 > 0, ErrorCode::NoPendingTax);
-
-        pool_state.tax_amount_0 = 0;
-        pool_state.tax_amount_1 = 0;
+        (amount_0, amount_1) = pool_state.take_tax(amount_0_requested, amount_1_requested)?;
         auth_bump = pool_state.auth_bump;
+        burn_token_0 = pool_state.tax_burn_token_0();
+        (burned_amount, transfer_amount_0, transfer_amount_1) =
+            pool_state.split_tax_burn(amount_0, amount_1);
     }
 
-    if amount_0 > 0 {
-        transfer_from_pool_vault_to_user(
+    let token_0_program =
+        if ctx.accounts.vault_0_mint.to_account_info().owner == ctx.accounts.token_program.key {
+            ctx.accounts.token_program.to_account_info()
+        } else {
+            ctx.accounts.token_program_2022.to_account_info()
+        };
+    let token_1_program =
+        if ctx.accounts.vault_1_mint.to_account_info().owner == ctx.accounts.token_program.key {
+            ctx.accounts.token_program.to_account_info()
+        } else {
+            ctx.accounts.token_program_2022.to_account_info()
+        };
+
+    // burn the tax of the tax mint, pay out the rest
+    match burn_token_0 {
+        Some(true) => burn_tax_from_vault(
             ctx.accounts.authority.to_account_info(),
-            ctx.accounts.token_0_vault.to_account_info(),
-            ctx.accounts.recipient_token_0_account.to_account_info(),
+            token_0_program.clone(),
             ctx.accounts.vault_0_mint.to_account_info(),
-            if ctx.accounts.vault_0_mint.to_account_info().owner == ctx.accounts.token_program.key {
-                ctx.accounts.token_program.to_account_info()
-            } else {
-                ctx.accounts.token_program_2022.to_account_info()
-            },
-            amount_0,
-            ctx.accounts.vault_0_mint.decimals,
-            &[&[crate::AUTH_SEED.as_bytes(), &[auth_bump]]],
-        )?;
+            ctx.accounts.token_0_vault.to_account_info(),
+            burned_amount,
+            auth_bump,
+        )?,
+        Some(false) => burn_tax_from_vault(
+            ctx.accounts.authority.to_account_info(),
+            token_1_program.clone(),
+            ctx.accounts.vault_1_mint.to_account_info(),
+            ctx.accounts.token_1_vault.to_account_info(),
+            burned_amount,
+            auth_bump,
+        )?,
+        None => {}
+    }
+
+    let tax_recipients = if ctx.accounts.tax_recipients.data_is_empty() {
+        None
+    } else {
+        Some(TaxRecipients::try_deserialize(
+            &mut &ctx.accounts.tax_recipients.try_borrow_data()?[..],
+        )?)
     }
+    .filter(|tax_recipients| tax_recipients.count > 0);
 
-    if amount_1 > 0 {
+    // each side only needs the accounts of its own recipients
+    let mut remaining_accounts = ctx.remaining_accounts;
+    let mut side_accounts = |amount: u64| -> &'info [AccountInfo<'info>] {
+        match &tax_recipients {
+            Some(tax_recipients) if amount > 0 => {
+                let len =
+                    std::cmp::min(tax_recipients.recipients().len(), remaining_accounts.len());
+                let (side, rest) = remaining_accounts.split_at(len);
+                remaining_accounts = rest;
+                side
+            }
+            _ => &[],
+        }
+    };
+    let recipient_accounts_0 = side_accounts(transfer_amount_0);
+    let recipient_accounts_1 = side_accounts(transfer_amount_1);
+
+    let dust_0 = pay_tax(
+        ctx.accounts.authority.to_account_info(),
+        ctx.accounts.token_0_vault.to_account_info(),
+        &ctx.accounts.vault_0_mint,
+        token_0_program,
+        ctx.accounts.recipient_token_0_account.as_deref(),
+        recipient_accounts_0,
+        tax_recipients.as_ref(),
+        tax_payee,
+        transfer_amount_0,
+        auth_bump,
+    )?;
+    let dust_1 = pay_tax(
+        ctx.accounts.authority.to_account_info(),
+        ctx.accounts.token_1_vault.to_account_info(),
+        &ctx.accounts.vault_1_mint,
+        token_1_program,
+        ctx.accounts.recipient_token_1_account.as_deref(),
+        recipient_accounts_1,
+        tax_recipients.as_ref(),
+        tax_payee,
+        transfer_amount_1,
+        auth_bump,
+    )?;
+
+    emit_cpi!(TaxCollectEvent {
+        pool_id: ctx.accounts.pool_state.key(),
+        amount_0,
+        amount_1,
+        dust_0,
+        dust_1,
+        burned_amount,
+    });
+
+    Ok(())
+}
+
+/// Pay `amount` of one side of the tax, split across the tax recipients when
+/// the pool has them, otherwise to the recipient token account. Returns the
+/// rounding dust of the split
+fn pay_tax<'info>(
+    authority: AccountInfo<'info>,
+    vault: AccountInfo<'info>,
+    mint: &InterfaceAccount<'info, Mint>,
+    token_program: AccountInfo<'info>,
+    recipient_token_account: Option<&InterfaceAccount<'info, TokenAccount>>,
+    recipient_accounts: &'info [AccountInfo<'info>],
+    tax_recipients: Option<&TaxRecipients>,
+    tax_payee: Option<Pubkey>,
+    amount: u64,
+    auth_bump: u8,
+) -> Result<u64> {
+    if amount == 0 {
+        return Ok(0);
+    }
+
+    let Some(tax_recipients) = tax_recipients else {
+        let recipient_token_account = recipient_token_account.ok_or(ErrorCode::InvalidInput)?;
+        require_keys_eq!(
+            recipient_token_account.mint,
+            mint.key(),
+            ErrorCode::InvalidInput
+        );
+        if let Some(tax_payee) = tax_payee {
+            require_keys_eq!(
+                recipient_token_account.owner,
+                tax_payee,
+                ErrorCode::InvalidOwner
+            );
+        }
         transfer_from_pool_vault_to_user(
-            ctx.accounts.authority.to_account_info(),
-            ctx.accounts.token_1_vault.to_account_info(),
-            ctx.accounts.recipient_token_1_account.to_account_info(),
-            ctx.accounts.vault_1_mint.to_account_info(),
-            if ctx.accounts.vault_1_mint.to_account_info().owner == ctx.accounts.token_program.key {
-                ctx.accounts.token_program.to_account_info()
-            } else {
-                ctx.accounts.token_program_2022.to_account_info()
-            },
-            amount_1,
-            ctx.accounts.vault_1_mint.decimals,
+            authority,
+            vault,
+            recipient_token_account.to_account_info(),
+            mint.to_account_info(),
+            token_program,
+            amount,
+            mint.decimals,
             &[&[crate::AUTH_SEED.as_bytes(), &[auth_bump]]],
         )?;
+        return Ok(0);
+    };
+
+    let recipients = tax_recipients.recipients();
+    require_eq!(
+        recipient_accounts.len(),
+        recipients.len(),
+        ErrorCode::InvalidTaxRecipients
+    );
+    let (shares, dust) = tax_recipients.split(amount);
+    for (i, recipient) in recipients.iter().enumerate() {
+        let recipient_token_account =
+            InterfaceAccount::<TokenAccount>::try_from(&recipient_accounts[i])?;
+        require!(
+            recipient_token_account.owner == recipient.wallet
+                && recipient_token_account.mint == mint.key(),
+            ErrorCode::InvalidTaxRecipients
+        );
+        if shares[i] > 0 {
+            transfer_from_pool_vault_to_user(
+                authority.clone(),
+                vault.clone(),
+                recipient_token_account.to_account_info(),
+                mint.to_account_info(),
+                token_program.clone(),
+                shares[i],
+                mint.decimals,
+                &[&[crate::AUTH_SEED.as_bytes(), &[auth_bump]]],
+            )?;
+        }
     }
+    Ok(dust)
+}
+
+/// Burns the accrued tax of the tax mint of a pool in burn mode, anyone can
+/// crank it
+#[event_cpi]
+#[derive(Accounts)]
+pub struct BurnTax<'info> {
+    #[account(mut)]
+    pub pool_state: AccountLoader<'info, PoolState>,
+
+    /// CHECK: pool vault authority
+    #[account(
+        seeds = [
+            crate::AUTH_SEED.as_bytes(),
+        ],
+        bump,
+    )]
+    pub authority: UncheckedAccount<'info>,
+
+    /// The vault of the tax mint
+    #[account(
+        mut,
+        constraint = (tax_vault.key() == pool_state.load()?.token_0_vault
+            || tax_vault.key() == pool_state.load()?.token_1_vault)
+            @ ErrorCode::InvalidVault
+    )]
+    pub tax_vault: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    /// The mint tax is taken in
+    #[account(
+        mut,
+        address = pool_state.load()?.tax_mint,
+        constraint = tax_mint.key() == tax_vault.mint @ ErrorCode::InvalidVault
+    )]
+    pub tax_mint: Box<InterfaceAccount<'info, Mint>>,
+
+    /// The SPL program to burn tokens
+    pub token_program: Program<'info, Token>,
+
+    /// The SPL program 2022 to burn tokens
+    pub token_program_2022: Program<'info, Token2022>,
+}
+
+pub fn burn_tax(ctx: Context<BurnTax>) -> Result<()> {
+    let amount_0: u64;
+    let amount_1: u64;
+    let auth_bump: u8;
+    {
+        let mut pool_state = ctx.accounts.pool_state.load_mut()?;
+        (amount_0, amount_1) = pool_state.take_burn_tax()?;
+        auth_bump = pool_state.auth_bump;
+    }
+    let amount = amount_0 + amount_1;
+
+    let token_program =
+        if ctx.accounts.tax_mint.to_account_info().owner == ctx.accounts.token_program.key {
+            ctx.accounts.token_program.to_account_info()
+        } else {
+            ctx.accounts.token_program_2022.to_account_info()
+        };
+
+    burn_tax_from_vault(
+        ctx.accounts.authority.to_account_info(),
+        token_program,
+        ctx.accounts.tax_mint.to_account_info(),
+        ctx.accounts.tax_vault.to_account_info(),
+        amount,
+        auth_bump,
+    )?;
 
     emit_cpi!(TaxCollectEvent {
         pool_id: ctx.accounts.pool_state.key(),
         amount_0,
         amount_1,
+        dust_0: 0,
+        dust_1: 0,
+        burned_amount: amount,
     });
 
     Ok(())
 }
+
+fn burn_tax_from_vault<'info>(
+    authority: AccountInfo<'info>,
+    token_program: AccountInfo<'info>,
+    mint: AccountInfo<'info>,
+    vault: AccountInfo<'info>,
+    amount: u64,
+    auth_bump: u8,
+) -> Result<()> {
+    if amount == 0 {
+        return Ok(());
+    }
+    token_burn(
+        authority,
+        token_program,
+        mint,
+        vault,
+        amount,
+        &[&[crate::AUTH_SEED.as_bytes(), &[auth_bump]]],
+    )
+}
@@ -0,0 +1,217 @@
+use crate::curve::TradeDirection;
+use crate::error::ErrorCode;
+use crate::states::*;
+use crate::utils::token::*;
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program;
+use anchor_spl::token_interface::{Mint, TokenAccount, TokenInterface};
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct CollectTaxAsQuote<'info> {
+    /// tax authority or tax recipient of pool
+    #[account(constraint = pool_state.load()?.is_tax_collector(owner.key()) @ ErrorCode::InvalidOwner)]
+    pub owner: Signer<'info>,
+
+    /// CHECK: pool vault authority
+    #[account(
+        seeds = [
+            crate::AUTH_SEED.as_bytes(),
+        ],
+        bump,
+    )]
+    pub authority: UncheckedAccount<'info>,
+
+    /// The factory state to read protocol fees
+    #[account(address = pool_state.load()?.amm_config)]
+    pub amm_config: Box<Account<'info, AmmConfig>>,
+
+    #[account(mut)]
+    pub pool_state: AccountLoader<'info, PoolState>,
+
+    /// The vault of the tax mint, the accrued tax is swapped out of it
+    #[account(
+        mut,
+        constraint = tax_vault.key() == pool_state.load()?.token_0_vault || tax_vault.key() == pool_state.load()?.token_1_vault
+    )]
+    pub tax_vault: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    /// The vault of the quote token, the other side of the pool
+    #[account(
+        mut,
+        constraint = quote_vault.key() == pool_state.load()?.token_0_vault || quote_vault.key() == pool_state.load()?.token_1_vault
+    )]
+    pub quote_vault: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    /// The mint tax is taken in
+    #[account(
+        address = pool_state.load()?.tax_mint,
+        constraint = tax_mint.key() == tax_vault.mint @ ErrorCode::InvalidVault
+    )]
+    pub tax_mint: Box<InterfaceAccount<'info, Mint>>,
+
+    /// The mint of the quote token
+    #[account(
+        address = quote_vault.mint,
+        token::token_program = quote_token_program
+    )]
+    pub quote_mint: Box<InterfaceAccount<'info, Mint>>,
+
+    /// The address that receives the tax in the quote token
+    #[account(mut, token::mint = quote_mint)]
+    pub recipient_quote_account: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    /// SPL program for quote token transfers
+    pub quote_token_program: Interface<'info, TokenInterface>,
+
+    /// The program account for the most recent oracle observation
+    #[account(mut, address = pool_state.load()?.observation_key)]
+    pub observation_state: AccountLoader<'info, ObservationState>,
+
+    /// Fee schedule of the amm config, needed when it has fee buckets beyond
+    /// the protocol and fund buckets
+    #[account(
+        seeds = [
+            FEE_SCHEDULE_SEED.as_bytes(),
+            amm_config.key().as_ref(),
+        ],
+        bump = fee_schedule.bump,
+    )]
+    pub fee_schedule: Option<Box<Account<'info, FeeSchedule>>>,
+}
+
+pub fn collect_tax_as_quote(
+    ctx: Context<CollectTaxAsQuote>,
+    minimum_amount_out: u64,
+) -> Result<()> {
+    let block_timestamp = solana_program::clock::Clock::get()?.unix_timestamp as u64;
+    let pool_id = ctx.accounts.pool_state.key();
+    let pool_state = &mut ctx.accounts.pool_state.load_mut()?;
+    require!(!pool_state.is_concentrated(), ErrorCode::InvalidPoolType);
+    require!(!pool_state.tax_disabled, ErrorCode::TaxDisabled);
+    // burned tax is not for sale
+    require!(!pool_state.tax_burn, ErrorCode::NotApproved);
+    if !pool_state.get_status_by_bit(PoolStatusBitIndex::Swap)
+        || block_timestamp < pool_state.open_time
+    {
+        return err!(ErrorCode::NotApproved);
+    }
+
+    let (trade_direction, total_input_token_amount, total_output_token_amount) =
+        if ctx.accounts.tax_vault.key() == pool_state.token_0_vault
+            && ctx.accounts.quote_vault.key() == pool_state.token_1_vault
+        {
+            let (total_input_token_amount, total_output_token_amount) = pool_state
+                .vault_amount_without_fee(
+                    ctx.accounts.tax_vault.amount,
+                    ctx.accounts.quote_vault.amount,
+                );
+
+            (
+                TradeDirection::ZeroForOne,
+                total_input_token_amount,
+                total_output_token_amount,
+            )
+        } else if ctx.accounts.tax_vault.key() == pool_state.token_1_vault
+            && ctx.accounts.quote_vault.key() == pool_state.token_0_vault
+        {
+            let (total_output_token_amount, total_input_token_amount) = pool_state
+                .vault_amount_without_fee(
+                    ctx.accounts.quote_vault.amount,
+                    ctx.accounts.tax_vault.amount,
+                );
+
+            (
+                TradeDirection::OneForZero,
+                total_input_token_amount,
+                total_output_token_amount,
+            )
+        } else {
+            return err!(ErrorCode::InvalidVault);
+        };
+
+    let quote_mint = ctx.accounts.quote_mint.to_account_info();
+    let (result, output_transfer_amount) = pool_state.swap_tax_as_quote(
+        block_timestamp,
+        trade_direction,
+        total_input_token_amount,
+        total_output_token_amount,
+        &ctx.accounts.amm_config,
+        ctx.accounts
+            .fee_schedule
+            .as_deref()
+            .map(|fee_schedule| &**fee_schedule),
+        minimum_amount_out,
+        |amount_out| get_transfer_fee(&quote_mint, amount_out),
+    )?;
+    let amount_in = u64::try_from(result.source_amount_swapped).unwrap();
+
+    #[cfg(feature = "enable-log")]
+    msg!(
+        "tax_amount:{}, destination_amount_swapped:{}, lp_fee:{}",
+        amount_in,
+        result.destination_amount_swapped,
+        result.lp_fee
+    );
+
+    transfer_from_pool_vault_to_user(
+        ctx.accounts.authority.to_account_info(),
+        ctx.accounts.quote_vault.to_account_info(),
+        ctx.accounts.recipient_quote_account.to_account_info(),
+        ctx.accounts.quote_mint.to_account_info(),
+        ctx.accounts.quote_token_program.to_account_info(),
+        output_transfer_amount,
+        ctx.accounts.quote_mint.decimals,
+        &[&[crate::AUTH_SEED.as_bytes(), &[pool_state.auth_bump]]],
+    )?;
+
+    ctx.accounts.tax_vault.reload()?;
+    ctx.accounts.quote_vault.reload()?;
+
+    let (reserve_0, reserve_1) = match trade_direction {
+        TradeDirection::ZeroForOne => pool_state.vault_amount_without_fee(
+            ctx.accounts.tax_vault.amount,
+            ctx.accounts.quote_vault.amount,
+        ),
+        TradeDirection::OneForZero => pool_state.vault_amount_without_fee(
+            ctx.accounts.quote_vault.amount,
+            ctx.accounts.tax_vault.amount,
+        ),
+    };
+
+    let (token_0_price_x32, token_1_price_x32) =
+        pool_state.token_price_x32(reserve_0, reserve_1, block_timestamp);
+    let observation_state = &mut ctx.accounts.observation_state.load_mut()?;
+    pool_state.update_volatility(
+        block_timestamp,
+        observation_state.token_0_price_x32,
+        token_0_price_x32,
+    );
+    observation_state.update(block_timestamp, token_0_price_x32, token_1_price_x32);
+
+    emit_cpi!(SwapEvent {
+        pool_id,
+        token_in: ctx.accounts.tax_vault.mint,
+        token_out: ctx.accounts.quote_vault.mint,
+        amount_in,
+        amount_out: output_transfer_amount,
+        reserve_0,
+        reserve_1,
+        referral_fee: 0,
+    });
+
+    let (amount_0, amount_1) = match trade_direction {
+        TradeDirection::ZeroForOne => (amount_in, 0),
+        TradeDirection::OneForZero => (0, amount_in),
+    };
+    emit_cpi!(TaxCollectEvent {
+        pool_id,
+        amount_0,
+        amount_1,
+        dust_0: 0,
+        dust_1: 0,
+        burned_amount: 0,
+    });
+
+    Ok(())
+}
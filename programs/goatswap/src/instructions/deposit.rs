@@ -4,6 +4,7 @@ use crate::error::ErrorCode;
 use crate::states::*;
 use crate::utils::token::*;
 use anchor_lang::prelude::*;
+use anchor_lang::solana_program;
 use anchor_spl::token::Token;
 use anchor_spl::token_interface::{Mint, Token2022, TokenAccount};
 
@@ -83,6 +84,10 @@ pub struct Deposit<'info> {
         address = pool_state.load()?.lp_mint @ ErrorCode::IncorrectLpMint)
     ]
     pub lp_mint: Box<InterfaceAccount<'info, Mint>>,
+
+    /// The program account for the most recent oracle observation
+    #[account(mut, address = pool_state.load()?.observation_key)]
+    pub observation_state: AccountLoader<'info, ObservationState>,
 }
 
 pub fn deposit(
@@ -93,6 +98,14 @@ pub fn deposit(
 ) -> Result<()> {
     let pool_id = ctx.accounts.pool_state.key();
     let pool_state = &mut ctx.accounts.pool_state.load_mut()?;
+    require!(!pool_state.is_concentrated(), ErrorCode::InvalidPoolType);
+    if pool_state.is_liquidity_bootstrapping() {
+        require_keys_eq!(
+            ctx.accounts.owner.key(),
+            pool_state.pool_creator,
+            ErrorCode::InvalidOwner
+        );
+    }
     if !pool_state.get_status_by_bit(PoolStatusBitIndex::Deposit) {
         return err!(ErrorCode::NotApproved);
     }
@@ -101,7 +114,9 @@ pub fn deposit(
         ctx.accounts.token_0_vault.amount,
         ctx.accounts.token_1_vault.amount,
     );
+    let block_timestamp = solana_program::clock::Clock::get()?.unix_timestamp as u64;
     let results = CurveCalculator::lp_tokens_to_trading_tokens(
+        pool_state.curve(block_timestamp).as_ref(),
         u128::from(lp_token_amount),
         u128::from(pool_state.lp_supply),
         u128::from(total_token_0_amount),
@@ -110,7 +125,17 @@ pub fn deposit(
     )
     .ok_or(ErrorCode::ZeroTradingTokens)?;
 
-    let token_0_amount = u64::try_from(results.token_0_amount).unwrap();
+    // the deposit tax is paid on top of the tax mint leg
+    let (tax_0, tax_1) = pool_state.lp_tax(
+        u64::try_from(results.token_0_amount).unwrap(),
+        u64::try_from(results.token_1_amount).unwrap(),
+        pool_state.deposit_tax_rate,
+    );
+
+    let token_0_amount = u64::try_from(results.token_0_amount)
+        .unwrap()
+        .checked_add(tax_0)
+        .unwrap();
     let (transfer_token_0_amount, transfer_token_0_fee) = {
         let transfer_fee =
             get_transfer_inverse_fee(&ctx.accounts.vault_0_mint.to_account_info(), token_0_amount)?;
@@ -120,7 +145,10 @@ pub fn deposit(
         )
     };
 
-    let token_1_amount = u64::try_from(results.token_1_amount).unwrap();
+    let token_1_amount = u64::try_from(results.token_1_amount)
+        .unwrap()
+        .checked_add(tax_1)
+        .unwrap();
     let (transfer_token_1_amount, transfer_token_1_fee) = {
         let transfer_fee =
             get_transfer_inverse_fee(&ctx.accounts.vault_1_mint.to_account_info(), token_1_amount)?;
@@ -175,6 +203,8 @@ pub fn deposit(
         ctx.accounts.vault_1_mint.decimals,
     )?;
 
+    let tax_mint = pool_state.tax_mint;
+    pool_state.add_tax_amount(tax_mint, tax_0 + tax_1);
     pool_state.lp_supply = pool_state.lp_supply.checked_add(lp_token_amount).unwrap();
 
     token_mint_to(
@@ -189,6 +219,18 @@ pub fn deposit(
     ctx.accounts.token_0_vault.reload()?;
     ctx.accounts.token_1_vault.reload()?;
 
+    let (reserve_0, reserve_1) = pool_state.vault_amount_without_fee(
+        ctx.accounts.token_0_vault.amount,
+        ctx.accounts.token_1_vault.amount,
+    );
+    let (token_0_price_x32, token_1_price_x32) =
+        pool_state.token_price_x32(reserve_0, reserve_1, block_timestamp);
+    ctx.accounts.observation_state.load_mut()?.update(
+        block_timestamp,
+        token_0_price_x32,
+        token_1_price_x32,
+    );
+
     emit_cpi!(LpChangeEvent {
         pool_id,
         amount_lp: lp_token_amount,
@@ -196,7 +238,8 @@ pub fn deposit(
         amount_1: u64::try_from(results.token_1_amount).unwrap(),
         reserve_0: ctx.accounts.token_0_vault.amount,
         reserve_1: ctx.accounts.token_1_vault.amount,
-        change_type: 1
+        change_type: 1,
+        tax_amount: tax_0 + tax_1,
     });
 
     Ok(())
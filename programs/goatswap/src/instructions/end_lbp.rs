@@ -0,0 +1,183 @@
+use crate::error::ErrorCode;
+use crate::states::*;
+use crate::utils::token::*;
+use anchor_lang::prelude::*;
+use anchor_spl::token::Token;
+use anchor_spl::token_interface::{Mint, Token2022, TokenAccount};
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct EndLbp<'info> {
+    /// The creator of the liquidity bootstrapping pool
+    #[account(constraint = creator.key() == pool_state.load()?.pool_creator @ ErrorCode::InvalidOwner)]
+    pub creator: Signer<'info>,
+
+    /// CHECK: pool vault and lp mint authority
+    #[account(
+        seeds = [
+            crate::AUTH_SEED.as_bytes(),
+        ],
+        bump,
+    )]
+    pub authority: UncheckedAccount<'info>,
+
+    /// Pool state account
+    #[account(mut)]
+    pub pool_state: AccountLoader<'info, PoolState>,
+
+    /// Creator lp token account, all of its lp tokens are burnt
+    #[account(
+        mut,
+        token::mint = lp_mint,
+        token::authority = creator
+    )]
+    pub creator_lp_token: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    /// The creator's token account to receive token_0
+    #[account(
+        mut,
+        token::mint = token_0_vault.mint,
+        token::authority = creator
+    )]
+    pub token_0_account: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    /// The creator's token account to receive token_1
+    #[account(
+        mut,
+        token::mint = token_1_vault.mint,
+        token::authority = creator
+    )]
+    pub token_1_account: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    /// The address that holds pool tokens for token_0
+    #[account(
+        mut,
+        constraint = token_0_vault.key() == pool_state.load()?.token_0_vault
+    )]
+    pub token_0_vault: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    /// The address that holds pool tokens for token_1
+    #[account(
+        mut,
+        constraint = token_1_vault.key() == pool_state.load()?.token_1_vault
+    )]
+    pub token_1_vault: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    /// token Program
+    pub token_program: Program<'info, Token>,
+
+    /// Token program 2022
+    pub token_program_2022: Program<'info, Token2022>,
+
+    /// The mint of token_0 vault
+    #[account(
+        address = token_0_vault.mint
+    )]
+    pub vault_0_mint: Box<InterfaceAccount<'info, Mint>>,
+
+    /// The mint of token_1 vault
+    #[account(
+        address = token_1_vault.mint
+    )]
+    pub vault_1_mint: Box<InterfaceAccount<'info, Mint>>,
+
+    /// Pool lp token mint
+    #[account(
+        mut,
+        address = pool_state.load()?.lp_mint @ ErrorCode::IncorrectLpMint)
+    ]
+    pub lp_mint: Box<InterfaceAccount<'info, Mint>>,
+}
+
+pub fn end_lbp(ctx: Context<EndLbp>) -> Result<()> {
+    let pool_id = ctx.accounts.pool_state.key();
+    let pool_state = &mut ctx.accounts.pool_state.load_mut()?;
+    require!(
+        pool_state.is_liquidity_bootstrapping(),
+        ErrorCode::InvalidPoolType
+    );
+    if !pool_state.get_status_by_bit(PoolStatusBitIndex::Withdraw) {
+        return err!(ErrorCode::NotApproved);
+    }
+
+    // only the creator can deposit into the pool, the reserves are only pulled
+    // while it still holds every minted lp token
+    let lp_token_amount = ctx.accounts.creator_lp_token.amount;
+    require_eq!(
+        lp_token_amount,
+        ctx.accounts.lp_mint.supply,
+        ErrorCode::NotApproved
+    );
+    let (token_0_amount, token_1_amount) = pool_state.vault_amount_without_fee(
+        ctx.accounts.token_0_vault.amount,
+        ctx.accounts.token_1_vault.amount,
+    );
+
+    #[cfg(feature = "enable-log")]
+    msg!(
+        "lp_token_amount:{}, token_0_amount:{}, token_1_amount:{}",
+        lp_token_amount,
+        token_0_amount,
+        token_1_amount
+    );
+
+    pool_state.lp_supply = pool_state.lp_supply.checked_sub(lp_token_amount).unwrap();
+    pool_state.set_status_by_bit(PoolStatusBitIndex::Deposit, PoolStatusBitFlag::Disable);
+    pool_state.set_status_by_bit(PoolStatusBitIndex::Withdraw, PoolStatusBitFlag::Disable);
+    pool_state.set_status_by_bit(PoolStatusBitIndex::Swap, PoolStatusBitFlag::Disable);
+
+    token_burn(
+        ctx.accounts.creator.to_account_info(),
+        ctx.accounts.token_program.to_account_info(),
+        ctx.accounts.lp_mint.to_account_info(),
+        ctx.accounts.creator_lp_token.to_account_info(),
+        lp_token_amount,
+        &[&[crate::AUTH_SEED.as_bytes(), &[pool_state.auth_bump]]],
+    )?;
+
+    transfer_from_pool_vault_to_user(
+        ctx.accounts.authority.to_account_info(),
+        ctx.accounts.token_0_vault.to_account_info(),
+        ctx.accounts.token_0_account.to_account_info(),
+        ctx.accounts.vault_0_mint.to_account_info(),
+        if ctx.accounts.vault_0_mint.to_account_info().owner == ctx.accounts.token_program.key {
+            ctx.accounts.token_program.to_account_info()
+        } else {
+            ctx.accounts.token_program_2022.to_account_info()
+        },
+        token_0_amount,
+        ctx.accounts.vault_0_mint.decimals,
+        &[&[crate::AUTH_SEED.as_bytes(), &[pool_state.auth_bump]]],
+    )?;
+
+    transfer_from_pool_vault_to_user(
+        ctx.accounts.authority.to_account_info(),
+        ctx.accounts.token_1_vault.to_account_info(),
+        ctx.accounts.token_1_account.to_account_info(),
+        ctx.accounts.vault_1_mint.to_account_info(),
+        if ctx.accounts.vault_1_mint.to_account_info().owner == ctx.accounts.token_program.key {
+            ctx.accounts.token_program.to_account_info()
+        } else {
+            ctx.accounts.token_program_2022.to_account_info()
+        },
+        token_1_amount,
+        ctx.accounts.vault_1_mint.decimals,
+        &[&[crate::AUTH_SEED.as_bytes(), &[pool_state.auth_bump]]],
+    )?;
+
+    ctx.accounts.token_0_vault.reload()?;
+    ctx.accounts.token_1_vault.reload()?;
+
+    emit_cpi!(LpChangeEvent {
+        pool_id,
+        amount_lp: lp_token_amount,
+        amount_0: token_0_amount,
+        amount_1: token_1_amount,
+        reserve_0: ctx.accounts.token_0_vault.amount,
+        reserve_1: ctx.accounts.token_1_vault.amount,
+        change_type: 2,
+        tax_amount: 0,
+    });
+
+    Ok(())
+}
@@ -64,6 +64,14 @@ pub struct Initialize<'info> {
     )]
     pub token_1_mint: Box<InterfaceAccount<'info, Mint>>,
 
+    /// CHECK: the bonding curve selling token_0 for token_1 under the config,
+    /// which must not exist
+    pub token_0_bonding_curve: UncheckedAccount<'info>,
+
+    /// CHECK: the bonding curve selling token_1 for token_0 under the config,
+    /// which must not exist
+    pub token_1_bonding_curve: UncheckedAccount<'info>,
+
     /// pool lp mint
     #[account(
         init,
@@ -140,6 +148,19 @@ pub struct Initialize<'info> {
     )]
     pub create_pool_fee: UncheckedAccount<'info>,
 
+    /// an account to store oracle observations
+    #[account(
+        init,
+        seeds = [
+            OBSERVATION_SEED.as_bytes(),
+            pool_state.key().as_ref(),
+        ],
+        bump,
+        payer = creator,
+        space = ObservationState::LEN
+    )]
+    pub observation_state: AccountLoader<'info, ObservationState>,
+
     /// Program to create mint account and mint tokens
     pub token_program: Program<'info, Token>,
     /// Spl token program or token program 2022
@@ -163,6 +184,9 @@ pub fn initialize(
     in_tax_rate: u64,
     out_tax_rate: u64,
     lp_fee_rate: Option<u64>,
+    curve_params: Option<CurveParams>,
+    tax_schedule: Option<TaxSchedule>,
+    trade_limits: Option<TradeLimits>,
 ) -> Result<()> {
     if !(is_supported_mint(&ctx.accounts.token_0_mint).unwrap()
         && is_supported_mint(&ctx.accounts.token_1_mint).unwrap())
@@ -174,6 +198,17 @@ pub fn initialize(
         return err!(ErrorCode::NotApproved);
     }
 
+    // a pair with a bonding curve gets its pool at graduation
+    BondingCurveState::check_pair_has_no_curve(
+        &ctx.accounts.amm_config.key(),
+        &ctx.accounts.token_0_mint.key(),
+        &ctx.accounts.token_1_mint.key(),
+        [
+            &ctx.accounts.token_0_bonding_curve.to_account_info(),
+            &ctx.accounts.token_1_bonding_curve.to_account_info(),
+        ],
+    )?;
+
     let block_timestamp = clock::Clock::get()?.unix_timestamp as u64;
     if open_time <= block_timestamp {
         open_time = block_timestamp + 1;
@@ -182,6 +217,8 @@ pub fn initialize(
     // check tax config
     assert!(in_tax_rate <= FEE_RATE_DENOMINATOR_VALUE);
     assert!(out_tax_rate <= FEE_RATE_DENOMINATOR_VALUE);
+    ctx.accounts.amm_config.check_tax_rate(in_tax_rate)?;
+    ctx.accounts.amm_config.check_tax_rate(out_tax_rate)?;
 
     let lp_fee_rate = if let Some(lp_fee_rate) = lp_fee_rate {
         assert!(lp_fee_rate + ctx.accounts.amm_config.trade_fee_rate <= FEE_RATE_DENOMINATOR_VALUE);
@@ -190,6 +227,26 @@ pub fn initialize(
         0
     };
 
+    let curve_params = curve_params.unwrap_or(CurveParams::ConstantProduct);
+    curve_params.validate(
+        ctx.accounts.token_0_mint.decimals,
+        ctx.accounts.token_1_mint.decimals,
+        open_time,
+    )?;
+
+    if let Some(tax_schedule) = tax_schedule {
+        tax_schedule.validate()?;
+        ctx.accounts
+            .amm_config
+            .check_tax_rate(tax_schedule.start_in_tax_rate)?;
+        ctx.accounts
+            .amm_config
+            .check_tax_rate(tax_schedule.start_out_tax_rate)?;
+    }
+    if let Some(trade_limits) = trade_limits {
+        trade_limits.validate()?;
+    }
+
     // get tax mint
     let tax_mint = if tax_use_token_0 {
         ctx.accounts.token_0_mint.key()
@@ -198,34 +255,25 @@ pub fn initialize(
     };
 
     // due to stack/heap limitations, we have to create redundant new accounts ourselves.
-    create_token_account(
+    create_pool_vault(
         &ctx.accounts.authority.to_account_info(),
         &ctx.accounts.creator.to_account_info(),
+        &ctx.accounts.pool_state.key(),
         &ctx.accounts.token_0_vault.to_account_info(),
         &ctx.accounts.token_0_mint.to_account_info(),
-        &ctx.accounts.system_program.to_account_info(),
         &ctx.accounts.token_0_program.to_account_info(),
-        &[&[
-            POOL_VAULT_SEED.as_bytes(),
-            ctx.accounts.pool_state.key().as_ref(),
-            ctx.accounts.token_0_mint.key().as_ref(),
-            &[ctx.bumps.token_0_vault][..],
-        ][..]],
+        &ctx.accounts.system_program.to_account_info(),
+        ctx.bumps.token_0_vault,
     )?;
-
-    create_token_account(
+    create_pool_vault(
         &ctx.accounts.authority.to_account_info(),
         &ctx.accounts.creator.to_account_info(),
+        &ctx.accounts.pool_state.key(),
         &ctx.accounts.token_1_vault.to_account_info(),
         &ctx.accounts.token_1_mint.to_account_info(),
-        &ctx.accounts.system_program.to_account_info(),
         &ctx.accounts.token_1_program.to_account_info(),
-        &[&[
-            POOL_VAULT_SEED.as_bytes(),
-            ctx.accounts.pool_state.key().as_ref(),
-            ctx.accounts.token_1_mint.key().as_ref(),
-            &[ctx.bumps.token_1_vault][..],
-        ][..]],
+        &ctx.accounts.system_program.to_account_info(),
+        ctx.bumps.token_1_vault,
     )?;
 
     let pool_state = &mut ctx.accounts.pool_state.load_init()?;
@@ -264,45 +312,23 @@ pub fn initialize(
         ctx.accounts.token_1_mint.decimals,
     )?;
 
-    let token_0_vault =
-        spl_token_2022::extension::StateWithExtensions::<spl_token_2022::state::Account>::unpack(
-            ctx.accounts
-                .token_0_vault
-                .to_account_info()
-                .try_borrow_data()?
-                .deref(),
-        )?
-        .base;
-    let token_1_vault =
-        spl_token_2022::extension::StateWithExtensions::<spl_token_2022::state::Account>::unpack(
-            ctx.accounts
-                .token_1_vault
-                .to_account_info()
-                .try_borrow_data()?
-                .deref(),
-        )?
-        .base;
-
-    CurveCalculator::validate_supply(token_0_vault.amount, token_1_vault.amount)?;
-
-    let liquidity = U128::from(token_0_vault.amount)
-        .checked_mul(token_1_vault.amount.into())
-        .unwrap()
-        .integer_sqrt()
-        .as_u64();
-    let lock_lp_amount = (10u64)
-        .checked_pow(u32::from(ctx.accounts.lp_mint.decimals))
-        .unwrap();
-
-    // Guard against case when minted liquidity equals to lock amount
-    require_gt!(liquidity, lock_lp_amount);
+    let (amount_0, amount_1) = pool_vault_amounts(
+        &ctx.accounts.token_0_vault.to_account_info(),
+        &ctx.accounts.token_1_vault.to_account_info(),
+    )?;
+    let (liquidity, lp_amount) = initial_liquidity(
+        &curve_params,
+        amount_0,
+        amount_1,
+        ctx.accounts.lp_mint.decimals,
+    )?;
 
     token::token_mint_to(
         ctx.accounts.authority.to_account_info(),
         ctx.accounts.token_program.to_account_info(),
         ctx.accounts.lp_mint.to_account_info(),
         ctx.accounts.creator_lp_token.to_account_info(),
-        liquidity.checked_sub(lock_lp_amount).unwrap(),
+        lp_amount,
         &[&[crate::AUTH_SEED.as_bytes(), &[ctx.bumps.authority]]],
     )?;
 
@@ -312,7 +338,7 @@ pub fn initialize(
             &system_instruction::transfer(
                 ctx.accounts.creator.key,
                 &ctx.accounts.create_pool_fee.key(),
-                u64::from(ctx.accounts.amm_config.create_pool_fee),
+                ctx.accounts.amm_config.create_pool_fee,
             ),
             &[
                 ctx.accounts.creator.to_account_info(),
@@ -333,30 +359,147 @@ pub fn initialize(
         &ctx.accounts.token_0_mint,
         &ctx.accounts.token_1_mint,
         &ctx.accounts.lp_mint,
+        ctx.accounts.observation_state.key(),
         tax_mint,
         in_tax_rate,
         out_tax_rate,
         lp_fee_rate,
+        curve_params,
     );
+    if let Some(tax_schedule) = tax_schedule {
+        pool_state.set_tax_schedule(tax_schedule);
+    }
+    if let Some(trade_limits) = trade_limits {
+        pool_state.set_trade_limits(trade_limits);
+    }
+
+    initialize_pool_observation(
+        &ctx.accounts.observation_state,
+        ctx.accounts.pool_state.key(),
+        pool_state,
+        amount_0,
+        amount_1,
+        block_timestamp,
+    )?;
 
     emit_cpi!(LpChangeEvent {
         pool_id: ctx.accounts.pool_state.key(),
         amount_lp: liquidity,
-        amount_0: token_0_vault.amount,
-        amount_1: token_1_vault.amount,
-        reserve_0: token_0_vault.amount,
-        reserve_1: token_1_vault.amount,
+        amount_0,
+        amount_1,
+        reserve_0: amount_0,
+        reserve_1: amount_1,
         change_type: 0,
+        tax_amount: 0,
     });
 
     emit_cpi!(TaxConfigUpdatedEvent {
         pool_id: ctx.accounts.pool_state.key(),
-        tax_mint: tax_mint,
+        tax_mint,
         tax_authority: ctx.accounts.creator.key(),
-        in_tax_rate: in_tax_rate,
-        out_tax_rate: out_tax_rate,
+        in_tax_rate,
+        out_tax_rate,
+        quote_in_tax_rate: 0,
+        quote_out_tax_rate: 0,
         tax_disabled: false,
     });
 
+    if let Some(trade_limits) = trade_limits {
+        emit_cpi!(TradeLimitsUpdatedEvent {
+            pool_id: ctx.accounts.pool_state.key(),
+            max_tx_amount: trade_limits.max_tx_amount,
+            max_tx_rate: trade_limits.max_tx_rate,
+            max_wallet_amount: trade_limits.max_wallet_amount,
+            end_time: trade_limits.end_time,
+        });
+    }
+
+    Ok(())
+}
+
+/// Create a token vault of a new pool at its PDA
+pub fn create_pool_vault<'info>(
+    authority: &AccountInfo<'info>,
+    payer: &AccountInfo<'info>,
+    pool_id: &Pubkey,
+    vault: &AccountInfo<'info>,
+    mint: &AccountInfo<'info>,
+    token_program: &AccountInfo<'info>,
+    system_program: &AccountInfo<'info>,
+    vault_bump: u8,
+) -> Result<()> {
+    create_token_account(
+        authority,
+        payer,
+        vault,
+        mint,
+        system_program,
+        token_program,
+        &[&[
+            POOL_VAULT_SEED.as_bytes(),
+            pool_id.as_ref(),
+            mint.key().as_ref(),
+            &[vault_bump][..],
+        ][..]],
+    )
+}
+
+/// Balances of the token vaults of a new pool, created in the same instruction
+pub fn pool_vault_amounts(
+    token_0_vault: &AccountInfo,
+    token_1_vault: &AccountInfo,
+) -> Result<(u64, u64)> {
+    let amount = |vault: &AccountInfo| -> Result<u64> {
+        Ok(
+            spl_token_2022::extension::StateWithExtensions::<spl_token_2022::state::Account>::unpack(
+                vault.try_borrow_data()?.deref(),
+            )?
+            .base
+            .amount,
+        )
+    };
+    Ok((amount(token_0_vault)?, amount(token_1_vault)?))
+}
+
+/// Liquidity of a new pool seeded with `amount_0` and `amount_1`, and the
+/// lp amount minted for it, short of the locked amount
+pub fn initial_liquidity(
+    curve_params: &CurveParams,
+    amount_0: u64,
+    amount_1: u64,
+    lp_mint_decimals: u8,
+) -> Result<(u64, u64)> {
+    CurveCalculator::validate_supply(amount_0, amount_1)?;
+
+    let liquidity = curve_params
+        .curve()
+        .initial_lp_tokens(u128::from(amount_0), u128::from(amount_1))
+        .ok_or(ErrorCode::ZeroTradingTokens)?;
+    let liquidity = u64::try_from(liquidity).unwrap();
+    let lock_lp_amount = (10u64).checked_pow(u32::from(lp_mint_decimals)).unwrap();
+
+    // Guard against case when minted liquidity equals to lock amount
+    require_gt!(liquidity, lock_lp_amount);
+
+    Ok((liquidity, liquidity.checked_sub(lock_lp_amount).unwrap()))
+}
+
+/// Start the observations of a pool at the price of the given reserves
+pub fn initialize_pool_observation(
+    observation_state: &AccountLoader<ObservationState>,
+    pool_id: Pubkey,
+    pool_state: &PoolState,
+    amount_0: u64,
+    amount_1: u64,
+    block_timestamp: u64,
+) -> Result<()> {
+    let (token_0_price_x32, token_1_price_x32) =
+        pool_state.token_price_x32(amount_0, amount_1, block_timestamp);
+    observation_state.load_init()?.initialize(
+        pool_id,
+        block_timestamp,
+        token_0_price_x32,
+        token_1_price_x32,
+    );
     Ok(())
 }
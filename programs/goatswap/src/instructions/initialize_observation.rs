@@ -0,0 +1,69 @@
+use crate::error::ErrorCode;
+use crate::instructions::initialize_pool_observation;
+use crate::states::*;
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program;
+use anchor_spl::token_interface::TokenAccount;
+
+#[derive(Accounts)]
+pub struct InitializeObservation<'info> {
+    /// Pays for the observation account
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    /// Pool state of a standard pool created before observations
+    #[account(
+        mut,
+        constraint = !pool_state.load()?.is_concentrated() @ ErrorCode::InvalidPoolType
+    )]
+    pub pool_state: AccountLoader<'info, PoolState>,
+
+    /// The address that holds pool tokens for token_0
+    #[account(
+        constraint = token_0_vault.key() == pool_state.load()?.token_0_vault
+    )]
+    pub token_0_vault: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    /// The address that holds pool tokens for token_1
+    #[account(
+        constraint = token_1_vault.key() == pool_state.load()?.token_1_vault
+    )]
+    pub token_1_vault: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    /// an account to store oracle observations, at the address pools created
+    /// since observations get it
+    #[account(
+        init,
+        seeds = [
+            OBSERVATION_SEED.as_bytes(),
+            pool_state.key().as_ref(),
+        ],
+        bump,
+        payer = payer,
+        space = ObservationState::LEN
+    )]
+    pub observation_state: AccountLoader<'info, ObservationState>,
+
+    pub system_program: Program<'info, System>,
+}
+
+/// Create the observation account of a pool created before observations,
+/// anyone can call it. Swaps, deposits and withdrawals of the pool need it
+pub fn initialize_observation(ctx: Context<InitializeObservation>) -> Result<()> {
+    let block_timestamp = solana_program::clock::Clock::get()?.unix_timestamp as u64;
+    let pool_state = &mut ctx.accounts.pool_state.load_mut()?;
+    pool_state.observation_key = ctx.accounts.observation_state.key();
+
+    let (reserve_0, reserve_1) = pool_state.vault_amount_without_fee(
+        ctx.accounts.token_0_vault.amount,
+        ctx.accounts.token_1_vault.amount,
+    );
+    initialize_pool_observation(
+        &ctx.accounts.observation_state,
+        ctx.accounts.pool_state.key(),
+        pool_state,
+        reserve_0,
+        reserve_1,
+        block_timestamp,
+    )
+}
@@ -65,6 +65,14 @@ pub struct InitializeWhitelisted<'info> {
     )]
     pub token_1_mint: Box<InterfaceAccount<'info, Mint>>,
 
+    /// CHECK: the bonding curve selling token_0 for token_1 under the config,
+    /// which must not exist
+    pub token_0_bonding_curve: UncheckedAccount<'info>,
+
+    /// CHECK: the bonding curve selling token_1 for token_0 under the config,
+    /// which must not exist
+    pub token_1_bonding_curve: UncheckedAccount<'info>,
+
     /// pool lp mint
     #[account(
         init,
@@ -134,6 +142,19 @@ pub struct InitializeWhitelisted<'info> {
     )]
     pub token_1_vault: UncheckedAccount<'info>,
 
+    /// an account to store oracle observations
+    #[account(
+        init,
+        seeds = [
+            OBSERVATION_SEED.as_bytes(),
+            pool_state.key().as_ref(),
+        ],
+        bump,
+        payer = creator,
+        space = ObservationState::LEN
+    )]
+    pub observation_state: AccountLoader<'info, ObservationState>,
+
     /// Program to create mint account and mint tokens
     pub token_program: Program<'info, Token>,
     /// Spl token program or token program 2022
@@ -160,6 +181,9 @@ pub fn initialize_whitelisted(
     in_tax_rate: u64,
     out_tax_rate: u64,
     lp_fee_rate: Option<u64>,
+    curve_params: Option<CurveParams>,
+    tax_schedule: Option<TaxSchedule>,
+    trade_limits: Option<TradeLimits>,
 ) -> Result<()> {
     let current_ix = sysvar::instructions::get_instruction_relative(
         0,
@@ -178,6 +202,17 @@ pub fn initialize_whitelisted(
         return err!(ErrorCode::NotApproved);
     }
 
+    // a pair with a bonding curve gets its pool at graduation
+    BondingCurveState::check_pair_has_no_curve(
+        &ctx.accounts.amm_config.key(),
+        &ctx.accounts.token_0_mint.key(),
+        &ctx.accounts.token_1_mint.key(),
+        [
+            &ctx.accounts.token_0_bonding_curve.to_account_info(),
+            &ctx.accounts.token_1_bonding_curve.to_account_info(),
+        ],
+    )?;
+
     let block_timestamp = clock::Clock::get()?.unix_timestamp as u64;
     if open_time <= block_timestamp {
         open_time = block_timestamp + 1;
@@ -186,6 +221,8 @@ pub fn initialize_whitelisted(
     // check tax config
     assert!(in_tax_rate <= FEE_RATE_DENOMINATOR_VALUE);
     assert!(out_tax_rate <= FEE_RATE_DENOMINATOR_VALUE);
+    ctx.accounts.amm_config.check_tax_rate(in_tax_rate)?;
+    ctx.accounts.amm_config.check_tax_rate(out_tax_rate)?;
 
     let lp_fee_rate = if let Some(lp_fee_rate) = lp_fee_rate {
         assert!(lp_fee_rate + ctx.accounts.amm_config.trade_fee_rate <= FEE_RATE_DENOMINATOR_VALUE);
@@ -194,6 +231,26 @@ pub fn initialize_whitelisted(
         0
     };
 
+    let curve_params = curve_params.unwrap_or(CurveParams::ConstantProduct);
+    curve_params.validate(
+        ctx.accounts.token_0_mint.decimals,
+        ctx.accounts.token_1_mint.decimals,
+        open_time,
+    )?;
+
+    if let Some(tax_schedule) = tax_schedule {
+        tax_schedule.validate()?;
+        ctx.accounts
+            .amm_config
+            .check_tax_rate(tax_schedule.start_in_tax_rate)?;
+        ctx.accounts
+            .amm_config
+            .check_tax_rate(tax_schedule.start_out_tax_rate)?;
+    }
+    if let Some(trade_limits) = trade_limits {
+        trade_limits.validate()?;
+    }
+
     // get tax mint
     let tax_mint = if tax_use_token_0 {
         ctx.accounts.token_0_mint.key()
@@ -289,11 +346,14 @@ pub fn initialize_whitelisted(
 
     CurveCalculator::validate_supply(token_0_vault.amount, token_1_vault.amount)?;
 
-    let liquidity = U128::from(token_0_vault.amount)
-        .checked_mul(token_1_vault.amount.into())
-        .unwrap()
-        .integer_sqrt()
-        .as_u64();
+    let liquidity = curve_params
+        .curve()
+        .initial_lp_tokens(
+            u128::from(token_0_vault.amount),
+            u128::from(token_1_vault.amount),
+        )
+        .ok_or(ErrorCode::ZeroTradingTokens)?;
+    let liquidity = u64::try_from(liquidity).unwrap();
     let lock_lp_amount = (10u64)
         .checked_pow(u32::from(ctx.accounts.lp_mint.decimals))
         .unwrap();
@@ -321,10 +381,28 @@ pub fn initialize_whitelisted(
         &ctx.accounts.token_0_mint,
         &ctx.accounts.token_1_mint,
         &ctx.accounts.lp_mint,
+        ctx.accounts.observation_state.key(),
         tax_mint,
         in_tax_rate,
         out_tax_rate,
         lp_fee_rate,
+        curve_params,
+    );
+    if let Some(tax_schedule) = tax_schedule {
+        pool_state.set_tax_schedule(tax_schedule);
+    }
+    if let Some(trade_limits) = trade_limits {
+        pool_state.set_trade_limits(trade_limits);
+    }
+
+    let observation_state = &mut ctx.accounts.observation_state.load_init()?;
+    let (token_0_price_x32, token_1_price_x32) =
+        pool_state.token_price_x32(token_0_vault.amount, token_1_vault.amount, block_timestamp);
+    observation_state.initialize(
+        ctx.accounts.pool_state.key(),
+        block_timestamp,
+        token_0_price_x32,
+        token_1_price_x32,
     );
 
     emit_cpi!(LpChangeEvent {
@@ -335,16 +413,29 @@ pub fn initialize_whitelisted(
         reserve_0: token_0_vault.amount,
         reserve_1: token_1_vault.amount,
         change_type: 0,
+        tax_amount: 0,
     });
 
     emit_cpi!(TaxConfigUpdatedEvent {
         pool_id: ctx.accounts.pool_state.key(),
-        tax_mint: tax_mint,
+        tax_mint,
         tax_authority: ctx.accounts.creator.key(),
-        in_tax_rate: in_tax_rate,
-        out_tax_rate: out_tax_rate,
+        in_tax_rate,
+        out_tax_rate,
+        quote_in_tax_rate: 0,
+        quote_out_tax_rate: 0,
         tax_disabled: false,
     });
 
+    if let Some(trade_limits) = trade_limits {
+        emit_cpi!(TradeLimitsUpdatedEvent {
+            pool_id: ctx.accounts.pool_state.key(),
+            max_tx_amount: trade_limits.max_tx_amount,
+            max_tx_rate: trade_limits.max_tx_rate,
+            max_wallet_amount: trade_limits.max_wallet_amount,
+            end_time: trade_limits.end_time,
+        });
+    }
+
     Ok(())
 }
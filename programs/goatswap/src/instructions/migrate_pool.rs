@@ -0,0 +1,29 @@
+use crate::states::*;
+use anchor_lang::prelude::*;
+
+#[derive(Accounts)]
+pub struct MigratePool<'info> {
+    /// Pays the rent of the bytes added to the pool state
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    /// Pool state account, grown to the current layout. The added fields
+    /// start zeroed, like the padding they extend
+    #[account(
+        mut,
+        realloc = PoolState::LEN,
+        realloc::payer = payer,
+        realloc::zero = true,
+    )]
+    pub pool_state: AccountLoader<'info, PoolState>,
+
+    pub system_program: Program<'info, System>,
+}
+
+/// Grow a pool created with an older, smaller layout of the pool state,
+/// anyone can call it and it does nothing on pools already at the current size
+pub fn migrate_pool(ctx: Context<MigratePool>) -> Result<()> {
+    // the pool loads again once it has its full size
+    ctx.accounts.pool_state.load()?;
+    Ok(())
+}
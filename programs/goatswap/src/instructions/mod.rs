@@ -20,8 +20,44 @@ pub use update_tax::*;
 pub mod collect_tax;
 pub use collect_tax::*;
 
+pub mod collect_tax_as_quote;
+pub use collect_tax_as_quote::*;
+
+pub mod tax_exemption;
+pub use tax_exemption::*;
+
+pub mod stake;
+pub use stake::*;
+
+pub mod update_tax_recipients;
+pub use update_tax_recipients::*;
+
 pub mod initialize_whitelisted;
 pub use initialize_whitelisted::*;
 
 pub mod update_lp_fee;
 pub use update_lp_fee::*;
+
+pub mod collect_creator_fee;
+pub use collect_creator_fee::*;
+
+pub mod update_dynamic_fee;
+pub use update_dynamic_fee::*;
+
+pub mod clmm;
+pub use clmm::*;
+
+pub mod end_lbp;
+pub use end_lbp::*;
+
+pub mod bonding_curve;
+pub use bonding_curve::*;
+
+pub mod observe_twap;
+pub use observe_twap::*;
+
+pub mod migrate_pool;
+pub use migrate_pool::*;
+
+pub mod initialize_observation;
+pub use initialize_observation::*;
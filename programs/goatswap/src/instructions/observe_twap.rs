@@ -0,0 +1,40 @@
+use crate::error::ErrorCode;
+use crate::states::*;
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program;
+
+#[derive(Accounts)]
+pub struct ObserveTwap<'info> {
+    /// The pool to read the price of, concentrated pools keep no observation
+    #[account(
+        constraint = !pool_state.load()?.is_concentrated() @ ErrorCode::InvalidPoolType
+    )]
+    pub pool_state: AccountLoader<'info, PoolState>,
+
+    /// The program account for the most recent oracle observation
+    #[account(address = pool_state.load()?.observation_key)]
+    pub observation_state: AccountLoader<'info, ObservationState>,
+}
+
+pub fn observe_twap(ctx: Context<ObserveTwap>, window: u64) -> Result<TwapPrice> {
+    let block_timestamp = solana_program::clock::Clock::get()?.unix_timestamp as u64;
+    let (token_0_price_x32, token_1_price_x32) = ctx
+        .accounts
+        .observation_state
+        .load()?
+        .twap(block_timestamp, window)
+        .ok_or(ErrorCode::ObservationNotFound)?;
+
+    #[cfg(feature = "enable-log")]
+    msg!(
+        "window:{}, token_0_price_x32:{}, token_1_price_x32:{}",
+        window,
+        token_0_price_x32,
+        token_1_price_x32
+    );
+
+    Ok(TwapPrice {
+        token_0_price_x32,
+        token_1_price_x32,
+    })
+}
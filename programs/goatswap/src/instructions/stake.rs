@@ -0,0 +1,192 @@
+use crate::error::ErrorCode;
+use crate::states::*;
+use crate::utils::token::*;
+use anchor_lang::prelude::*;
+use anchor_spl::token_interface::{Mint, TokenAccount, TokenInterface};
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct Stake<'info> {
+    /// The staking wallet, pays for its stake record and the stake vault
+    #[account(mut)]
+    pub owner: Signer<'info>,
+
+    /// CHECK: stake vault authority
+    #[account(
+        seeds = [
+            crate::AUTH_SEED.as_bytes(),
+        ],
+        bump,
+    )]
+    pub authority: UncheckedAccount<'info>,
+
+    /// The mint to stake
+    #[account(token::token_program = token_program)]
+    pub mint: Box<InterfaceAccount<'info, Mint>>,
+
+    /// The owner's account the stake is taken from
+    #[account(
+        mut,
+        token::mint = mint,
+        token::authority = owner,
+    )]
+    pub owner_token_account: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    /// CHECK: Stake vault of the mint, created by the first stake
+    #[account(
+        mut,
+        seeds = [
+            STAKE_VAULT_SEED.as_bytes(),
+            mint.key().as_ref(),
+        ],
+        bump,
+    )]
+    pub stake_vault: UncheckedAccount<'info>,
+
+    /// The stake record of the owner
+    #[account(
+        init_if_needed,
+        seeds = [
+            STAKE_RECORD_SEED.as_bytes(),
+            mint.key().as_ref(),
+            owner.key().as_ref(),
+        ],
+        bump,
+        payer = owner,
+        space = StakeRecord::LEN
+    )]
+    pub stake_record: Box<Account<'info, StakeRecord>>,
+
+    /// SPL program for the mint
+    pub token_program: Interface<'info, TokenInterface>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct Unstake<'info> {
+    /// The staking wallet
+    pub owner: Signer<'info>,
+
+    /// CHECK: stake vault authority
+    #[account(
+        seeds = [
+            crate::AUTH_SEED.as_bytes(),
+        ],
+        bump,
+    )]
+    pub authority: UncheckedAccount<'info>,
+
+    /// The staked mint
+    #[account(token::token_program = token_program)]
+    pub mint: Box<InterfaceAccount<'info, Mint>>,
+
+    /// The account receiving the unstaked tokens
+    #[account(mut, token::mint = mint)]
+    pub recipient_token_account: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    /// Stake vault of the mint
+    #[account(
+        mut,
+        seeds = [
+            STAKE_VAULT_SEED.as_bytes(),
+            mint.key().as_ref(),
+        ],
+        bump,
+    )]
+    pub stake_vault: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    /// The stake record of the owner
+    #[account(
+        mut,
+        seeds = [
+            STAKE_RECORD_SEED.as_bytes(),
+            mint.key().as_ref(),
+            owner.key().as_ref(),
+        ],
+        bump = stake_record.bump,
+    )]
+    pub stake_record: Box<Account<'info, StakeRecord>>,
+
+    /// SPL program for the mint
+    pub token_program: Interface<'info, TokenInterface>,
+}
+
+pub fn stake(ctx: Context<Stake>, amount: u64) -> Result<()> {
+    require_gt!(amount, 0, ErrorCode::InvalidInput);
+
+    if ctx.accounts.stake_vault.data_is_empty() {
+        create_token_account(
+            &ctx.accounts.authority.to_account_info(),
+            &ctx.accounts.owner.to_account_info(),
+            &ctx.accounts.stake_vault.to_account_info(),
+            &ctx.accounts.mint.to_account_info(),
+            &ctx.accounts.system_program.to_account_info(),
+            &ctx.accounts.token_program.to_account_info(),
+            &[&[
+                STAKE_VAULT_SEED.as_bytes(),
+                ctx.accounts.mint.key().as_ref(),
+                &[ctx.bumps.stake_vault][..],
+            ][..]],
+        )?;
+    }
+
+    transfer_from_user_to_pool_vault(
+        ctx.accounts.owner.to_account_info(),
+        ctx.accounts.owner_token_account.to_account_info(),
+        ctx.accounts.stake_vault.to_account_info(),
+        ctx.accounts.mint.to_account_info(),
+        ctx.accounts.token_program.to_account_info(),
+        amount,
+        ctx.accounts.mint.decimals,
+    )?;
+
+    // only what reaches the vault is staked
+    let transfer_fee = get_transfer_fee(&ctx.accounts.mint.to_account_info(), amount)?;
+    let staked = amount.checked_sub(transfer_fee).unwrap();
+
+    let stake_record = &mut ctx.accounts.stake_record;
+    stake_record.bump = ctx.bumps.stake_record;
+    stake_record.owner = ctx.accounts.owner.key();
+    stake_record.mint = ctx.accounts.mint.key();
+    stake_record.amount = stake_record.amount.checked_add(staked).unwrap();
+
+    emit_cpi!(StakeChangeEvent {
+        owner: stake_record.owner,
+        mint: stake_record.mint,
+        amount: staked,
+        staked_amount: stake_record.amount,
+        change_type: 0,
+    });
+
+    Ok(())
+}
+
+pub fn unstake(ctx: Context<Unstake>, amount: u64) -> Result<()> {
+    require_gt!(amount, 0, ErrorCode::InvalidInput);
+    let stake_record = &mut ctx.accounts.stake_record;
+    require_gte!(stake_record.amount, amount, ErrorCode::ExceededStakedAmount);
+    stake_record.amount -= amount;
+
+    transfer_from_pool_vault_to_user(
+        ctx.accounts.authority.to_account_info(),
+        ctx.accounts.stake_vault.to_account_info(),
+        ctx.accounts.recipient_token_account.to_account_info(),
+        ctx.accounts.mint.to_account_info(),
+        ctx.accounts.token_program.to_account_info(),
+        amount,
+        ctx.accounts.mint.decimals,
+        &[&[crate::AUTH_SEED.as_bytes(), &[ctx.bumps.authority]]],
+    )?;
+
+    emit_cpi!(StakeChangeEvent {
+        owner: stake_record.owner,
+        mint: stake_record.mint,
+        amount,
+        staked_amount: stake_record.amount,
+        change_type: 1,
+    });
+
+    Ok(())
+}
@@ -1,4 +1,5 @@
 use crate::curve::calculator::CurveCalculator;
+use crate::curve::fees::Fees;
 use crate::curve::TradeDirection;
 use crate::error::ErrorCode;
 use crate::states::*;
@@ -72,12 +73,118 @@ pub struct Swap<'info> {
         token::token_program = output_token_program
     )]
     pub output_token_mint: Box<InterfaceAccount<'info, Mint>>,
+
+    /// The program account for the most recent oracle observation
+    #[account(mut, address = pool_state.load()?.observation_key)]
+    pub observation_state: AccountLoader<'info, ObservationState>,
+
+    /// The tax exemption of the payer, the swap is not taxed when set
+    #[account(
+        seeds = [
+            TAX_EXEMPTION_SEED.as_bytes(),
+            pool_state.key().as_ref(),
+            payer.key().as_ref(),
+        ],
+        bump = tax_exemption.bump,
+    )]
+    pub tax_exemption: Option<Box<Account<'info, TaxExemption>>>,
+
+    /// The referrer's token account for the input token, receives the
+    /// referral share of the trade fee when set. Its owner can not be the payer
+    #[account(mut, token::mint = input_token_mint)]
+    pub referrer_token_account: Option<Box<InterfaceAccount<'info, TokenAccount>>>,
+
+    /// The payer's account of the fee discount mint, its balance counts toward
+    /// the fee discount tier
+    #[account(
+        token::mint = amm_config.fee_discount_mint,
+        token::authority = payer,
+    )]
+    pub fee_discount_token_account: Option<Box<InterfaceAccount<'info, TokenAccount>>>,
+
+    /// The payer's stake of the fee discount mint, counts toward the fee
+    /// discount tier
+    #[account(
+        seeds = [
+            STAKE_RECORD_SEED.as_bytes(),
+            amm_config.fee_discount_mint.as_ref(),
+            payer.key().as_ref(),
+        ],
+        bump = stake_record.bump,
+    )]
+    pub stake_record: Option<Box<Account<'info, StakeRecord>>>,
+
+    /// Fee schedule of the amm config, needed when it has fee buckets beyond
+    /// the protocol and fund buckets
+    #[account(
+        seeds = [
+            FEE_SCHEDULE_SEED.as_bytes(),
+            amm_config.key().as_ref(),
+        ],
+        bump = fee_schedule.bump,
+    )]
+    pub fee_schedule: Option<Box<Account<'info, FeeSchedule>>>,
+}
+
+impl<'info> Swap<'info> {
+    /// Fees of the fee schedule buckets out of the trade fee of a swap
+    pub fn scheduled_fees(&self, trade_fee: u128) -> Result<[u64; MAX_SCHEDULED_FEE_BUCKETS]> {
+        self.amm_config.scheduled_fees(
+            self.fee_schedule
+                .as_deref()
+                .map(|fee_schedule| &**fee_schedule),
+            trade_fee,
+        )
+    }
+
+    /// The discount on the trade fee of the payer's tier
+    pub fn fee_discount_rate(&self) -> u64 {
+        let held = self
+            .fee_discount_token_account
+            .as_ref()
+            .map_or(0, |account| account.amount);
+        let staked = self
+            .stake_record
+            .as_ref()
+            .map_or(0, |stake_record| stake_record.amount);
+        self.amm_config
+            .fee_discount_rate(held.saturating_add(staked))
+    }
+
+    /// The referrer's share of the trade fee of a swap
+    pub fn referral_fee(&self, trade_fee: u128) -> Result<u64> {
+        let referral_fee_rate = self.amm_config.referral_fee_rate(
+            self.payer.key(),
+            self.referrer_token_account
+                .as_ref()
+                .map(|referrer_token_account| referrer_token_account.owner),
+        )?;
+        Ok(u64::try_from(Fees::referral_fee(trade_fee, referral_fee_rate).unwrap()).unwrap())
+    }
+
+    /// Pay the referral fee out of the input vault
+    pub fn transfer_referral_fee(&self, referral_fee: u64, auth_bump: u8) -> Result<()> {
+        match &self.referrer_token_account {
+            Some(referrer_token_account) if referral_fee > 0 => transfer_from_pool_vault_to_user(
+                self.authority.to_account_info(),
+                self.input_vault.to_account_info(),
+                referrer_token_account.to_account_info(),
+                self.input_token_mint.to_account_info(),
+                self.input_token_program.to_account_info(),
+                referral_fee,
+                self.input_token_mint.decimals,
+                &[&[crate::AUTH_SEED.as_bytes(), &[auth_bump]]],
+            ),
+            _ => Ok(()),
+        }
+    }
 }
 
 pub fn swap_base_input(ctx: Context<Swap>, amount_in: u64, minimum_amount_out: u64) -> Result<()> {
     let block_timestamp = solana_program::clock::Clock::get()?.unix_timestamp as u64;
     let pool_id = ctx.accounts.pool_state.key();
     let pool_state = &mut ctx.accounts.pool_state.load_mut()?;
+    require!(!pool_state.is_concentrated(), ErrorCode::InvalidPoolType);
     if !pool_state.get_status_by_bit(PoolStatusBitIndex::Swap)
         || block_timestamp < pool_state.open_time
     {
@@ -91,15 +198,19 @@ pub fn swap_base_input(ctx: Context<Swap>, amount_in: u64, minimum_amount_out: u
     let amount_in_without_transfer_fee = amount_in.saturating_sub(transfer_fee);
 
     // check in|out tax
-    let has_out_tax = !pool_state.tax_disabled
-        && pool_state.out_tax_rate > 0
-        && pool_state.tax_mint == ctx.accounts.output_token_mint.key();
-    let has_in_tax = !pool_state.tax_disabled
-        && pool_state.in_tax_rate > 0
-        && pool_state.tax_mint == ctx.accounts.input_token_mint.key();
+    let (in_tax_rate, out_tax_rate) = pool_state.payer_tax_rates(
+        block_timestamp,
+        ctx.accounts.input_token_mint.key(),
+        ctx.accounts
+            .tax_exemption
+            .as_deref()
+            .map(|tax_exemption| &**tax_exemption),
+    );
+    let has_out_tax = out_tax_rate > 0;
+    let has_in_tax = in_tax_rate > 0;
 
     let in_tax = if has_in_tax {
-        let in_tax = tax_amount(amount_in_without_transfer_fee, pool_state.in_tax_rate).unwrap();
+        let in_tax = tax_amount(amount_in_without_transfer_fee, in_tax_rate).unwrap();
         u64::try_from(in_tax).unwrap()
     } else {
         0
@@ -143,27 +254,31 @@ pub fn swap_base_input(ctx: Context<Swap>, amount_in: u64, minimum_amount_out: u
             return err!(ErrorCode::InvalidVault);
         };
 
-    let constant_before = u128::from(total_input_token_amount)
-        .checked_mul(u128::from(total_output_token_amount))
-        .unwrap();
-
+    let curve = pool_state.curve(block_timestamp);
     let result = CurveCalculator::swap_base_input(
+        curve.as_ref(),
         u128::from(actual_amount_in),
         u128::from(total_input_token_amount),
         u128::from(total_output_token_amount),
         ctx.accounts.amm_config.trade_fee_rate,
         ctx.accounts.amm_config.protocol_fee_rate,
         ctx.accounts.amm_config.fund_fee_rate,
-        pool_state.lp_fee_rate,
+        ctx.accounts.fee_discount_rate(),
+        pool_state.lp_fee_rate + pool_state.dynamic_fee_rate(block_timestamp),
+        trade_direction,
     )
     .ok_or(ErrorCode::ZeroTradingTokens)?;
 
-    let constant_after = result
-        .new_swap_source_amount
-        .checked_mul(u128::from(result.new_swap_destination_amount))
-        .unwrap();
-
-    require_gte!(constant_after, constant_before);
+    require!(
+        curve.check_invariant(
+            u128::from(total_input_token_amount),
+            u128::from(total_output_token_amount),
+            result.new_swap_source_amount,
+            result.new_swap_destination_amount,
+            trade_direction,
+        ),
+        ErrorCode::InvariantViolated
+    );
 
     require_eq!(
         u64::try_from(result.source_amount_swapped).unwrap(),
@@ -175,7 +290,7 @@ pub fn swap_base_input(ctx: Context<Swap>, amount_in: u64, minimum_amount_out: u
     let out_tax = if has_out_tax {
         tax_amount(
             u64::try_from(result.destination_amount_swapped).unwrap(),
-            pool_state.out_tax_rate,
+            out_tax_rate,
         )
         .ok_or(ErrorCode::TaxAmountCalculationFailed)?
     } else {
@@ -206,29 +321,41 @@ pub fn swap_base_input(ctx: Context<Swap>, amount_in: u64, minimum_amount_out: u
 
     let protocol_fee = u64::try_from(result.protocol_fee).unwrap();
     let fund_fee = u64::try_from(result.fund_fee).unwrap();
-
-    // update tax in vault
-    let tax_amount: u64 = if has_in_tax {
-        in_tax
-    } else {
-        u64::try_from(out_tax).unwrap()
-    };
-
-    if pool_state.tax_mint == pool_state.token_0_mint {
-        pool_state.tax_amount_0 = pool_state.tax_amount_0.checked_add(tax_amount).unwrap();
+    let creator_fee = pool_state.creator_fee(
+        ctx.accounts.amm_config.max_creator_fee_rate,
+        result.trade_fee,
+    );
+    let referral_fee = ctx.accounts.referral_fee(result.trade_fee)?;
+
+    // check trade limits on the taxed token
+    if pool_state.tax_mint == ctx.accounts.input_token_mint.key() {
+        pool_state.check_max_tx(
+            block_timestamp,
+            amount_in_without_transfer_fee,
+            total_input_token_amount,
+        )?;
     } else {
-        pool_state.tax_amount_1 = pool_state.tax_amount_1.checked_add(tax_amount).unwrap();
+        pool_state.check_max_tx(
+            block_timestamp,
+            u64::try_from(result.destination_amount_swapped).unwrap(),
+            total_output_token_amount,
+        )?;
     }
 
+    // update tax in vault, each side accrues its own tax
+    let out_tax = u64::try_from(out_tax).unwrap();
+    let in_tax_liquidity_amount =
+        pool_state.accrue_tax(ctx.accounts.input_token_mint.key(), in_tax);
+    let out_tax_liquidity_amount =
+        pool_state.accrue_tax(ctx.accounts.output_token_mint.key(), out_tax);
+
     #[cfg(feature = "enable-log")]
     msg!(
-        "source_amount_swapped:{}, destination_amount_swapped:{},constant_before:{},constant_after:{},tax_use_token_0:{},tax_amount:{},lp_fee:{}",
+        "source_amount_swapped:{}, destination_amount_swapped:{},in_tax:{},out_tax:{},lp_fee:{}",
         result.source_amount_swapped,
         result.destination_amount_swapped,
-        constant_before,
-        constant_after,
-        pool_state.tax_mint == pool_state.token_0_mint,
-        tax_amount,
+        in_tax,
+        out_tax,
         result.lp_fee
     );
 
@@ -251,6 +378,11 @@ pub fn swap_base_input(ctx: Context<Swap>, amount_in: u64, minimum_amount_out: u
                 pool_state.fund_fees_token_1.checked_add(fund_fee).unwrap();
         }
     };
+    pool_state.add_bucket_fees(
+        trade_direction == TradeDirection::ZeroForOne,
+        &ctx.accounts.scheduled_fees(result.trade_fee)?,
+    );
+    pool_state.add_creator_fee(trade_direction == TradeDirection::ZeroForOne, creator_fee);
 
     transfer_from_user_to_pool_vault(
         ctx.accounts.payer.to_account_info(),
@@ -273,6 +405,18 @@ pub fn swap_base_input(ctx: Context<Swap>, amount_in: u64, minimum_amount_out: u
         &[&[crate::AUTH_SEED.as_bytes(), &[pool_state.auth_bump]]],
     )?;
 
+    // the referral fee comes out of the trade fee left to the pool
+    ctx.accounts
+        .transfer_referral_fee(referral_fee, pool_state.auth_bump)?;
+
+    // check the balance a buy of the taxed token leaves
+    if pool_state.tax_mint == ctx.accounts.output_token_mint.key()
+        && pool_state.has_max_wallet(block_timestamp)
+    {
+        ctx.accounts.output_token_account.reload()?;
+        pool_state.check_max_wallet(block_timestamp, ctx.accounts.output_token_account.amount)?;
+    }
+
     ctx.accounts.input_vault.reload()?;
     ctx.accounts.output_vault.reload()?;
 
@@ -287,6 +431,16 @@ pub fn swap_base_input(ctx: Context<Swap>, amount_in: u64, minimum_amount_out: u
         ),
     };
 
+    let (token_0_price_x32, token_1_price_x32) =
+        pool_state.token_price_x32(reserve_0, reserve_1, block_timestamp);
+    let observation_state = &mut ctx.accounts.observation_state.load_mut()?;
+    pool_state.update_volatility(
+        block_timestamp,
+        observation_state.token_0_price_x32,
+        token_0_price_x32,
+    );
+    observation_state.update(block_timestamp, token_0_price_x32, token_1_price_x32);
+
     emit_cpi!(SwapEvent {
         pool_id,
         token_in: ctx.accounts.input_token_account.mint,
@@ -295,7 +449,24 @@ pub fn swap_base_input(ctx: Context<Swap>, amount_in: u64, minimum_amount_out: u
         amount_out: u64::try_from(result.destination_amount_swapped).unwrap(),
         reserve_0,
         reserve_1,
+        referral_fee,
     });
 
+    for (tax_mint, amount) in [
+        (ctx.accounts.input_token_mint.key(), in_tax_liquidity_amount),
+        (
+            ctx.accounts.output_token_mint.key(),
+            out_tax_liquidity_amount,
+        ),
+    ] {
+        if amount > 0 {
+            emit_cpi!(TaxLiquidityAddedEvent {
+                pool_id,
+                tax_mint,
+                amount,
+            });
+        }
+    }
+
     Ok(())
 }
@@ -15,6 +15,7 @@ pub fn swap_base_output(
     let block_timestamp = solana_program::clock::Clock::get()?.unix_timestamp as u64;
     let pool_id = ctx.accounts.pool_state.key();
     let pool_state = &mut ctx.accounts.pool_state.load_mut()?;
+    require!(!pool_state.is_concentrated(), ErrorCode::InvalidPoolType);
     if !pool_state.get_status_by_bit(PoolStatusBitIndex::Swap)
         || block_timestamp < pool_state.open_time
     {
@@ -22,19 +23,21 @@ pub fn swap_base_output(
     }
 
     // check in|out tax
-    let has_out_tax = !pool_state.tax_disabled
-        && pool_state.out_tax_rate > 0
-        && pool_state.tax_mint == ctx.accounts.output_token_mint.key();
-    let has_in_tax = !pool_state.tax_disabled
-        && pool_state.in_tax_rate > 0
-        && pool_state.tax_mint == ctx.accounts.input_token_mint.key();
+    let (in_tax_rate, out_tax_rate) = pool_state.payer_tax_rates(
+        block_timestamp,
+        ctx.accounts.input_token_mint.key(),
+        ctx.accounts
+            .tax_exemption
+            .as_deref()
+            .map(|tax_exemption| &**tax_exemption),
+    );
+    let has_out_tax = out_tax_rate > 0;
+    let has_in_tax = in_tax_rate > 0;
 
     let (out_tax, amount_out_with_tax) = if has_out_tax {
-        let amount_out_with_tax = fees::Fees::calculate_pre_fee_amount(
-            u128::try_from(amount_out_less_fee).unwrap(),
-            pool_state.out_tax_rate,
-        )
-        .ok_or(ErrorCode::TaxAmountCalculationFailed)?;
+        let amount_out_with_tax =
+            fees::Fees::calculate_pre_fee_amount(u128::from(amount_out_less_fee), out_tax_rate)
+                .ok_or(ErrorCode::TaxAmountCalculationFailed)?;
 
         let amount_out_with_tax = u64::try_from(amount_out_with_tax).unwrap();
 
@@ -89,35 +92,37 @@ pub fn swap_base_output(
             return err!(ErrorCode::InvalidVault);
         };
 
-    let constant_before = u128::from(total_input_token_amount)
-        .checked_mul(u128::from(total_output_token_amount))
-        .unwrap();
-
+    let curve = pool_state.curve(block_timestamp);
     let result = CurveCalculator::swap_base_output(
+        curve.as_ref(),
         u128::from(actual_amount_out),
         u128::from(total_input_token_amount),
         u128::from(total_output_token_amount),
         ctx.accounts.amm_config.trade_fee_rate,
         ctx.accounts.amm_config.protocol_fee_rate,
         ctx.accounts.amm_config.fund_fee_rate,
-        pool_state.lp_fee_rate,
+        ctx.accounts.fee_discount_rate(),
+        pool_state.lp_fee_rate + pool_state.dynamic_fee_rate(block_timestamp),
+        trade_direction,
     )
     .ok_or(ErrorCode::ZeroTradingTokens)?;
 
-    let constant_after = result
-        .new_swap_source_amount
-        .checked_mul(u128::from(result.new_swap_destination_amount))
-        .unwrap();
-
-    require_gte!(constant_after, constant_before);
+    require!(
+        curve.check_invariant(
+            u128::from(total_input_token_amount),
+            u128::from(total_output_token_amount),
+            result.new_swap_source_amount,
+            result.new_swap_destination_amount,
+            trade_direction,
+        ),
+        ErrorCode::InvariantViolated
+    );
 
     // calculate amount out with tax
     let (in_tax, amount_in_with_tax) = if has_in_tax {
-        let amount_in_with_tax = fees::Fees::calculate_pre_fee_amount(
-            result.source_amount_swapped,
-            pool_state.in_tax_rate,
-        )
-        .ok_or(ErrorCode::TaxAmountCalculationFailed)?;
+        let amount_in_with_tax =
+            fees::Fees::calculate_pre_fee_amount(result.source_amount_swapped, in_tax_rate)
+                .ok_or(ErrorCode::TaxAmountCalculationFailed)?;
 
         let in_tax = amount_in_with_tax
             .checked_sub(result.source_amount_swapped)
@@ -156,29 +161,41 @@ pub fn swap_base_output(
 
     let protocol_fee = u64::try_from(result.protocol_fee).unwrap();
     let fund_fee = u64::try_from(result.fund_fee).unwrap();
-
-    // update tax in vault
-    let tax_amount: u64 = if has_out_tax {
-        out_tax
-    } else {
-        u64::try_from(in_tax).unwrap()
-    };
-
-    if pool_state.tax_mint == pool_state.token_0_mint {
-        pool_state.tax_amount_0 = pool_state.tax_amount_0.checked_add(tax_amount).unwrap();
+    let creator_fee = pool_state.creator_fee(
+        ctx.accounts.amm_config.max_creator_fee_rate,
+        result.trade_fee,
+    );
+    let referral_fee = ctx.accounts.referral_fee(result.trade_fee)?;
+
+    // check trade limits on the taxed token
+    if pool_state.tax_mint == ctx.accounts.input_token_mint.key() {
+        pool_state.check_max_tx(
+            block_timestamp,
+            u64::try_from(amount_in_with_tax).unwrap(),
+            total_input_token_amount,
+        )?;
     } else {
-        pool_state.tax_amount_1 = pool_state.tax_amount_1.checked_add(tax_amount).unwrap();
+        pool_state.check_max_tx(
+            block_timestamp,
+            actual_amount_out,
+            total_output_token_amount,
+        )?;
     }
 
+    // update tax in vault, each side accrues its own tax
+    let in_tax = u64::try_from(in_tax).unwrap();
+    let in_tax_liquidity_amount =
+        pool_state.accrue_tax(ctx.accounts.input_token_mint.key(), in_tax);
+    let out_tax_liquidity_amount =
+        pool_state.accrue_tax(ctx.accounts.output_token_mint.key(), out_tax);
+
     #[cfg(feature = "enable-log")]
     msg!(
-        "source_amount_swapped:{}, destination_amount_swapped:{},constant_before:{},constant_after:{},tax_use_token_0:{},tax_amount:{},lp_fee:{}",
+        "source_amount_swapped:{}, destination_amount_swapped:{},in_tax:{},out_tax:{},lp_fee:{}",
         result.source_amount_swapped,
         result.destination_amount_swapped,
-        constant_before,
-        constant_after,
-        pool_state.tax_mint == pool_state.token_0_mint,
-        tax_amount,
+        in_tax,
+        out_tax,
         result.lp_fee
     );
 
@@ -200,6 +217,11 @@ pub fn swap_base_output(
                 pool_state.fund_fees_token_1.checked_add(fund_fee).unwrap();
         }
     };
+    pool_state.add_bucket_fees(
+        trade_direction == TradeDirection::ZeroForOne,
+        &ctx.accounts.scheduled_fees(result.trade_fee)?,
+    );
+    pool_state.add_creator_fee(trade_direction == TradeDirection::ZeroForOne, creator_fee);
 
     transfer_from_user_to_pool_vault(
         ctx.accounts.payer.to_account_info(),
@@ -222,6 +244,18 @@ pub fn swap_base_output(
         &[&[crate::AUTH_SEED.as_bytes(), &[pool_state.auth_bump]]],
     )?;
 
+    // the referral fee comes out of the trade fee left to the pool
+    ctx.accounts
+        .transfer_referral_fee(referral_fee, pool_state.auth_bump)?;
+
+    // check the balance a buy of the taxed token leaves
+    if pool_state.tax_mint == ctx.accounts.output_token_mint.key()
+        && pool_state.has_max_wallet(block_timestamp)
+    {
+        ctx.accounts.output_token_account.reload()?;
+        pool_state.check_max_wallet(block_timestamp, ctx.accounts.output_token_account.amount)?;
+    }
+
     ctx.accounts.input_vault.reload()?;
     ctx.accounts.output_vault.reload()?;
 
@@ -236,6 +270,16 @@ pub fn swap_base_output(
         ),
     };
 
+    let (token_0_price_x32, token_1_price_x32) =
+        pool_state.token_price_x32(reserve_0, reserve_1, block_timestamp);
+    let observation_state = &mut ctx.accounts.observation_state.load_mut()?;
+    pool_state.update_volatility(
+        block_timestamp,
+        observation_state.token_0_price_x32,
+        token_0_price_x32,
+    );
+    observation_state.update(block_timestamp, token_0_price_x32, token_1_price_x32);
+
     emit_cpi!(SwapEvent {
         pool_id,
         token_in: ctx.accounts.input_token_account.mint,
@@ -244,7 +288,24 @@ pub fn swap_base_output(
         amount_out: u64::try_from(result.destination_amount_swapped).unwrap(),
         reserve_0,
         reserve_1,
+        referral_fee,
     });
 
+    for (tax_mint, amount) in [
+        (ctx.accounts.input_token_mint.key(), in_tax_liquidity_amount),
+        (
+            ctx.accounts.output_token_mint.key(),
+            out_tax_liquidity_amount,
+        ),
+    ] {
+        if amount > 0 {
+            emit_cpi!(TaxLiquidityAddedEvent {
+                pool_id,
+                tax_mint,
+                amount,
+            });
+        }
+    }
+
     Ok(())
 }
@@ -0,0 +1,87 @@
+use crate::error::ErrorCode;
+use crate::states::*;
+use anchor_lang::prelude::*;
+
+#[event_cpi]
+#[derive(Accounts)]
+#[instruction(wallet: Pubkey)]
+pub struct AddTaxExemption<'info> {
+    /// tax authority of pool, pays for the exemption account
+    #[account(
+        mut,
+        address = pool_state.load()?.tax_authority @ ErrorCode::InvalidOwner
+    )]
+    pub owner: Signer<'info>,
+
+    pub pool_state: AccountLoader<'info, PoolState>,
+
+    /// Initialize an account to mark the wallet as exempt
+    #[account(
+        init,
+        seeds = [
+            TAX_EXEMPTION_SEED.as_bytes(),
+            pool_state.key().as_ref(),
+            wallet.as_ref(),
+        ],
+        bump,
+        payer = owner,
+        space = TaxExemption::LEN
+    )]
+    pub tax_exemption: Box<Account<'info, TaxExemption>>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct RemoveTaxExemption<'info> {
+    /// tax authority of pool, receives the rent of the exemption account
+    #[account(
+        mut,
+        address = pool_state.load()?.tax_authority @ ErrorCode::InvalidOwner
+    )]
+    pub owner: Signer<'info>,
+
+    pub pool_state: AccountLoader<'info, PoolState>,
+
+    /// The exemption to remove
+    #[account(
+        mut,
+        seeds = [
+            TAX_EXEMPTION_SEED.as_bytes(),
+            pool_state.key().as_ref(),
+            tax_exemption.wallet.as_ref(),
+        ],
+        bump = tax_exemption.bump,
+        close = owner,
+    )]
+    pub tax_exemption: Box<Account<'info, TaxExemption>>,
+
+    pub system_program: Program<'info, System>,
+}
+
+pub fn add_tax_exemption(ctx: Context<AddTaxExemption>, wallet: Pubkey) -> Result<()> {
+    let pool_id = ctx.accounts.pool_state.key();
+    let tax_exemption = &mut ctx.accounts.tax_exemption;
+    tax_exemption.bump = ctx.bumps.tax_exemption;
+    tax_exemption.pool_id = pool_id;
+    tax_exemption.wallet = wallet;
+
+    emit_cpi!(TaxExemptionUpdatedEvent {
+        pool_id,
+        wallet,
+        exempt: true,
+    });
+
+    Ok(())
+}
+
+pub fn remove_tax_exemption(ctx: Context<RemoveTaxExemption>) -> Result<()> {
+    emit_cpi!(TaxExemptionUpdatedEvent {
+        pool_id: ctx.accounts.pool_state.key(),
+        wallet: ctx.accounts.tax_exemption.wallet,
+        exempt: false,
+    });
+
+    Ok(())
+}
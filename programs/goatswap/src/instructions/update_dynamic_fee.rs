@@ -0,0 +1,43 @@
+use crate::curve::fees::FEE_RATE_DENOMINATOR_VALUE;
+use crate::error::ErrorCode;
+use crate::instructions::UpdateLpFee;
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program;
+
+/// Opt the pool in or out of the dynamic fee, a decay period of 0 turns it off
+pub fn update_dynamic_fee(
+    ctx: Context<UpdateLpFee>,
+    min_fee_rate: u64,
+    max_fee_rate: u64,
+    decay_period: u64,
+) -> Result<()> {
+    let pool_state = &mut ctx.accounts.pool_state.load_mut()?;
+    // the volatility is only tracked by the swaps of standard pools
+    require!(!pool_state.is_concentrated(), ErrorCode::InvalidPoolType);
+
+    if decay_period == 0 {
+        pool_state.dynamic_fee_min_rate = 0;
+        pool_state.dynamic_fee_max_rate = 0;
+    } else {
+        assert!(min_fee_rate <= max_fee_rate);
+        assert!(
+            pool_state.lp_fee_rate + max_fee_rate + ctx.accounts.amm_config.trade_fee_rate
+                <= FEE_RATE_DENOMINATOR_VALUE
+        );
+        pool_state.dynamic_fee_min_rate = min_fee_rate;
+        pool_state.dynamic_fee_max_rate = max_fee_rate;
+    }
+    pool_state.dynamic_fee_decay_period = decay_period;
+    pool_state.volatility_accumulator = 0;
+    pool_state.volatility_update_time = solana_program::clock::Clock::get()?.unix_timestamp as u64;
+
+    #[cfg(feature = "enable-log")]
+    msg!(
+        "min_fee_rate:{}, max_fee_rate:{}, decay_period:{}",
+        min_fee_rate,
+        max_fee_rate,
+        decay_period
+    );
+
+    Ok(())
+}
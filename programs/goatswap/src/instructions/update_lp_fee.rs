@@ -18,9 +18,11 @@ pub struct UpdateLpFee<'info> {
 }
 
 pub fn update_lp_fee(ctx: Context<UpdateLpFee>, lp_fee_rate: u64) -> Result<()> {
-    assert!(lp_fee_rate + ctx.accounts.amm_config.trade_fee_rate <= FEE_RATE_DENOMINATOR_VALUE);
-
     let pool_state = &mut ctx.accounts.pool_state.load_mut()?;
+    assert!(
+        lp_fee_rate + pool_state.dynamic_fee_max_rate + ctx.accounts.amm_config.trade_fee_rate
+            <= FEE_RATE_DENOMINATOR_VALUE
+    );
 
     #[cfg(feature = "enable-log")]
     {
@@ -36,3 +38,26 @@ pub fn update_lp_fee(ctx: Context<UpdateLpFee>, lp_fee_rate: u64) -> Result<()>
 
     Ok(())
 }
+
+pub fn update_creator_fee_rate(ctx: Context<UpdateLpFee>, creator_fee_rate: u64) -> Result<()> {
+    require_gte!(
+        ctx.accounts.amm_config.max_creator_fee_rate,
+        creator_fee_rate,
+        ErrorCode::ExceededMaxCreatorFeeRate
+    );
+    let pool_state = &mut ctx.accounts.pool_state.load_mut()?;
+
+    #[cfg(feature = "enable-log")]
+    {
+        let old_creator_fee_rate = pool_state.creator_fee_rate;
+        msg!(
+            "old_creator_fee_rate:{}, new_creator_fee_rate:{}",
+            old_creator_fee_rate,
+            creator_fee_rate
+        );
+    }
+
+    pool_state.creator_fee_rate = creator_fee_rate;
+
+    Ok(())
+}
@@ -2,6 +2,7 @@ use crate::curve::fees::FEE_RATE_DENOMINATOR_VALUE;
 use crate::error::ErrorCode;
 use crate::states::*;
 use anchor_lang::prelude::*;
+use anchor_lang::solana_program;
 
 #[event_cpi]
 #[derive(Accounts)]
@@ -17,6 +18,18 @@ pub struct UpdateTaxConfig<'info> {
     pub amm_config: Box<Account<'info, AmmConfig>>,
 }
 
+/// Applies the queued tax and lp tax increases and trade limits whose timelock
+/// has passed, anyone can crank it
+#[event_cpi]
+#[derive(Accounts)]
+pub struct ApplyTaxChange<'info> {
+    #[account(mut)]
+    pub pool_state: AccountLoader<'info, PoolState>,
+
+    #[account(address = pool_state.load()?.amm_config)]
+    pub amm_config: Box<Account<'info, AmmConfig>>,
+}
+
 #[event_cpi]
 #[derive(Accounts)]
 pub struct TransferTaxAuthority<'info> {
@@ -33,10 +46,22 @@ pub fn update_tax(
     tax_use_token_0: bool,
     in_tax_rate: u64,
     out_tax_rate: u64,
+    quote_in_tax_rate: u64,
+    quote_out_tax_rate: u64,
 ) -> Result<()> {
-    assert!(in_tax_rate <= FEE_RATE_DENOMINATOR_VALUE);
-    assert!(out_tax_rate <= FEE_RATE_DENOMINATOR_VALUE);
+    let amm_config = &ctx.accounts.amm_config;
+    for tax_rate in [
+        in_tax_rate,
+        out_tax_rate,
+        quote_in_tax_rate,
+        quote_out_tax_rate,
+    ] {
+        assert!(tax_rate <= FEE_RATE_DENOMINATOR_VALUE);
+        amm_config.check_tax_rate(tax_rate)?;
+    }
 
+    let block_timestamp = solana_program::clock::Clock::get()?.unix_timestamp as u64;
+    let pool_id = ctx.accounts.pool_state.key();
     let pool_state = &mut ctx.accounts.pool_state.load_mut()?;
 
     // get tax mint
@@ -46,22 +71,316 @@ pub fn update_tax(
         pool_state.token_1_mint
     };
 
-    pool_state.tax_mint = tax_mint;
-    pool_state.in_tax_rate = in_tax_rate;
-    pool_state.out_tax_rate = out_tax_rate;
+    // a new update replaces the queued one
+    if pool_state.has_pending_tax_change() {
+        pool_state.clear_pending_tax_change();
+        emit_cpi!(TaxChangeCancelledEvent { pool_id });
+    }
+
+    // increases wait out the timelock, decreases apply immediately
+    if amm_config.tax_increase_delay > 0
+        && pool_state.is_tax_increase(
+            tax_mint,
+            in_tax_rate,
+            out_tax_rate,
+            quote_in_tax_rate,
+            quote_out_tax_rate,
+        )
+    {
+        let apply_time = block_timestamp
+            .checked_add(amm_config.tax_increase_delay)
+            .unwrap();
+        pool_state.queue_tax_change(
+            tax_mint,
+            in_tax_rate,
+            out_tax_rate,
+            quote_in_tax_rate,
+            quote_out_tax_rate,
+            apply_time,
+        );
+
+        emit_cpi!(TaxChangeQueuedEvent {
+            pool_id,
+            tax_mint,
+            in_tax_rate,
+            out_tax_rate,
+            quote_in_tax_rate,
+            quote_out_tax_rate,
+            apply_time,
+        });
+        return Ok(());
+    }
+
+    pool_state.set_tax(
+        tax_mint,
+        in_tax_rate,
+        out_tax_rate,
+        quote_in_tax_rate,
+        quote_out_tax_rate,
+    );
 
     emit_cpi!(TaxConfigUpdatedEvent {
-        pool_id: ctx.accounts.pool_state.key(),
-        tax_mint: tax_mint,
+        pool_id,
+        tax_mint,
         tax_authority: pool_state.tax_authority,
-        in_tax_rate: in_tax_rate,
-        out_tax_rate: out_tax_rate,
+        in_tax_rate,
+        out_tax_rate,
+        quote_in_tax_rate,
+        quote_out_tax_rate,
         tax_disabled: pool_state.tax_disabled,
     });
 
     Ok(())
 }
 
+pub fn apply_tax_change(ctx: Context<ApplyTaxChange>) -> Result<()> {
+    let block_timestamp = solana_program::clock::Clock::get()?.unix_timestamp as u64;
+    let pool_id = ctx.accounts.pool_state.key();
+    let pool_state = &mut ctx.accounts.pool_state.load_mut()?;
+
+    require!(
+        pool_state.has_pending_tax_change()
+            || pool_state.has_pending_lp_tax_change()
+            || pool_state.has_pending_trade_limits(),
+        ErrorCode::NoPendingTaxChange
+    );
+    let mut applied = false;
+
+    let apply_time = pool_state.pending_tax_apply_time;
+    if pool_state.has_pending_tax_change() && block_timestamp >= apply_time {
+        let tax_mint = pool_state.pending_tax_mint;
+        let in_tax_rate = pool_state.pending_in_tax_rate;
+        let out_tax_rate = pool_state.pending_out_tax_rate;
+        let quote_in_tax_rate = pool_state.pending_quote_in_tax_rate;
+        let quote_out_tax_rate = pool_state.pending_quote_out_tax_rate;
+        // the cap may have been lowered while the change was queued
+        for tax_rate in [
+            in_tax_rate,
+            out_tax_rate,
+            quote_in_tax_rate,
+            quote_out_tax_rate,
+        ] {
+            ctx.accounts.amm_config.check_tax_rate(tax_rate)?;
+        }
+
+        pool_state.set_tax(
+            tax_mint,
+            in_tax_rate,
+            out_tax_rate,
+            quote_in_tax_rate,
+            quote_out_tax_rate,
+        );
+        pool_state.clear_pending_tax_change();
+
+        emit_cpi!(TaxChangeAppliedEvent {
+            pool_id,
+            tax_mint,
+            in_tax_rate,
+            out_tax_rate,
+            quote_in_tax_rate,
+            quote_out_tax_rate,
+        });
+        emit_cpi!(TaxConfigUpdatedEvent {
+            pool_id,
+            tax_mint,
+            tax_authority: pool_state.tax_authority,
+            in_tax_rate,
+            out_tax_rate,
+            quote_in_tax_rate,
+            quote_out_tax_rate,
+            tax_disabled: pool_state.tax_disabled,
+        });
+        applied = true;
+    }
+
+    if let Some((deposit_tax_rate, withdraw_tax_rate)) =
+        pool_state.take_ready_lp_tax_change(block_timestamp)
+    {
+        ctx.accounts
+            .amm_config
+            .check_lp_tax_rates(deposit_tax_rate, withdraw_tax_rate)?;
+        pool_state.set_lp_tax(deposit_tax_rate, withdraw_tax_rate);
+
+        emit_cpi!(LpTaxUpdatedEvent {
+            pool_id,
+            deposit_tax_rate,
+            withdraw_tax_rate,
+        });
+        applied = true;
+    }
+
+    if let Some(trade_limits) = pool_state.take_ready_trade_limits(block_timestamp) {
+        pool_state.set_trade_limits(trade_limits);
+
+        emit_cpi!(TradeLimitsUpdatedEvent {
+            pool_id,
+            max_tx_amount: trade_limits.max_tx_amount,
+            max_tx_rate: trade_limits.max_tx_rate,
+            max_wallet_amount: trade_limits.max_wallet_amount,
+            end_time: trade_limits.end_time,
+        });
+        applied = true;
+    }
+
+    require!(applied, ErrorCode::TaxChangeNotReady);
+
+    Ok(())
+}
+
+pub fn cancel_tax_change(ctx: Context<UpdateTaxConfig>) -> Result<()> {
+    let pool_state = &mut ctx.accounts.pool_state.load_mut()?;
+
+    require!(
+        pool_state.has_pending_tax_change()
+            || pool_state.has_pending_lp_tax_change()
+            || pool_state.has_pending_trade_limits(),
+        ErrorCode::NoPendingTaxChange
+    );
+    pool_state.clear_pending_tax_change();
+    pool_state.clear_pending_lp_tax_change();
+    pool_state.clear_pending_trade_limits();
+
+    emit_cpi!(TaxChangeCancelledEvent {
+        pool_id: ctx.accounts.pool_state.key(),
+    });
+
+    Ok(())
+}
+
+pub fn update_tax_recipient(ctx: Context<UpdateTaxConfig>, tax_recipient: Pubkey) -> Result<()> {
+    let pool_state = &mut ctx.accounts.pool_state.load_mut()?;
+    pool_state.tax_recipient = tax_recipient;
+
+    emit_cpi!(TaxRecipientDelegatedEvent {
+        pool_id: ctx.accounts.pool_state.key(),
+        tax_recipient,
+    });
+
+    Ok(())
+}
+
+pub fn update_trade_limits(ctx: Context<UpdateTaxConfig>, trade_limits: TradeLimits) -> Result<()> {
+    trade_limits.validate()?;
+
+    let amm_config = &ctx.accounts.amm_config;
+    let block_timestamp = solana_program::clock::Clock::get()?.unix_timestamp as u64;
+    let pool_id = ctx.accounts.pool_state.key();
+    let pool_state = &mut ctx.accounts.pool_state.load_mut()?;
+
+    // a new update replaces the queued one
+    pool_state.clear_pending_trade_limits();
+
+    // tightening or extending waits out the timelock, loosening applies immediately
+    if amm_config.tax_increase_delay > 0 && !trade_limits.loosens(&pool_state.trade_limits()) {
+        let apply_time = block_timestamp
+            .checked_add(amm_config.tax_increase_delay)
+            .unwrap();
+        pool_state.queue_trade_limits(trade_limits, apply_time);
+
+        emit_cpi!(TradeLimitsQueuedEvent {
+            pool_id,
+            max_tx_amount: trade_limits.max_tx_amount,
+            max_tx_rate: trade_limits.max_tx_rate,
+            max_wallet_amount: trade_limits.max_wallet_amount,
+            end_time: trade_limits.end_time,
+            apply_time,
+        });
+        return Ok(());
+    }
+
+    pool_state.set_trade_limits(trade_limits);
+
+    emit_cpi!(TradeLimitsUpdatedEvent {
+        pool_id,
+        max_tx_amount: trade_limits.max_tx_amount,
+        max_tx_rate: trade_limits.max_tx_rate,
+        max_wallet_amount: trade_limits.max_wallet_amount,
+        end_time: trade_limits.end_time,
+    });
+
+    Ok(())
+}
+
+pub fn update_tax_liquidity_rate(
+    ctx: Context<UpdateTaxConfig>,
+    tax_liquidity_rate: u64,
+) -> Result<()> {
+    require_gte!(
+        FEE_RATE_DENOMINATOR_VALUE,
+        tax_liquidity_rate,
+        ErrorCode::InvalidInput
+    );
+
+    let pool_state = &mut ctx.accounts.pool_state.load_mut()?;
+    // concentrated liquidity has no reserves for the tax to go back to
+    require!(!pool_state.is_concentrated(), ErrorCode::InvalidPoolType);
+    pool_state.tax_liquidity_rate = tax_liquidity_rate;
+
+    emit_cpi!(TaxLiquidityRateUpdatedEvent {
+        pool_id: ctx.accounts.pool_state.key(),
+        tax_liquidity_rate,
+    });
+
+    Ok(())
+}
+
+pub fn update_lp_tax(
+    ctx: Context<UpdateTaxConfig>,
+    deposit_tax_rate: u64,
+    withdraw_tax_rate: u64,
+) -> Result<()> {
+    let amm_config = &ctx.accounts.amm_config;
+    amm_config.check_lp_tax_rates(deposit_tax_rate, withdraw_tax_rate)?;
+
+    let block_timestamp = solana_program::clock::Clock::get()?.unix_timestamp as u64;
+    let pool_id = ctx.accounts.pool_state.key();
+    let pool_state = &mut ctx.accounts.pool_state.load_mut()?;
+    require!(!pool_state.is_concentrated(), ErrorCode::InvalidPoolType);
+
+    // a new update replaces the queued one
+    pool_state.clear_pending_lp_tax_change();
+
+    // increases wait out the timelock, decreases apply immediately
+    if amm_config.tax_increase_delay > 0
+        && pool_state.is_lp_tax_increase(deposit_tax_rate, withdraw_tax_rate)
+    {
+        let apply_time = block_timestamp
+            .checked_add(amm_config.tax_increase_delay)
+            .unwrap();
+        pool_state.queue_lp_tax_change(deposit_tax_rate, withdraw_tax_rate, apply_time);
+
+        emit_cpi!(LpTaxChangeQueuedEvent {
+            pool_id,
+            deposit_tax_rate,
+            withdraw_tax_rate,
+            apply_time,
+        });
+        return Ok(());
+    }
+
+    pool_state.set_lp_tax(deposit_tax_rate, withdraw_tax_rate);
+
+    emit_cpi!(LpTaxUpdatedEvent {
+        pool_id,
+        deposit_tax_rate,
+        withdraw_tax_rate,
+    });
+
+    Ok(())
+}
+
+pub fn update_tax_burn(ctx: Context<UpdateTaxConfig>, tax_burn: bool) -> Result<()> {
+    let pool_state = &mut ctx.accounts.pool_state.load_mut()?;
+    pool_state.tax_burn = tax_burn;
+
+    emit_cpi!(TaxBurnUpdatedEvent {
+        pool_id: ctx.accounts.pool_state.key(),
+        tax_burn,
+    });
+
+    Ok(())
+}
+
 pub fn transfer_tax_authority(
     ctx: Context<TransferTaxAuthority>,
     new_authority: Pubkey,
@@ -74,6 +393,8 @@ pub fn transfer_tax_authority(
     );
 
     pool_state.tax_authority = new_authority;
+    // the proceeds go with the authority
+    pool_state.tax_recipient = Pubkey::default();
 
     emit_cpi!(TaxConfigUpdatedEvent {
         pool_id: ctx.accounts.pool_state.key(),
@@ -81,6 +402,8 @@ pub fn transfer_tax_authority(
         tax_authority: new_authority,
         in_tax_rate: pool_state.in_tax_rate,
         out_tax_rate: pool_state.out_tax_rate,
+        quote_in_tax_rate: pool_state.quote_in_tax_rate,
+        quote_out_tax_rate: pool_state.quote_out_tax_rate,
         tax_disabled: pool_state.tax_disabled,
     });
 
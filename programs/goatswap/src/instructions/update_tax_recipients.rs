@@ -0,0 +1,49 @@
+use crate::error::ErrorCode;
+use crate::states::*;
+use anchor_lang::prelude::*;
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct UpdateTaxRecipients<'info> {
+    /// tax authority of pool, pays for the recipients account
+    #[account(
+        mut,
+        address = pool_state.load()?.tax_authority @ ErrorCode::InvalidOwner
+    )]
+    pub owner: Signer<'info>,
+
+    pub pool_state: AccountLoader<'info, PoolState>,
+
+    /// The wallets the tax of the pool is split across
+    #[account(
+        init_if_needed,
+        seeds = [
+            TAX_RECIPIENTS_SEED.as_bytes(),
+            pool_state.key().as_ref(),
+        ],
+        bump,
+        payer = owner,
+        space = TaxRecipients::LEN
+    )]
+    pub tax_recipients: Box<Account<'info, TaxRecipients>>,
+
+    pub system_program: Program<'info, System>,
+}
+
+pub fn update_tax_recipients(
+    ctx: Context<UpdateTaxRecipients>,
+    recipients: Vec<TaxRecipient>,
+) -> Result<()> {
+    let pool_id = ctx.accounts.pool_state.key();
+    let tax_recipients = &mut ctx.accounts.tax_recipients;
+    tax_recipients.bump = ctx.bumps.tax_recipients;
+    tax_recipients.pool_id = pool_id;
+    tax_recipients.set(&recipients)?;
+
+    emit_cpi!(TaxRecipientsUpdatedEvent {
+        pool_id,
+        recipients,
+    });
+
+    Ok(())
+}
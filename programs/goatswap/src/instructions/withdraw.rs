@@ -4,6 +4,7 @@ use crate::error::ErrorCode;
 use crate::states::*;
 use crate::utils::token::*;
 use anchor_lang::prelude::*;
+use anchor_lang::solana_program;
 use anchor_spl::token::Token;
 use anchor_spl::token_interface::{Mint, Token2022, TokenAccount};
 
@@ -94,6 +95,10 @@ pub struct Withdraw<'info> {
         address = spl_memo::id()
     )]
     pub memo_program: UncheckedAccount<'info>,
+
+    /// The program account for the most recent oracle observation
+    #[account(mut, address = pool_state.load()?.observation_key)]
+    pub observation_state: AccountLoader<'info, ObservationState>,
 }
 
 pub fn withdraw(
@@ -105,6 +110,7 @@ pub fn withdraw(
     require_gt!(ctx.accounts.lp_mint.supply, 0);
     let pool_id = ctx.accounts.pool_state.key();
     let pool_state = &mut ctx.accounts.pool_state.load_mut()?;
+    require!(!pool_state.is_concentrated(), ErrorCode::InvalidPoolType);
     if !pool_state.get_status_by_bit(PoolStatusBitIndex::Withdraw) {
         return err!(ErrorCode::NotApproved);
     }
@@ -113,7 +119,9 @@ pub fn withdraw(
         ctx.accounts.token_0_vault.amount,
         ctx.accounts.token_1_vault.amount,
     );
+    let block_timestamp = solana_program::clock::Clock::get()?.unix_timestamp as u64;
     let results = CurveCalculator::lp_tokens_to_trading_tokens(
+        pool_state.curve(block_timestamp).as_ref(),
         u128::from(lp_token_amount),
         u128::from(pool_state.lp_supply),
         u128::from(total_token_0_amount),
@@ -124,6 +132,15 @@ pub fn withdraw(
 
     let token_0_amount = u64::try_from(results.token_0_amount).unwrap();
     let token_0_amount = std::cmp::min(total_token_0_amount, token_0_amount);
+    let token_1_amount = u64::try_from(results.token_1_amount).unwrap();
+    let token_1_amount = std::cmp::min(total_token_1_amount, token_1_amount);
+
+    // the withdraw tax is kept in the vault from the tax mint leg
+    let (tax_0, tax_1) =
+        pool_state.lp_tax(token_0_amount, token_1_amount, pool_state.withdraw_tax_rate);
+    let token_0_amount = token_0_amount.checked_sub(tax_0).unwrap();
+    let token_1_amount = token_1_amount.checked_sub(tax_1).unwrap();
+
     let (receive_token_0_amount, token_0_transfer_fee) = {
         let transfer_fee =
             get_transfer_fee(&ctx.accounts.vault_0_mint.to_account_info(), token_0_amount)?;
@@ -133,8 +150,6 @@ pub fn withdraw(
         )
     };
 
-    let token_1_amount = u64::try_from(results.token_1_amount).unwrap();
-    let token_1_amount = std::cmp::min(total_token_1_amount, token_1_amount);
     let (receive_token_1_amount, token_1_transfer_fee) = {
         let transfer_fee =
             get_transfer_fee(&ctx.accounts.vault_1_mint.to_account_info(), token_1_amount)?;
@@ -161,6 +176,8 @@ pub fn withdraw(
         return Err(ErrorCode::ExceededSlippage.into());
     }
 
+    let tax_mint = pool_state.tax_mint;
+    pool_state.add_tax_amount(tax_mint, tax_0 + tax_1);
     pool_state.lp_supply = pool_state.lp_supply.checked_sub(lp_token_amount).unwrap();
     token_burn(
         ctx.accounts.owner.to_account_info(),
@@ -204,6 +221,18 @@ pub fn withdraw(
     ctx.accounts.token_0_vault.reload()?;
     ctx.accounts.token_1_vault.reload()?;
 
+    let (reserve_0, reserve_1) = pool_state.vault_amount_without_fee(
+        ctx.accounts.token_0_vault.amount,
+        ctx.accounts.token_1_vault.amount,
+    );
+    let (token_0_price_x32, token_1_price_x32) =
+        pool_state.token_price_x32(reserve_0, reserve_1, block_timestamp);
+    ctx.accounts.observation_state.load_mut()?.update(
+        block_timestamp,
+        token_0_price_x32,
+        token_1_price_x32,
+    );
+
     emit_cpi!(LpChangeEvent {
         pool_id,
         amount_lp: lp_token_amount,
@@ -211,7 +240,8 @@ pub fn withdraw(
         amount_1: token_1_amount,
         reserve_0: ctx.accounts.token_0_vault.amount,
         reserve_1: ctx.accounts.token_1_vault.amount,
-        change_type: 2
+        change_type: 2,
+        tax_amount: tax_0 + tax_1,
     });
 
     Ok(())
@@ -1,3 +1,5 @@
+#![allow(clippy::too_many_arguments)]
+
 pub mod curve;
 pub mod error;
 pub mod instructions;
@@ -7,6 +9,9 @@ pub mod utils;
 use crate::curve::fees::FEE_RATE_DENOMINATOR_VALUE;
 use anchor_lang::prelude::*;
 use instructions::*;
+use states::{
+    CurveParams, FeeBucket, FeeDiscountTier, TaxRecipient, TaxSchedule, TradeLimits, TwapPrice,
+};
 
 #[cfg(feature = "devnet")]
 declare_id!("HKwqLZQw1fcnnFds4nkxYAmYK67TvtZ6TnVLUMJviWPL");
@@ -88,12 +93,88 @@ pub mod goatswap {
     /// * `new_fund_owner`- The config's new fund owner, be set when `param` is 4
     /// * `create_pool_fee`- The config's new owner, be set when `param` is 5
     /// * `disable_create_pool`- The config's new fund owner, be set when `param` is 6
-    /// * `param`- The vaule can be 0 | 1 | 2 | 3 | 4 | 5 | 6, otherwise will report a error
+    /// * `max_tax_rate`- The max tax rate of the pools, no cap when 0, be set when `param` is 7
+    /// * `tax_increase_delay`- The timelock of tax increases in seconds, be set when `param` is 8
+    /// * `referral_fee_rate`- The referrer's share of the trade fee, be set when `param` is 9
+    /// * `max_creator_fee_rate`- The max share of the trade fee of pool creators, be set when `param` is 10
+    /// * `param`- The vaule can be 0 | 1 | 2 | 3 | 4 | 5 | 6 | 7 | 8 | 9 | 10, otherwise will report a error
     ///
     pub fn update_amm_config(ctx: Context<UpdateAmmConfig>, param: u8, value: u64) -> Result<()> {
         instructions::update_amm_config(ctx, param, value)
     }
 
+    /// Set the fee discount mint of amm config and the tiers of the discount on
+    /// the trade fee its holders and stakers get
+    ///
+    /// # Arguments
+    ///
+    /// * `ctx`- The context of accounts
+    /// * `fee_discount_mint` - The mint held or staked for the discount
+    /// * `tiers` - Up to three tiers, replacing the current ones
+    ///
+    pub fn update_fee_discount(
+        ctx: Context<UpdateAmmConfig>,
+        fee_discount_mint: Pubkey,
+        tiers: Vec<FeeDiscountTier>,
+    ) -> Result<()> {
+        instructions::update_fee_discount(ctx, fee_discount_mint, tiers)
+    }
+
+    /// Set the fee buckets of amm config beyond its protocol and fund buckets,
+    /// each taking a share of the trade fee out of the lp share. The buckets in
+    /// use keep their index and owner, only their rates can change
+    ///
+    /// # Arguments
+    ///
+    /// * `ctx`- The context of accounts
+    /// * `buckets` - Up to six buckets, collected as buckets 2 and up
+    ///
+    pub fn update_fee_schedule(
+        ctx: Context<UpdateFeeSchedule>,
+        buckets: Vec<FeeBucket>,
+    ) -> Result<()> {
+        instructions::update_fee_schedule(ctx, buckets)
+    }
+
+    /// Hand a bucket of the fee schedule to a new owner, signed by its current owner
+    ///
+    /// # Arguments
+    ///
+    /// * `ctx`- The context of accounts
+    /// * `bucket` - The fee bucket, numbered as in `collect_fees`
+    /// * `new_owner` - The wallet collecting the bucket from now on
+    ///
+    pub fn transfer_fee_bucket(
+        ctx: Context<TransferFeeBucket>,
+        bucket: u8,
+        new_owner: Pubkey,
+    ) -> Result<()> {
+        instructions::transfer_fee_bucket(ctx, bucket, new_owner)
+    }
+
+    /// Stake tokens of a mint, the stake counts toward the fee discount tier of
+    /// the owner's swaps on configs discounting that mint
+    ///
+    /// # Arguments
+    ///
+    /// * `ctx`- The context of accounts
+    /// * `amount` - amount to stake
+    ///
+    pub fn stake(ctx: Context<Stake>, amount: u64) -> Result<()> {
+        instructions::stake(ctx, amount)
+    }
+
+    /// Unstake tokens of a mint
+    ///
+    /// # Arguments
+    ///
+    /// * `ctx`- The context of accounts
+    /// * `amount` - amount to unstake
+    ///
+    pub fn unstake(ctx: Context<Unstake>, amount: u64) -> Result<()> {
+        instructions::unstake(ctx, amount)
+    }
+
     /// Update pool status for given vaule
     ///
     /// # Arguments
@@ -119,7 +200,8 @@ pub mod goatswap {
         instructions::update_pool_tax_status(ctx, tax_disabled)
     }
 
-    /// Update tax
+    /// Update tax, capped by the max tax rate of the amm config. Increases are
+    /// queued until the tax increase delay has passed, decreases apply immediately
     ///
     /// # Arguments
     ///
@@ -127,14 +209,119 @@ pub mod goatswap {
     /// * `tax_use_token_0` - tax use token0 or token1
     /// * `tax_fee_in_rate` - new tax fee rate of pool when swap in
     /// * `tax_fee_out_rate` - new tax fee rate of pool when swap out
+    /// * `quote_in_tax_rate` - new tax rate of the other token of the pair when swap in
+    /// * `quote_out_tax_rate` - new tax rate of the other token of the pair when swap out
     ///
     pub fn update_tax(
         ctx: Context<UpdateTaxConfig>,
         tax_use_token_0: bool,
         in_tax_rate: u64,
         out_tax_rate: u64,
+        quote_in_tax_rate: u64,
+        quote_out_tax_rate: u64,
     ) -> Result<()> {
-        instructions::update_tax(ctx, tax_use_token_0, in_tax_rate, out_tax_rate)
+        instructions::update_tax(
+            ctx,
+            tax_use_token_0,
+            in_tax_rate,
+            out_tax_rate,
+            quote_in_tax_rate,
+            quote_out_tax_rate,
+        )
+    }
+
+    /// Update the auto-liquidity share of the tax, which swaps leave in the
+    /// reserves for the liquidity providers instead of accruing it for collection
+    ///
+    /// # Arguments
+    ///
+    /// * `ctx`- The context of accounts
+    /// * `tax_liquidity_rate` - the share of the tax, over 1000000
+    ///
+    pub fn update_tax_liquidity_rate(
+        ctx: Context<UpdateTaxConfig>,
+        tax_liquidity_rate: u64,
+    ) -> Result<()> {
+        instructions::update_tax_liquidity_rate(ctx, tax_liquidity_rate)
+    }
+
+    /// Update the tax rates on the tax mint leg of deposits and withdrawals,
+    /// increases are queued behind the tax increase delay of the amm config
+    ///
+    /// # Arguments
+    ///
+    /// * `ctx`- The context of accounts
+    /// * `deposit_tax_rate` - the tax rate paid on top of a deposit
+    /// * `withdraw_tax_rate` - the tax rate kept from a withdrawal
+    ///
+    pub fn update_lp_tax(
+        ctx: Context<UpdateTaxConfig>,
+        deposit_tax_rate: u64,
+        withdraw_tax_rate: u64,
+    ) -> Result<()> {
+        instructions::update_lp_tax(ctx, deposit_tax_rate, withdraw_tax_rate)
+    }
+
+    /// Burn the collected tax of the tax mint instead of paying it out
+    ///
+    /// # Arguments
+    ///
+    /// * `ctx`- The context of accounts
+    /// * `tax_burn` - whether to burn the tax
+    ///
+    pub fn update_tax_burn(ctx: Context<UpdateTaxConfig>, tax_burn: bool) -> Result<()> {
+        instructions::update_tax_burn(ctx, tax_burn)
+    }
+
+    /// Apply the queued tax and lp tax increases and trade limits whose timelock has passed,
+    /// anyone can call it
+    ///
+    /// # Arguments
+    ///
+    /// * `ctx`- The context of accounts
+    ///
+    pub fn apply_tax_change(ctx: Context<ApplyTaxChange>) -> Result<()> {
+        instructions::apply_tax_change(ctx)
+    }
+
+    /// Cancel the queued tax and lp tax increases and trade limits
+    ///
+    /// # Arguments
+    ///
+    /// * `ctx`- The context of accounts
+    ///
+    pub fn cancel_tax_change(ctx: Context<UpdateTaxConfig>) -> Result<()> {
+        instructions::cancel_tax_change(ctx)
+    }
+
+    /// Delegate tax collection to a wallet, which also becomes the payee of
+    /// permissionless collections. The default pubkey removes the delegate
+    ///
+    /// # Arguments
+    ///
+    /// * `ctx`- The context of accounts
+    /// * `tax_recipient` - the delegated wallet
+    ///
+    pub fn update_tax_recipient(
+        ctx: Context<UpdateTaxConfig>,
+        tax_recipient: Pubkey,
+    ) -> Result<()> {
+        instructions::update_tax_recipient(ctx, tax_recipient)
+    }
+
+    /// Update the max transaction and max wallet limits of the pool, limits that
+    /// tighten or extend the current ones are queued behind the tax increase delay
+    ///
+    /// # Arguments
+    ///
+    /// * `ctx`- The context of accounts
+    /// * `trade_limits` - new limits, zero values turn a limit off
+    ///
+    pub fn update_trade_limits(
+        ctx: Context<UpdateTaxConfig>,
+        trade_limits: TradeLimits,
+    ) -> Result<()> {
+        instructions::update_trade_limits(ctx, trade_limits)
     }
 
     /// Update tax
@@ -148,6 +335,52 @@ pub mod goatswap {
         instructions::update_lp_fee(ctx, lp_fee_rate)
     }
 
+    /// Update the pool creator's share of the trade fee, capped by the amm config
+    ///
+    /// # Arguments
+    ///
+    /// * `ctx`- The context of accounts
+    /// * `creator_fee_rate` - creator fee rate value
+    ///
+    pub fn update_creator_fee_rate(ctx: Context<UpdateLpFee>, creator_fee_rate: u64) -> Result<()> {
+        instructions::update_creator_fee_rate(ctx, creator_fee_rate)
+    }
+
+    /// Collect the creator fee accrued to the pool
+    ///
+    /// # Arguments
+    ///
+    /// * `ctx` - The context of accounts
+    /// * `amount_0_requested` - The maximum amount of token_0 to send, can be 0 to collect fees in only token_1
+    /// * `amount_1_requested` - The maximum amount of token_1 to send, can be 0 to collect fees in only token_0
+    ///
+    pub fn collect_creator_fee(
+        ctx: Context<CollectCreatorFee>,
+        amount_0_requested: u64,
+        amount_1_requested: u64,
+    ) -> Result<()> {
+        instructions::collect_creator_fee(ctx, amount_0_requested, amount_1_requested)
+    }
+
+    /// Opt the pool in or out of the dynamic fee, an extra lp fee rising with the
+    /// price moves of recent swaps and decaying back to the min rate
+    ///
+    /// # Arguments
+    ///
+    /// * `ctx`- The context of accounts
+    /// * `min_fee_rate` - extra lp fee rate charged when the pool is calm
+    /// * `max_fee_rate` - cap on the extra lp fee rate
+    /// * `decay_period` - seconds for the volatility to decay back to 0, 0 to opt out
+    ///
+    pub fn update_dynamic_fee(
+        ctx: Context<UpdateLpFee>,
+        min_fee_rate: u64,
+        max_fee_rate: u64,
+        decay_period: u64,
+    ) -> Result<()> {
+        instructions::update_dynamic_fee(ctx, min_fee_rate, max_fee_rate, decay_period)
+    }
+
     /// Update tax authority
     ///
     /// # Arguments
@@ -173,6 +406,32 @@ pub mod goatswap {
         instructions::transfer_pool_owner(ctx, new_owner)
     }
 
+    /// Ramp the amplification coefficient of a stable swap pool linearly to a new value
+    ///
+    /// # Arguments
+    ///
+    /// * `ctx`- The context of accounts
+    /// * `target_amp` - The amplification coefficient to reach
+    /// * `ramp_stop_time` - The timestamp at which `target_amp` is reached
+    ///
+    pub fn ramp_amp(
+        ctx: Context<UpdatePoolStatus>,
+        target_amp: u64,
+        ramp_stop_time: u64,
+    ) -> Result<()> {
+        instructions::ramp_amp(ctx, target_amp, ramp_stop_time)
+    }
+
+    /// Stop an ongoing amplification coefficient ramp at its current value
+    ///
+    /// # Arguments
+    ///
+    /// * `ctx`- The context of accounts
+    ///
+    pub fn stop_ramp_amp(ctx: Context<UpdatePoolStatus>) -> Result<()> {
+        instructions::stop_ramp_amp(ctx)
+    }
+
     /// Collect the protocol fee accrued to the pool
     ///
     /// # Arguments
@@ -205,14 +464,114 @@ pub mod goatswap {
         instructions::collect_fund_fee(ctx, amount_0_requested, amount_1_requested)
     }
 
-    /// Collect the tax accrued to the pool
+    /// Collect the fees accrued to a fee bucket of the pool
+    ///
+    /// # Arguments
+    ///
+    /// * `ctx` - The context of accounts
+    /// * `bucket` - 0 for the protocol fee, 1 for the fund fee, 2 and up for the buckets of the fee schedule, 255 for the creator fee
+    /// * `amount_0_requested` - The maximum amount of token_0 to send, can be 0 to collect fees in only token_1
+    /// * `amount_1_requested` - The maximum amount of token_1 to send, can be 0 to collect fees in only token_0
+    ///
+    pub fn collect_fees(
+        ctx: Context<CollectFees>,
+        bucket: u8,
+        amount_0_requested: u64,
+        amount_1_requested: u64,
+    ) -> Result<()> {
+        instructions::collect_fees(ctx, bucket, amount_0_requested, amount_1_requested)
+    }
+
+    /// Sweep all the fees accrued to a fee bucket of the pool to the associated
+    /// token accounts of the bucket owner, anyone can call it
+    ///
+    /// # Arguments
+    ///
+    /// * `ctx` - The context of accounts
+    /// * `bucket` - The fee bucket, numbered as in `collect_fees`
+    ///
+    pub fn sweep_fees(ctx: Context<SweepFees>, bucket: u8) -> Result<()> {
+        instructions::sweep_fees(ctx, bucket)
+    }
+
+    /// Collect the tax accrued to the pool, split across the tax recipients
+    /// when the pool has them. Signed by the tax authority or tax recipient the
+    /// tax can go to any account, signed by anyone else only to the tax recipient
+    ///
+    /// # Arguments
+    ///
+    /// * `ctx` - The context of accounts, followed by the token_0 accounts of
+    /// the tax recipients when token_0 tax is paid out, then their token_1 accounts
+    /// * `amount_0_requested` - The maximum amount of token_0 tax to collect
+    /// * `amount_1_requested` - The maximum amount of token_1 tax to collect
+    ///
+    pub fn collect_tax<'a, 'b, 'c: 'info, 'info>(
+        ctx: Context<'a, 'b, 'c, 'info, CollectTax<'info>>,
+        amount_0_requested: u64,
+        amount_1_requested: u64,
+    ) -> Result<()> {
+        instructions::collect_tax(ctx, amount_0_requested, amount_1_requested)
+    }
+
+    /// Collect the tax accrued in the tax mint as the quote token, swapping it
+    /// through the pool at the current curve price
     ///
     /// # Arguments
     ///
     /// * `ctx` - The context of accounts
+    /// * `minimum_amount_out` - Minimum amount of the quote token to receive, prevents excessive slippage
+    ///
+    pub fn collect_tax_as_quote(
+        ctx: Context<CollectTaxAsQuote>,
+        minimum_amount_out: u64,
+    ) -> Result<()> {
+        instructions::collect_tax_as_quote(ctx, minimum_amount_out)
+    }
+
+    /// Burn the accrued tax of the tax mint of a pool in burn mode, anyone can call it
+    ///
+    /// # Arguments
+    ///
+    /// * `ctx` - The context of accounts
+    ///
+    pub fn burn_tax(ctx: Context<BurnTax>) -> Result<()> {
+        instructions::burn_tax(ctx)
+    }
+
+    /// Split the collected tax of the pool across up to five wallets by basis
+    /// points, an empty list sends it to a single recipient again
+    ///
+    /// # Arguments
     ///
-    pub fn collect_tax(ctx: Context<CollectTax>) -> Result<()> {
-        instructions::collect_tax(ctx)
+    /// * `ctx`- The context of accounts
+    /// * `recipients` - the wallets and their shares, adding up to 10000
+    ///
+    pub fn update_tax_recipients(
+        ctx: Context<UpdateTaxRecipients>,
+        recipients: Vec<TaxRecipient>,
+    ) -> Result<()> {
+        instructions::update_tax_recipients(ctx, recipients)
+    }
+
+    /// Exempt a wallet from the tax of the pool, swaps paid by it are not taxed
+    ///
+    /// # Arguments
+    ///
+    /// * `ctx`- The context of accounts
+    /// * `wallet` - the wallet to exempt
+    ///
+    pub fn add_tax_exemption(ctx: Context<AddTaxExemption>, wallet: Pubkey) -> Result<()> {
+        instructions::add_tax_exemption(ctx, wallet)
+    }
+
+    /// Remove the tax exemption of a wallet
+    ///
+    /// # Arguments
+    ///
+    /// * `ctx`- The context of accounts
+    ///
+    pub fn remove_tax_exemption(ctx: Context<RemoveTaxExemption>) -> Result<()> {
+        instructions::remove_tax_exemption(ctx)
     }
 
     /// Creates a pool for the given token pair and the initial price
@@ -223,6 +582,9 @@ pub mod goatswap {
     /// * `init_amount_0` - the initial amount_0 to deposit
     /// * `init_amount_1` - the initial amount_1 to deposit
     /// * `open_time` - the timestamp allowed for swap
+    /// * `curve_params` - the curve of the pool, constant product when not set
+    /// * `tax_schedule` - launch tax rates decaying to `in_tax_rate` and `out_tax_rate` from the open time
+    /// * `trade_limits` - max transaction and max wallet limits on the taxed token
     ///
     pub fn initialize(
         ctx: Context<Initialize>,
@@ -233,6 +595,9 @@ pub mod goatswap {
         in_tax_rate: u64,
         out_tax_rate: u64,
         lp_fee_rate: Option<u64>,
+        curve_params: Option<CurveParams>,
+        tax_schedule: Option<TaxSchedule>,
+        trade_limits: Option<TradeLimits>,
     ) -> Result<()> {
         instructions::initialize(
             ctx,
@@ -243,6 +608,9 @@ pub mod goatswap {
             in_tax_rate,
             out_tax_rate,
             lp_fee_rate,
+            curve_params,
+            tax_schedule,
+            trade_limits,
         )
     }
 
@@ -254,6 +622,9 @@ pub mod goatswap {
     /// * `init_amount_0` - the initial amount_0 to deposit
     /// * `init_amount_1` - the initial amount_1 to deposit
     /// * `open_time` - the timestamp allowed for swap
+    /// * `curve_params` - the curve of the pool, constant product when not set
+    /// * `tax_schedule` - launch tax rates decaying to `in_tax_rate` and `out_tax_rate` from the open time
+    /// * `trade_limits` - max transaction and max wallet limits on the taxed token
     ///
     pub fn initialize_whitelisted(
         ctx: Context<InitializeWhitelisted>,
@@ -264,6 +635,9 @@ pub mod goatswap {
         in_tax_rate: u64,
         out_tax_rate: u64,
         lp_fee_rate: Option<u64>,
+        curve_params: Option<CurveParams>,
+        tax_schedule: Option<TaxSchedule>,
+        trade_limits: Option<TradeLimits>,
     ) -> Result<()> {
         instructions::initialize_whitelisted(
             ctx,
@@ -274,6 +648,9 @@ pub mod goatswap {
             in_tax_rate,
             out_tax_rate,
             lp_fee_rate,
+            curve_params,
+            tax_schedule,
+            trade_limits,
         )
     }
 
@@ -323,6 +700,17 @@ pub mod goatswap {
         )
     }
 
+    /// End a liquidity bootstrapping pool, burning all of the creator's lp tokens
+    /// for the remaining liquidity and closing the pool
+    ///
+    /// # Arguments
+    ///
+    /// * `ctx`- The context of accounts
+    ///
+    pub fn end_lbp(ctx: Context<EndLbp>) -> Result<()> {
+        instructions::end_lbp(ctx)
+    }
+
     /// Swap the tokens in the pool base input amount
     ///
     /// # Arguments
@@ -350,4 +738,255 @@ pub mod goatswap {
     pub fn swap_base_output(ctx: Context<Swap>, max_amount_in: u64, amount_out: u64) -> Result<()> {
         instructions::swap_base_output(ctx, max_amount_in, amount_out)
     }
+
+    /// Grow a pool created with an older layout of the pool state to the
+    /// current one, the added fields start zeroed. Anyone can call it, the
+    /// payer funds the rent of the added bytes
+    ///
+    /// # Arguments
+    ///
+    /// * `ctx`- The context of accounts
+    ///
+    pub fn migrate_pool(ctx: Context<MigratePool>) -> Result<()> {
+        instructions::migrate_pool(ctx)
+    }
+
+    /// Create the observation account of a standard pool created before
+    /// observations, which its swaps, deposits and withdrawals write to.
+    /// Anyone can call it, the payer funds the account
+    ///
+    /// # Arguments
+    ///
+    /// * `ctx`- The context of accounts
+    ///
+    pub fn initialize_observation(ctx: Context<InitializeObservation>) -> Result<()> {
+        instructions::initialize_observation(ctx)
+    }
+
+    /// Read the time weighted average prices of a pool over the last `window`
+    /// seconds, returned as Q32.32. CPI callers can also load the observation
+    /// account and call `ObservationState::twap` directly
+    ///
+    /// # Arguments
+    ///
+    /// * `ctx`- The context of accounts
+    /// * `window` - the number of seconds to average the prices over
+    ///
+    pub fn observe_twap(ctx: Context<ObserveTwap>, window: u64) -> Result<TwapPrice> {
+        instructions::observe_twap(ctx, window)
+    }
+
+    /// Creates a concentrated liquidity pool for the given token pair and the initial price
+    ///
+    /// # Arguments
+    ///
+    /// * `ctx`- The context of accounts
+    /// * `sqrt_price_x64` - the initial sqrt price (token_1/token_0) of the pool as a Q64.64
+    /// * `tick_spacing` - positions can only start and end at multiples of the tick spacing
+    /// * `open_time` - the timestamp allowed for swap
+    ///
+    pub fn initialize_clmm(
+        ctx: Context<InitializeClmm>,
+        sqrt_price_x64: u128,
+        tick_spacing: u16,
+        open_time: u64,
+        tax_use_token_0: bool,
+        in_tax_rate: u64,
+        out_tax_rate: u64,
+    ) -> Result<()> {
+        instructions::initialize_clmm(
+            ctx,
+            sqrt_price_x64,
+            tick_spacing,
+            open_time,
+            tax_use_token_0,
+            in_tax_rate,
+            out_tax_rate,
+        )
+    }
+
+    /// Initialize a tick array of a concentrated liquidity pool
+    ///
+    /// # Arguments
+    ///
+    /// * `ctx`- The context of accounts
+    /// * `start_tick_index` - the first tick of the array, a multiple of 60 * tick_spacing
+    ///
+    pub fn initialize_tick_array(
+        ctx: Context<InitializeTickArray>,
+        start_tick_index: i32,
+    ) -> Result<()> {
+        instructions::initialize_tick_array(ctx, start_tick_index)
+    }
+
+    /// Open a position in a concentrated liquidity pool and add liquidity to it
+    ///
+    /// # Arguments
+    ///
+    /// * `ctx`- The context of accounts
+    /// * `tick_lower_index` - The low boundary of the position
+    /// * `tick_upper_index` - The upper boundary of the position
+    /// * `liquidity` - The liquidity to add
+    /// * `amount_0_max` - The max amount of token_0 to spend, which serves as a slippage check
+    /// * `amount_1_max` - The max amount of token_1 to spend, which serves as a slippage check
+    ///
+    pub fn open_position(
+        ctx: Context<OpenPosition>,
+        tick_lower_index: i32,
+        tick_upper_index: i32,
+        liquidity: u128,
+        amount_0_max: u64,
+        amount_1_max: u64,
+    ) -> Result<()> {
+        instructions::open_position(
+            ctx,
+            tick_lower_index,
+            tick_upper_index,
+            liquidity,
+            amount_0_max,
+            amount_1_max,
+        )
+    }
+
+    /// Add liquidity to an existing position
+    ///
+    /// # Arguments
+    ///
+    /// * `ctx`- The context of accounts
+    /// * `liquidity` - The liquidity to add
+    /// * `amount_0_max` - The max amount of token_0 to spend, which serves as a slippage check
+    /// * `amount_1_max` - The max amount of token_1 to spend, which serves as a slippage check
+    ///
+    pub fn increase_liquidity(
+        ctx: Context<IncreaseLiquidity>,
+        liquidity: u128,
+        amount_0_max: u64,
+        amount_1_max: u64,
+    ) -> Result<()> {
+        instructions::increase_liquidity(ctx, liquidity, amount_0_max, amount_1_max)
+    }
+
+    /// Remove liquidity from a position, the fees owed to the position are paid out as well
+    ///
+    /// # Arguments
+    ///
+    /// * `ctx`- The context of accounts
+    /// * `liquidity` - The liquidity to remove, can be 0 to only collect fees
+    /// * `amount_0_min` - The minimum amount of token_0 to receive, prevents excessive slippage
+    /// * `amount_1_min` - The minimum amount of token_1 to receive, prevents excessive slippage
+    ///
+    pub fn decrease_liquidity(
+        ctx: Context<DecreaseLiquidity>,
+        liquidity: u128,
+        amount_0_min: u64,
+        amount_1_min: u64,
+    ) -> Result<()> {
+        instructions::decrease_liquidity(ctx, liquidity, amount_0_min, amount_1_min)
+    }
+
+    /// Close an empty position and reclaim its rent
+    ///
+    /// # Arguments
+    ///
+    /// * `ctx`- The context of accounts
+    ///
+    pub fn close_position(ctx: Context<ClosePosition>) -> Result<()> {
+        instructions::close_position(ctx)
+    }
+
+    /// Swap the tokens in a concentrated liquidity pool, the tick arrays to cross
+    /// are passed as remaining accounts
+    ///
+    /// # Arguments
+    ///
+    /// * `ctx`- The context of accounts
+    /// * `amount` - Input amount when `is_base_input`, otherwise the output amount
+    /// * `other_amount_threshold` - Minimum output when `is_base_input`, otherwise the maximum input
+    /// * `sqrt_price_limit_x64` - The price can not move past this limit, 0 for no limit
+    /// * `is_base_input` - Whether `amount` is the input or the output amount
+    ///
+    pub fn swap_clmm<'a, 'b, 'c: 'info, 'info>(
+        ctx: Context<'a, 'b, 'c, 'info, SwapClmm<'info>>,
+        amount: u64,
+        other_amount_threshold: u64,
+        sqrt_price_limit_x64: u128,
+        is_base_input: bool,
+    ) -> Result<()> {
+        instructions::swap_clmm(
+            ctx,
+            amount,
+            other_amount_threshold,
+            sqrt_price_limit_x64,
+            is_base_input,
+        )
+    }
+
+    /// Create a bonding curve selling a token against a quote token over virtual reserves,
+    /// the curve graduates into a pool once `graduation_quote_amount` has been raised
+    ///
+    /// # Arguments
+    ///
+    /// * `ctx`- The context of accounts
+    /// * `sale_token_amount` - the amount of token sold along the curve
+    /// * `pool_token_amount` - the amount of token set aside to seed the graduated pool
+    /// * `virtual_token_reserves` - the initial token reserve the price is computed from
+    /// * `virtual_quote_reserves` - the initial quote reserve the price is computed from
+    /// * `graduation_quote_amount` - the curve completes once this much quote has been raised
+    /// * `tax_use_token_0` - whether the tax is taken on token_0 of the graduated pool
+    /// * `in_tax_rate` - the tax rate on the input side, carried over to the pool
+    /// * `out_tax_rate` - the tax rate on the output side, carried over to the pool
+    /// * `burn_lp` - burn the lp tokens of the graduated pool instead of locking them
+    ///
+    pub fn create_bonding_curve(
+        ctx: Context<CreateBondingCurve>,
+        sale_token_amount: u64,
+        pool_token_amount: u64,
+        virtual_token_reserves: u64,
+        virtual_quote_reserves: u64,
+        graduation_quote_amount: u64,
+        tax_use_token_0: bool,
+        in_tax_rate: u64,
+        out_tax_rate: u64,
+        burn_lp: bool,
+    ) -> Result<()> {
+        instructions::create_bonding_curve(
+            ctx,
+            sale_token_amount,
+            pool_token_amount,
+            virtual_token_reserves,
+            virtual_quote_reserves,
+            graduation_quote_amount,
+            tax_use_token_0,
+            in_tax_rate,
+            out_tax_rate,
+            burn_lp,
+        )
+    }
+
+    /// Buy or sell along a bonding curve base input amount
+    ///
+    /// # Arguments
+    ///
+    /// * `ctx`- The context of accounts
+    /// * `amount_in` -  input amount to transfer, output to DESTINATION is based on the curve price
+    /// * `minimum_amount_out` -  Minimum amount of output token, prevents excessive slippage
+    ///
+    pub fn swap_bonding_curve(
+        ctx: Context<SwapBondingCurve>,
+        amount_in: u64,
+        minimum_amount_out: u64,
+    ) -> Result<()> {
+        instructions::swap_bonding_curve(ctx, amount_in, minimum_amount_out)
+    }
+
+    /// Seed a pool with the reserves of a completed bonding curve, the lp tokens
+    /// are locked or burnt. Can be called by anyone
+    ///
+    /// # Arguments
+    ///
+    /// * `ctx`- The context of accounts
+    ///
+    pub fn graduate_bonding_curve(ctx: Context<GraduateBondingCurve>) -> Result<()> {
+        instructions::graduate_bonding_curve(ctx)
+    }
 }
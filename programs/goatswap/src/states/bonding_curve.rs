@@ -0,0 +1,347 @@
+use crate::curve::ConstantProductCurve;
+use crate::error::ErrorCode;
+use crate::states::POOL_SEED;
+use anchor_lang::prelude::*;
+
+pub const BONDING_CURVE_SEED: &str = "bonding_curve";
+pub const BONDING_CURVE_VAULT_SEED: &str = "bonding_curve_vault";
+pub const BONDING_CURVE_LP_LOCK_SEED: &str = "bonding_curve_lp_lock";
+
+/// Pre-market sale of a token along a constant product curve over virtual
+/// reserves, graduating into a pool once enough quote has been raised
+#[account]
+#[derive(Default, Debug)]
+pub struct BondingCurveState {
+    /// Bump to identify PDA
+    pub bump: u8,
+    /// Set once the graduation threshold is reached, trading stops from then on
+    pub complete: bool,
+    /// Set once the pool has been created from the curve
+    pub graduated: bool,
+    /// Burn the lp tokens of the graduated pool instead of locking them
+    pub burn_lp: bool,
+    /// The creator of the sale, also the tax authority of the graduated pool
+    pub creator: Pubkey,
+    /// Which config the graduated pool belongs to
+    pub amm_config: Pubkey,
+    /// The token sold along the curve
+    pub token_mint: Pubkey,
+    /// The token paid for it
+    pub quote_mint: Pubkey,
+    pub token_vault: Pubkey,
+    pub quote_vault: Pubkey,
+
+    /// Reserves the price is computed from, real reserves plus a virtual offset
+    pub virtual_token_reserves: u64,
+    pub virtual_quote_reserves: u64,
+    /// Tokens left for sale
+    pub real_token_reserves: u64,
+    /// Quote raised so far
+    pub real_quote_reserves: u64,
+    /// Tokens set aside to seed the graduated pool with
+    pub pool_token_amount: u64,
+    /// The curve completes once this much quote has been raised
+    pub graduation_quote_amount: u64,
+
+    /// Tax, carried over to the graduated pool
+    pub tax_mint: Pubkey,
+    pub in_tax_rate: u64,
+    pub out_tax_rate: u64,
+    /// total amount of tax in the token and quote vaults
+    pub tax_amount_token: u64,
+    pub tax_amount_quote: u64,
+
+    /// The pool created at graduation
+    pub pool_id: Pubkey,
+
+    /// padding
+    pub padding: [u64; 16],
+}
+
+impl BondingCurveState {
+    pub const LEN: usize = 8 + 4 + 32 * 6 + 8 * 6 + 32 + 8 * 4 + 32 + 8 * 16;
+
+    /// Address of the bonding curve selling `token_mint` for `quote_mint`
+    pub fn address(amm_config: &Pubkey, token_mint: &Pubkey, quote_mint: &Pubkey) -> Pubkey {
+        Pubkey::find_program_address(
+            &[
+                BONDING_CURVE_SEED.as_bytes(),
+                amm_config.as_ref(),
+                token_mint.as_ref(),
+                quote_mint.as_ref(),
+            ],
+            &crate::id(),
+        )
+        .0
+    }
+
+    /// Check a pair has no bonding curve under `amm_config`, given the accounts
+    /// of the curves selling token_0 and selling token_1. The pool of a curve
+    /// is created by its graduation, the only way out of its vaults
+    pub fn check_pair_has_no_curve(
+        amm_config: &Pubkey,
+        token_0_mint: &Pubkey,
+        token_1_mint: &Pubkey,
+        bonding_curves: [&AccountInfo; 2],
+    ) -> Result<()> {
+        for (bonding_curve, (token_mint, quote_mint)) in bonding_curves
+            .into_iter()
+            .zip([(token_0_mint, token_1_mint), (token_1_mint, token_0_mint)])
+        {
+            require_keys_eq!(
+                bonding_curve.key(),
+                Self::address(amm_config, token_mint, quote_mint),
+                ErrorCode::InvalidInput
+            );
+            require!(
+                bonding_curve.data_is_empty(),
+                ErrorCode::PairHasBondingCurve
+            );
+        }
+        Ok(())
+    }
+
+    /// Check the pair of a new curve has no pool under `amm_config` yet, given
+    /// the account at the address of that pool, which graduation creates
+    pub fn check_pair_has_no_pool(
+        amm_config: &Pubkey,
+        token_mint: &Pubkey,
+        quote_mint: &Pubkey,
+        pool_state: &AccountInfo,
+    ) -> Result<()> {
+        let (token_0_mint, token_1_mint) = if token_mint < quote_mint {
+            (token_mint, quote_mint)
+        } else {
+            (quote_mint, token_mint)
+        };
+        let (pool_id, _) = Pubkey::find_program_address(
+            &[
+                POOL_SEED.as_bytes(),
+                amm_config.as_ref(),
+                token_0_mint.as_ref(),
+                token_1_mint.as_ref(),
+            ],
+            &crate::id(),
+        );
+        require_keys_eq!(pool_state.key(), pool_id, ErrorCode::InvalidInput);
+        require!(pool_state.data_is_empty(), ErrorCode::PairHasPool);
+        Ok(())
+    }
+
+    /// Amounts in and out for selling `amount_in` into the curve. When the
+    /// amount out is capped by the real reserves, the amount in is only what
+    /// buys that capped amount
+    pub fn swap_base_input(&self, amount_in: u64, is_buy: bool) -> Option<(u64, u64)> {
+        let (virtual_in, virtual_out, real_out) = if is_buy {
+            (
+                self.virtual_quote_reserves,
+                self.virtual_token_reserves,
+                self.real_token_reserves,
+            )
+        } else {
+            (
+                self.virtual_token_reserves,
+                self.virtual_quote_reserves,
+                self.real_quote_reserves,
+            )
+        };
+        let amount_out = ConstantProductCurve::swap_base_input_without_fees(
+            u128::from(amount_in),
+            u128::from(virtual_in),
+            u128::from(virtual_out),
+        );
+        let amount_out = u64::try_from(amount_out).ok()?;
+        if amount_out <= real_out {
+            return Some((amount_in, amount_out));
+        }
+        let amount_in = ConstantProductCurve::swap_base_output_without_fees(
+            u128::from(real_out),
+            u128::from(virtual_in),
+            u128::from(virtual_out),
+        );
+        Some((u64::try_from(amount_in).ok()?, real_out))
+    }
+
+    /// Moves the reserves by a trade, completing the curve at the threshold
+    pub fn apply_swap(&mut self, amount_in: u64, amount_out: u64, is_buy: bool) -> Option<()> {
+        if is_buy {
+            self.virtual_quote_reserves = self.virtual_quote_reserves.checked_add(amount_in)?;
+            self.real_quote_reserves = self.real_quote_reserves.checked_add(amount_in)?;
+            self.virtual_token_reserves = self.virtual_token_reserves.checked_sub(amount_out)?;
+            self.real_token_reserves = self.real_token_reserves.checked_sub(amount_out)?;
+        } else {
+            self.virtual_token_reserves = self.virtual_token_reserves.checked_add(amount_in)?;
+            self.real_token_reserves = self.real_token_reserves.checked_add(amount_in)?;
+            self.virtual_quote_reserves = self.virtual_quote_reserves.checked_sub(amount_out)?;
+            self.real_quote_reserves = self.real_quote_reserves.checked_sub(amount_out)?;
+        }
+        if self.real_quote_reserves >= self.graduation_quote_amount || self.real_token_reserves == 0
+        {
+            self.complete = true;
+        }
+        Some(())
+    }
+}
+
+#[cfg(test)]
+pub mod bonding_curve_test {
+    use super::*;
+    use crate::states::PoolState;
+
+    fn curve() -> BondingCurveState {
+        BondingCurveState {
+            virtual_token_reserves: 1_000_000_000,
+            virtual_quote_reserves: 30_000,
+            real_token_reserves: 800_000_000,
+            graduation_quote_amount: 85_000,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn buy_then_sell_round_trip() {
+        let mut state = curve();
+        let (amount_in, tokens) = state.swap_base_input(3_000, true).unwrap();
+        assert_eq!((amount_in, tokens), (3_000, 90_909_090));
+        state.apply_swap(3_000, tokens, true).unwrap();
+        assert_eq!(state.real_quote_reserves, 3_000);
+        assert!(!state.complete);
+
+        let (_, quote) = state.swap_base_input(tokens, false).unwrap();
+        // rounding keeps the dust in the curve
+        assert_eq!(quote, 2_999);
+        state.apply_swap(tokens, quote, false).unwrap();
+        assert_eq!(state.real_quote_reserves, 1);
+        assert_eq!(state.real_token_reserves, 800_000_000);
+    }
+
+    #[test]
+    fn completes_at_threshold() {
+        let mut state = curve();
+        let (_, tokens) = state.swap_base_input(85_000, true).unwrap();
+        state.apply_swap(85_000, tokens, true).unwrap();
+        assert!(state.complete);
+    }
+
+    #[test]
+    fn buy_is_capped_by_tokens_for_sale() {
+        let mut state = curve();
+        state.graduation_quote_amount = u64::MAX;
+        let (amount_in, tokens) = state.swap_base_input(1_000_000_000, true).unwrap();
+        assert_eq!(tokens, 800_000_000);
+        // the last buyer only pays for the tokens left
+        assert_eq!(amount_in, 120_000);
+        state.apply_swap(amount_in, tokens, true).unwrap();
+        assert!(state.complete);
+        assert_eq!(state.real_quote_reserves, 120_000);
+    }
+
+    /// Checks a pair against accounts at the curve addresses `keys`, holding
+    /// `data_lens` bytes
+    fn check_pair(
+        amm_config: &Pubkey,
+        token_0_mint: &Pubkey,
+        token_1_mint: &Pubkey,
+        keys: [Pubkey; 2],
+        data_lens: [usize; 2],
+    ) -> Result<()> {
+        let owner = crate::id();
+        let (mut lamports_0, mut lamports_1) = (0, 0);
+        let mut data_0 = vec![0u8; data_lens[0]];
+        let mut data_1 = vec![0u8; data_lens[1]];
+        let curve_0 = AccountInfo::new(
+            &keys[0],
+            false,
+            false,
+            &mut lamports_0,
+            &mut data_0,
+            &owner,
+            false,
+            0,
+        );
+        let curve_1 = AccountInfo::new(
+            &keys[1],
+            false,
+            false,
+            &mut lamports_1,
+            &mut data_1,
+            &owner,
+            false,
+            0,
+        );
+        BondingCurveState::check_pair_has_no_curve(
+            amm_config,
+            token_0_mint,
+            token_1_mint,
+            [&curve_0, &curve_1],
+        )
+    }
+
+    #[test]
+    fn pair_with_a_curve_is_left_to_graduation() {
+        let amm_config = Pubkey::new_unique();
+        let (token_0_mint, token_1_mint) = (Pubkey::new_unique(), Pubkey::new_unique());
+        let curves = [
+            BondingCurveState::address(&amm_config, &token_0_mint, &token_1_mint),
+            BondingCurveState::address(&amm_config, &token_1_mint, &token_0_mint),
+        ];
+        let check = |keys, data_lens| {
+            check_pair(&amm_config, &token_0_mint, &token_1_mint, keys, data_lens)
+        };
+
+        assert!(check(curves, [0, 0]).is_ok());
+        // a curve selling either token blocks creating the pool
+        assert!(check(curves, [BondingCurveState::LEN, 0]).is_err());
+        assert!(check(curves, [0, BondingCurveState::LEN]).is_err());
+        // the empty accounts must be the curves of the pair
+        let other_config_curve =
+            BondingCurveState::address(&Pubkey::new_unique(), &token_0_mint, &token_1_mint);
+        assert!(check([other_config_curve, curves[1]], [0, 0]).is_err());
+        assert!(check([curves[1], curves[0]], [0, 0]).is_err());
+    }
+
+    #[test]
+    fn curve_needs_a_pair_without_pool() {
+        let amm_config = Pubkey::new_unique();
+        let (token_mint, quote_mint) = (Pubkey::new_unique(), Pubkey::new_unique());
+        let (token_0_mint, token_1_mint) = if token_mint < quote_mint {
+            (token_mint, quote_mint)
+        } else {
+            (quote_mint, token_mint)
+        };
+        let (pool_id, _) = Pubkey::find_program_address(
+            &[
+                POOL_SEED.as_bytes(),
+                amm_config.as_ref(),
+                token_0_mint.as_ref(),
+                token_1_mint.as_ref(),
+            ],
+            &crate::id(),
+        );
+        let check = |key: Pubkey, data_len: usize| {
+            let owner = crate::id();
+            let mut lamports = 0;
+            let mut data = vec![0u8; data_len];
+            let pool_state = AccountInfo::new(
+                &key,
+                false,
+                false,
+                &mut lamports,
+                &mut data,
+                &owner,
+                false,
+                0,
+            );
+            BondingCurveState::check_pair_has_no_pool(
+                &amm_config,
+                &token_mint,
+                &quote_mint,
+                &pool_state,
+            )
+        };
+
+        assert!(check(pool_id, 0).is_ok());
+        assert!(check(pool_id, PoolState::LEN).is_err());
+        assert!(check(Pubkey::new_unique(), 0).is_err());
+    }
+}
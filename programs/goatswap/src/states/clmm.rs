@@ -0,0 +1,67 @@
+use crate::curve::clmm::*;
+use crate::error::ErrorCode;
+use anchor_lang::prelude::*;
+
+pub const CLMM_STATE_SEED: &str = "clmm_state";
+
+/// Price and liquidity of a concentrated liquidity pool. The `PoolState` of the
+/// same pool still holds the vaults, fee counters and tax configuration.
+#[account(zero_copy(unsafe))]
+#[repr(C, packed)]
+#[derive(Default, Debug)]
+pub struct ClmmState {
+    /// Bump to identify PDA
+    pub bump: u8,
+    /// The pool this state belongs to
+    pub pool_id: Pubkey,
+    /// Ticks that can hold liquidity are multiples of the tick spacing
+    pub tick_spacing: u16,
+    /// The currently in range liquidity available to the pool
+    pub liquidity: u128,
+    /// The current price of the pool as a sqrt(token_1/token_0) Q64.64 value
+    pub sqrt_price_x64: u128,
+    /// The current tick of the pool, i.e. according to the last tick transition that was run
+    pub tick_current: i32,
+    /// The fee growth of each token as a Q64.64 number, i.e. fees of token collected per
+    /// unit of liquidity for the entire life of the pool
+    pub fee_growth_global_0_x64: u128,
+    pub fee_growth_global_1_x64: u128,
+
+    pub padding: [u64; 16],
+}
+
+impl ClmmState {
+    pub const LEN: usize = 8 + 1 + 32 + 2 + 16 * 2 + 4 + 16 * 2 + 8 * 16;
+
+    pub fn initialize(
+        &mut self,
+        bump: u8,
+        pool_id: Pubkey,
+        tick_spacing: u16,
+        sqrt_price_x64: u128,
+    ) -> Result<()> {
+        self.bump = bump;
+        self.pool_id = pool_id;
+        self.tick_spacing = tick_spacing;
+        self.liquidity = 0;
+        self.sqrt_price_x64 = sqrt_price_x64;
+        self.tick_current = get_tick_at_sqrt_price(sqrt_price_x64)?;
+        self.fee_growth_global_0_x64 = 0;
+        self.fee_growth_global_1_x64 = 0;
+        Ok(())
+    }
+
+    /// Check that a position range is aligned to the tick spacing and within bounds
+    pub fn check_ticks(&self, tick_lower_index: i32, tick_upper_index: i32) -> Result<()> {
+        let tick_spacing = i32::from(self.tick_spacing);
+        require!(
+            tick_lower_index < tick_upper_index
+                && tick_lower_index >= MIN_TICK
+                && tick_upper_index <= MAX_TICK
+                && tick_lower_index % tick_spacing == 0
+                && tick_upper_index % tick_spacing == 0,
+            ErrorCode::InvalidTickIndex
+        );
+        Ok(())
+    }
+}
@@ -1,6 +1,19 @@
+use crate::curve::FEE_RATE_DENOMINATOR_VALUE;
+use crate::error::ErrorCode;
+use crate::states::{FeeSchedule, FUND_FEE_BUCKET, MAX_SCHEDULED_FEE_BUCKETS, PROTOCOL_FEE_BUCKET};
 use anchor_lang::prelude::*;
 
 pub const AMM_CONFIG_SEED: &str = "amm_config";
+pub const MAX_FEE_DISCOUNT_TIERS: usize = 3;
+
+/// A holding threshold of the fee discount mint and the discount it earns
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Default, Debug, PartialEq)]
+pub struct FeeDiscountTier {
+    /// Minimum amount held and staked for the tier
+    pub threshold: u64,
+    /// Discount on the trade fee, denominated in hundredths of a bip (10^-6) of the trade fee
+    pub discount_rate: u64,
+}
 
 /// Holds the current owner of the factory
 #[account]
@@ -24,11 +37,215 @@ pub struct AmmConfig {
     pub protocol_owner: Pubkey,
     /// Address of the fund fee owner
     pub fund_owner: Pubkey,
+    /// Max tax rate of the pools, denominated in hundredths of a bip (10^-6). No cap when 0
+    pub max_tax_rate: u64,
+    /// Delay in seconds before a tax increase of a pool applies
+    pub tax_increase_delay: u64,
+    /// The referrer's share of the trade fee when a swap has a referrer, denominated in hundredths of a bip (10^-6)
+    pub referral_fee_rate: u64,
+    /// The mint whose holders and stakers get a discount on the trade fee
+    pub fee_discount_mint: Pubkey,
+    /// Fee discount tiers, unused tiers have a zero discount rate
+    pub fee_discount_tiers: [FeeDiscountTier; MAX_FEE_DISCOUNT_TIERS],
+    /// Total fee rate of the fee schedule buckets, a swap needs the fee schedule when set
+    pub fee_schedule_rate: u64,
+    /// Max share of the trade fee a pool creator can take, denominated in hundredths of a bip (10^-6)
+    pub max_creator_fee_rate: u64,
 
     /// padding
-    pub padding: [u64; 16],
+    pub padding: [u64; 1],
 }
 
 impl AmmConfig {
-    pub const LEN: usize = 8 + 1 + 1 + 2 + 4 * 8 + 32 * 2 + 8 * 16;
+    pub const LEN: usize =
+        8 + 1 + 1 + 2 + 4 * 8 + 32 * 2 + 8 * 3 + 32 + 16 * MAX_FEE_DISCOUNT_TIERS + 8 * 2 + 8;
+
+    /// Check a tax rate of a pool against the max tax rate
+    pub fn check_tax_rate(&self, tax_rate: u64) -> Result<()> {
+        require!(
+            self.max_tax_rate == 0 || tax_rate <= self.max_tax_rate,
+            ErrorCode::ExceededMaxTaxRate
+        );
+        Ok(())
+    }
+
+    /// Check the deposit and withdraw tax rates of a pool against the max tax
+    /// rate. Neither can take a whole leg, a full withdraw tax would keep the
+    /// tax mint leg of lps in the pool
+    pub fn check_lp_tax_rates(&self, deposit_tax_rate: u64, withdraw_tax_rate: u64) -> Result<()> {
+        for tax_rate in [deposit_tax_rate, withdraw_tax_rate] {
+            require_gt!(
+                FEE_RATE_DENOMINATOR_VALUE,
+                tax_rate,
+                ErrorCode::InvalidInput
+            );
+            self.check_tax_rate(tax_rate)?;
+        }
+        Ok(())
+    }
+
+    /// Owner of a fee bucket, the protocol and fund buckets come first and the
+    /// buckets of the fee schedule after them
+    pub fn fee_bucket_owner(
+        &self,
+        bucket: u8,
+        fee_schedule: Option<&FeeSchedule>,
+    ) -> Option<Pubkey> {
+        match bucket {
+            PROTOCOL_FEE_BUCKET => Some(self.protocol_owner),
+            FUND_FEE_BUCKET => Some(self.fund_owner),
+            _ => fee_schedule?
+                .buckets()
+                .get(usize::from(bucket - 2))
+                .map(|fee_bucket| fee_bucket.owner),
+        }
+    }
+
+    /// Sum of the shares of the trade fee that do not go to lps, at their max
+    pub fn fee_share_rate(&self) -> u64 {
+        self.protocol_fee_rate
+            + self.fund_fee_rate
+            + self.referral_fee_rate
+            + self.fee_schedule_rate
+            + self.max_creator_fee_rate
+    }
+
+    /// Referral fee rate of a swap by `payer` referred by the owner of
+    /// `referrer`, 0 without a referrer. A payer can not refer itself
+    pub fn referral_fee_rate(&self, payer: Pubkey, referrer: Option<Pubkey>) -> Result<u64> {
+        match referrer {
+            Some(referrer) => {
+                require_keys_neq!(referrer, payer, ErrorCode::InvalidReferrer);
+                Ok(self.referral_fee_rate)
+            }
+            None => Ok(0),
+        }
+    }
+
+    /// Fees of the fee schedule buckets out of a trade fee
+    pub fn scheduled_fees(
+        &self,
+        fee_schedule: Option<&FeeSchedule>,
+        trade_fee: u128,
+    ) -> Result<[u64; MAX_SCHEDULED_FEE_BUCKETS]> {
+        match fee_schedule {
+            Some(fee_schedule) => Ok(fee_schedule.bucket_fees(trade_fee)),
+            None => {
+                require_eq!(self.fee_schedule_rate, 0, ErrorCode::MissingFeeSchedule);
+                Ok([0; MAX_SCHEDULED_FEE_BUCKETS])
+            }
+        }
+    }
+
+    /// Discount on the trade fee for a wallet holding and staking `balance`
+    /// of the fee discount mint, the best tier it reaches applies
+    pub fn fee_discount_rate(&self, balance: u64) -> u64 {
+        self.fee_discount_tiers
+            .iter()
+            .filter(|tier| balance >= tier.threshold)
+            .map(|tier| tier.discount_rate)
+            .max()
+            .unwrap_or(0)
+    }
+}
+
+#[cfg(test)]
+mod tax_rate_test {
+    use super::*;
+
+    #[test]
+    fn lp_tax_rates_are_capped_by_the_max_tax_rate() {
+        let amm_config = AmmConfig {
+            max_tax_rate: 100_000,
+            ..Default::default()
+        };
+        assert!(amm_config.check_lp_tax_rates(100_000, 0).is_ok());
+        assert!(amm_config.check_lp_tax_rates(0, 100_000).is_ok());
+        assert!(amm_config.check_lp_tax_rates(100_001, 0).is_err());
+        assert!(amm_config.check_lp_tax_rates(0, 100_001).is_err());
+        assert!(amm_config
+            .check_lp_tax_rates(0, FEE_RATE_DENOMINATOR_VALUE)
+            .is_err());
+    }
+
+    #[test]
+    fn lp_tax_rates_never_take_a_whole_leg() {
+        let amm_config = AmmConfig::default();
+        assert!(amm_config
+            .check_lp_tax_rates(
+                FEE_RATE_DENOMINATOR_VALUE - 1,
+                FEE_RATE_DENOMINATOR_VALUE - 1
+            )
+            .is_ok());
+        assert!(amm_config
+            .check_lp_tax_rates(FEE_RATE_DENOMINATOR_VALUE, 0)
+            .is_err());
+        assert!(amm_config
+            .check_lp_tax_rates(0, FEE_RATE_DENOMINATOR_VALUE)
+            .is_err());
+    }
+}
+
+#[cfg(test)]
+mod referral_test {
+    use super::*;
+    use crate::curve::{ConstantProductCurve, CurveCalculator, Fees, TradeDirection};
+
+    fn amm_config() -> AmmConfig {
+        AmmConfig {
+            trade_fee_rate: 10_000,
+            protocol_fee_rate: 120_000,
+            fund_fee_rate: 40_000,
+            referral_fee_rate: 100_000,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn no_referrer_takes_no_referral_fee() {
+        assert_eq!(
+            amm_config()
+                .referral_fee_rate(Pubkey::new_unique(), None)
+                .unwrap(),
+            0
+        );
+    }
+
+    #[test]
+    fn payer_can_not_refer_itself() {
+        let payer = Pubkey::new_unique();
+        assert!(amm_config().referral_fee_rate(payer, Some(payer)).is_err());
+    }
+
+    #[test]
+    fn referrer_takes_its_share_out_of_the_pool_share() {
+        let amm_config = amm_config();
+        let referral_fee_rate = amm_config
+            .referral_fee_rate(Pubkey::new_unique(), Some(Pubkey::new_unique()))
+            .unwrap();
+        let result = CurveCalculator::swap_base_input(
+            &ConstantProductCurve,
+            100_000,
+            10_000_000,
+            10_000_000,
+            amm_config.trade_fee_rate,
+            amm_config.protocol_fee_rate,
+            amm_config.fund_fee_rate,
+            0,
+            0,
+            TradeDirection::ZeroForOne,
+        )
+        .unwrap();
+        let referral_fee = Fees::referral_fee(result.trade_fee, referral_fee_rate).unwrap();
+        assert_eq!(
+            (result.trade_fee, result.protocol_fee, result.fund_fee),
+            (1_000, 120, 40)
+        );
+        assert_eq!(referral_fee, 100);
+        // the pool keeps the rest of the trade fee
+        assert_eq!(
+            result.trade_fee - result.protocol_fee - result.fund_fee - referral_fee,
+            740
+        );
+    }
 }
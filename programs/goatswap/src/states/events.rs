@@ -1,3 +1,4 @@
+use super::TaxRecipient;
 use anchor_lang::prelude::*;
 
 /// Emitted when deposit and withdraw
@@ -12,6 +13,8 @@ pub struct LpChangeEvent {
     pub reserve_1: u64,
     // 0: create, 1: add, 2: burn
     pub change_type: u8,
+    /// Tax on the tax mint leg, paid on top of a deposit or kept from a withdrawal
+    pub tax_amount: u64,
 }
 
 /// Emitted when swap
@@ -25,6 +28,8 @@ pub struct SwapEvent {
     pub amount_out: u64,
     pub reserve_0: u64,
     pub reserve_1: u64,
+    /// Share of the trade fee paid to the referrer, in the input token
+    pub referral_fee: u64,
 }
 
 /// Emitted when init pool, update tax
@@ -35,13 +40,185 @@ pub struct TaxConfigUpdatedEvent {
     pub tax_authority: Pubkey,
     pub in_tax_rate: u64,
     pub out_tax_rate: u64,
+    pub quote_in_tax_rate: u64,
+    pub quote_out_tax_rate: u64,
     pub tax_disabled: bool,
 }
 
+/// Emitted when a tax increase is queued behind the timelock
+#[event]
+pub struct TaxChangeQueuedEvent {
+    #[index]
+    pub pool_id: Pubkey,
+    pub tax_mint: Pubkey,
+    pub in_tax_rate: u64,
+    pub out_tax_rate: u64,
+    pub quote_in_tax_rate: u64,
+    pub quote_out_tax_rate: u64,
+    pub apply_time: u64,
+}
+
+/// Emitted when a queued tax increase applies
+#[event]
+pub struct TaxChangeAppliedEvent {
+    #[index]
+    pub pool_id: Pubkey,
+    pub tax_mint: Pubkey,
+    pub in_tax_rate: u64,
+    pub out_tax_rate: u64,
+    pub quote_in_tax_rate: u64,
+    pub quote_out_tax_rate: u64,
+}
+
+/// Emitted when a queued tax increase is cancelled or replaced
+#[event]
+pub struct TaxChangeCancelledEvent {
+    #[index]
+    pub pool_id: Pubkey,
+}
+
+/// Emitted when init pool with trade limits, update trade limits
+#[event]
+pub struct TradeLimitsUpdatedEvent {
+    pub pool_id: Pubkey,
+    pub max_tx_amount: u64,
+    pub max_tx_rate: u64,
+    pub max_wallet_amount: u64,
+    pub end_time: u64,
+}
+
+/// Emitted when the tax authority queues trade limits that tighten or extend
+/// the current ones behind the timelock
+#[event]
+pub struct TradeLimitsQueuedEvent {
+    #[index]
+    pub pool_id: Pubkey,
+    pub max_tx_amount: u64,
+    pub max_tx_rate: u64,
+    pub max_wallet_amount: u64,
+    pub end_time: u64,
+    pub apply_time: u64,
+}
+
+/// Emitted when the auto-liquidity share of the tax is updated
+#[event]
+pub struct TaxLiquidityRateUpdatedEvent {
+    #[index]
+    pub pool_id: Pubkey,
+    pub tax_liquidity_rate: u64,
+}
+
+/// Emitted when swap leaves the auto-liquidity share of its tax in the reserves
+#[event]
+pub struct TaxLiquidityAddedEvent {
+    #[index]
+    pub pool_id: Pubkey,
+    pub tax_mint: Pubkey,
+    pub amount: u64,
+}
+
+/// Emitted when a wallet is added to or removed from the tax exemptions of a pool
+#[event]
+pub struct TaxExemptionUpdatedEvent {
+    #[index]
+    pub pool_id: Pubkey,
+    pub wallet: Pubkey,
+    pub exempt: bool,
+}
+
 /// Emitted when collect tax
 #[event]
 pub struct TaxCollectEvent {
     pub pool_id: Pubkey,
     pub amount_0: u64,
     pub amount_1: u64,
+    /// Rounding dust of splitting the tax across recipients, paid to the first recipient
+    pub dust_0: u64,
+    pub dust_1: u64,
+    /// Part of the tax of the tax mint burned instead of paid out
+    pub burned_amount: u64,
+}
+
+/// Emitted when the deposit and withdraw tax rates are updated
+#[event]
+pub struct LpTaxUpdatedEvent {
+    #[index]
+    pub pool_id: Pubkey,
+    pub deposit_tax_rate: u64,
+    pub withdraw_tax_rate: u64,
+}
+
+/// Emitted when the tax authority queues an lp tax increase behind the timelock
+#[event]
+pub struct LpTaxChangeQueuedEvent {
+    #[index]
+    pub pool_id: Pubkey,
+    pub deposit_tax_rate: u64,
+    pub withdraw_tax_rate: u64,
+    pub apply_time: u64,
+}
+
+/// Emitted when the tax is switched between burning and paying out
+#[event]
+pub struct TaxBurnUpdatedEvent {
+    #[index]
+    pub pool_id: Pubkey,
+    pub tax_burn: bool,
+}
+
+/// Emitted when the tax authority delegates tax collection
+#[event]
+pub struct TaxRecipientDelegatedEvent {
+    #[index]
+    pub pool_id: Pubkey,
+    pub tax_recipient: Pubkey,
+}
+
+/// Emitted when the tax recipients of a pool are updated
+#[event]
+pub struct TaxRecipientsUpdatedEvent {
+    #[index]
+    pub pool_id: Pubkey,
+    pub recipients: Vec<TaxRecipient>,
+}
+
+/// Emitted when liquidity of a concentrated liquidity position changes
+#[event]
+pub struct PositionChangeEvent {
+    #[index]
+    pub pool_id: Pubkey,
+    pub position: Pubkey,
+    pub tick_lower_index: i32,
+    pub tick_upper_index: i32,
+    pub liquidity: u128,
+    pub amount_0: u64,
+    pub amount_1: u64,
+    pub fees_0: u64,
+    pub fees_1: u64,
+    // 0: open, 1: increase, 2: decrease
+    pub change_type: u8,
+}
+
+/// Emitted when a bonding curve graduates into a pool
+#[event]
+pub struct BondingCurveGraduatedEvent {
+    #[index]
+    pub bonding_curve: Pubkey,
+    pub pool_id: Pubkey,
+    pub token_amount: u64,
+    pub quote_amount: u64,
+    pub amount_lp: u64,
+    pub lp_burnt: bool,
+}
+
+/// Emitted when stake and unstake the fee discount mint
+#[event]
+pub struct StakeChangeEvent {
+    #[index]
+    pub owner: Pubkey,
+    pub mint: Pubkey,
+    pub amount: u64,
+    pub staked_amount: u64,
+    // 0: stake, 1: unstake
+    pub change_type: u8,
 }
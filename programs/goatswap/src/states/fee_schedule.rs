@@ -0,0 +1,138 @@
+use crate::curve::fees::{floor_div, FEE_RATE_DENOMINATOR_VALUE};
+use anchor_lang::prelude::*;
+
+pub const FEE_SCHEDULE_SEED: &str = "fee_schedule";
+/// The protocol bucket, owned by `protocol_owner` of the amm config
+pub const PROTOCOL_FEE_BUCKET: u8 = 0;
+/// The fund bucket, owned by `fund_owner` of the amm config
+pub const FUND_FEE_BUCKET: u8 = 1;
+/// The creator bucket of a pool, owned by its `pool_creator`
+pub const CREATOR_FEE_BUCKET: u8 = u8::MAX;
+/// Buckets a fee schedule can add after the protocol and fund buckets
+pub const MAX_SCHEDULED_FEE_BUCKETS: usize = 6;
+
+/// A share of the trade fee and the owner collecting it
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Default, Debug, PartialEq)]
+pub struct FeeBucket {
+    pub owner: Pubkey,
+    /// Share of the trade fee, denominated in hundredths of a bip (10^-6)
+    pub fee_rate: u64,
+}
+
+/// Fee buckets of an amm config beyond its protocol and fund buckets. Bucket
+/// `i` of the schedule is collected as bucket `i + 2`, and like the protocol
+/// and fund fees it is taken out of the lp share of the trade fee
+#[account]
+#[derive(Default, Debug)]
+pub struct FeeSchedule {
+    /// Bump to identify PDA
+    pub bump: u8,
+    /// The amm config the buckets belong to
+    pub amm_config: Pubkey,
+    /// Number of buckets in use
+    pub count: u8,
+    pub buckets: [FeeBucket; MAX_SCHEDULED_FEE_BUCKETS],
+
+    /// padding
+    pub padding: [u64; 4],
+}
+
+impl FeeSchedule {
+    pub const LEN: usize = 8 + 1 + 32 + 1 + 40 * MAX_SCHEDULED_FEE_BUCKETS + 8 * 4;
+
+    /// Replace the buckets
+    pub fn set(&mut self, buckets: &[FeeBucket]) {
+        self.count = buckets.len() as u8;
+        self.buckets = [FeeBucket::default(); MAX_SCHEDULED_FEE_BUCKETS];
+        self.buckets[..buckets.len()].copy_from_slice(buckets);
+    }
+
+    pub fn buckets(&self) -> &[FeeBucket] {
+        &self.buckets[..self.count as usize]
+    }
+
+    /// Whether `buckets` keeps every bucket in use at its index and with its
+    /// owner. Pools accrue the fees of a bucket by its index, so a bucket can
+    /// not be removed or reassigned while pools may still owe it fees, only
+    /// its rate can change, down to 0 to stop its accrual
+    pub fn keeps_buckets(&self, buckets: &[FeeBucket]) -> bool {
+        buckets.len() >= self.buckets().len()
+            && self
+                .buckets()
+                .iter()
+                .zip(buckets)
+                .all(|(current, new)| current.owner == new.owner)
+    }
+
+    /// Sum of the fee rates of the buckets
+    pub fn total_fee_rate(buckets: &[FeeBucket]) -> Option<u64> {
+        buckets
+            .iter()
+            .try_fold(0u64, |total, bucket| total.checked_add(bucket.fee_rate))
+    }
+
+    /// Fees of each bucket out of a trade fee
+    pub fn bucket_fees(&self, trade_fee: u128) -> [u64; MAX_SCHEDULED_FEE_BUCKETS] {
+        let mut fees = [0u64; MAX_SCHEDULED_FEE_BUCKETS];
+        for (fee, bucket) in fees.iter_mut().zip(self.buckets()) {
+            *fee = floor_div(
+                trade_fee,
+                u128::from(bucket.fee_rate),
+                u128::from(FEE_RATE_DENOMINATOR_VALUE),
+            )
+            .and_then(|fee| u64::try_from(fee).ok())
+            .unwrap();
+        }
+        fees
+    }
+}
+
+#[cfg(test)]
+pub mod fee_schedule_test {
+    use super::*;
+
+    #[test]
+    fn bucket_fees_test() {
+        let mut fee_schedule = FeeSchedule::default();
+        fee_schedule.set(&[
+            FeeBucket {
+                owner: Pubkey::new_unique(),
+                fee_rate: 100_000,
+            },
+            FeeBucket {
+                owner: Pubkey::new_unique(),
+                fee_rate: 50_000,
+            },
+        ]);
+
+        assert_eq!(
+            FeeSchedule::total_fee_rate(fee_schedule.buckets()),
+            Some(150_000)
+        );
+        assert_eq!(fee_schedule.bucket_fees(1_999), [199, 99, 0, 0, 0, 0]);
+    }
+
+    #[test]
+    fn buckets_in_use_keep_their_index_and_owner() {
+        let (owner_0, owner_1) = (Pubkey::new_unique(), Pubkey::new_unique());
+        let bucket = |owner, fee_rate| FeeBucket { owner, fee_rate };
+        let mut fee_schedule = FeeSchedule::default();
+        assert!(fee_schedule.keeps_buckets(&[]));
+        fee_schedule.set(&[bucket(owner_0, 100_000), bucket(owner_1, 50_000)]);
+
+        // rates can change and buckets can be added
+        assert!(fee_schedule.keeps_buckets(&[bucket(owner_0, 0), bucket(owner_1, 80_000)]));
+        assert!(fee_schedule.keeps_buckets(&[
+            bucket(owner_0, 100_000),
+            bucket(owner_1, 50_000),
+            bucket(Pubkey::new_unique(), 10_000),
+        ]));
+        // shrinking would strand the fees pools owe, reordering pay them to another owner
+        assert!(!fee_schedule.keeps_buckets(&[bucket(owner_0, 100_000)]));
+        assert!(!fee_schedule.keeps_buckets(&[bucket(owner_1, 50_000), bucket(owner_0, 100_000)]));
+        assert!(!fee_schedule.keeps_buckets(&[
+            bucket(owner_0, 100_000),
+            bucket(Pubkey::new_unique(), 50_000),
+        ]));
+    }
+}
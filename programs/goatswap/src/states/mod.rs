@@ -1,8 +1,26 @@
+pub mod bonding_curve;
+pub mod clmm;
 pub mod config;
+pub mod fee_schedule;
+pub mod oracle;
 pub mod pool;
+pub mod position;
+pub mod stake_record;
+pub mod tax_exemption;
+pub mod tax_recipients;
+pub mod tick_array;
 
+pub use bonding_curve::*;
+pub use clmm::*;
 pub use config::*;
+pub use fee_schedule::*;
+pub use oracle::*;
 pub use pool::*;
+pub use position::*;
+pub use stake_record::*;
+pub use tax_exemption::*;
+pub use tax_recipients::*;
+pub use tick_array::*;
 
 pub mod events;
 pub use events::*;
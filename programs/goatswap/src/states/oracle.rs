@@ -0,0 +1,282 @@
+//! Oracle provides price data useful for a wide variety of system designs
+use crate::utils::U256;
+use anchor_lang::prelude::*;
+
+/// Seed to derive account address and signature
+pub const OBSERVATION_SEED: &str = "observation";
+/// Number of observations kept in the ring buffer
+pub const OBSERVATION_NUM: usize = 100;
+/// Minimum interval between two observations in the ring buffer, in seconds
+pub const OBSERVATION_UPDATE_DURATION_DEFAULT: u64 = 15;
+
+/// A snapshot of the price accumulators
+#[zero_copy(unsafe)]
+#[repr(C, packed)]
+#[derive(Default, Debug)]
+pub struct Observation {
+    /// The block timestamp of the observation
+    pub block_timestamp: u64,
+    /// The cumulative of token_0 price in token_1 as Q32.32, over seconds
+    pub cumulative_token_0_price_x32: u128,
+    /// The cumulative of token_1 price in token_0 as Q32.32, over seconds
+    pub cumulative_token_1_price_x32: u128,
+}
+
+impl Observation {
+    pub const LEN: usize = 8 + 16 + 16;
+}
+
+/// Time weighted average prices as Q32.32
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, Default, PartialEq)]
+pub struct TwapPrice {
+    /// Price of token_0 in token_1
+    pub token_0_price_x32: u128,
+    /// Price of token_1 in token_0
+    pub token_1_price_x32: u128,
+}
+
+#[account(zero_copy(unsafe))]
+#[repr(C, packed)]
+#[derive(Debug)]
+pub struct ObservationState {
+    /// Whether the ObservationState is initialized
+    pub initialized: bool,
+    /// The most recently written index of the ring buffer
+    pub observation_index: u16,
+    pub pool_id: Pubkey,
+    /// The accumulators, up to date as of their block timestamp
+    pub latest: Observation,
+    /// The spot prices left by the last write, which have held since then
+    pub token_0_price_x32: u128,
+    pub token_1_price_x32: u128,
+    /// Ring buffer of accumulator snapshots, at least
+    /// OBSERVATION_UPDATE_DURATION_DEFAULT seconds apart
+    pub observations: [Observation; OBSERVATION_NUM],
+    /// padding for feature update
+    pub padding: [u64; 4],
+}
+
+impl Default for ObservationState {
+    #[inline]
+    fn default() -> ObservationState {
+        ObservationState {
+            initialized: false,
+            observation_index: 0,
+            pool_id: Pubkey::default(),
+            latest: Observation::default(),
+            token_0_price_x32: 0,
+            token_1_price_x32: 0,
+            observations: [Observation::default(); OBSERVATION_NUM],
+            padding: [0u64; 4],
+        }
+    }
+}
+
+impl ObservationState {
+    pub const LEN: usize =
+        8 + 1 + 2 + 32 + Observation::LEN + 16 * 2 + Observation::LEN * OBSERVATION_NUM + 8 * 4;
+
+    /// Start the accumulators at the pool creation price
+    pub fn initialize(
+        &mut self,
+        pool_id: Pubkey,
+        block_timestamp: u64,
+        token_0_price_x32: u128,
+        token_1_price_x32: u128,
+    ) {
+        self.initialized = true;
+        self.observation_index = 0;
+        self.pool_id = pool_id;
+        self.latest = Observation {
+            block_timestamp,
+            cumulative_token_0_price_x32: 0,
+            cumulative_token_1_price_x32: 0,
+        };
+        self.observations[0] = self.latest;
+        self.token_0_price_x32 = token_0_price_x32;
+        self.token_1_price_x32 = token_1_price_x32;
+    }
+
+    /// Accumulate the price held since the last write, then record the new
+    /// spot price left by the current instruction
+    ///
+    /// # Arguments
+    ///
+    /// * `block_timestamp` - the current timestamp
+    /// * `token_0_price_x32` - the price of token_0 in token_1 after the instruction
+    /// * `token_1_price_x32` - the price of token_1 in token_0 after the instruction
+    ///
+    pub fn update(
+        &mut self,
+        block_timestamp: u64,
+        token_0_price_x32: u128,
+        token_1_price_x32: u128,
+    ) {
+        self.latest = self.accumulate(block_timestamp);
+
+        let index = usize::from(self.observation_index);
+        let last_timestamp = self.observations[index].block_timestamp;
+        if block_timestamp >= last_timestamp + OBSERVATION_UPDATE_DURATION_DEFAULT {
+            let next_index = (index + 1) % OBSERVATION_NUM;
+            self.observations[next_index] = self.latest;
+            self.observation_index = next_index as u16;
+        }
+
+        self.token_0_price_x32 = token_0_price_x32;
+        self.token_1_price_x32 = token_1_price_x32;
+    }
+
+    /// The accumulators extrapolated to a timestamp not before the latest write
+    fn accumulate(&self, block_timestamp: u64) -> Observation {
+        let latest = self.latest;
+        let delta_time = u128::from(block_timestamp.saturating_sub(latest.block_timestamp));
+        let token_0_price_x32 = self.token_0_price_x32;
+        let token_1_price_x32 = self.token_1_price_x32;
+        Observation {
+            block_timestamp: std::cmp::max(block_timestamp, latest.block_timestamp),
+            cumulative_token_0_price_x32: latest
+                .cumulative_token_0_price_x32
+                .wrapping_add(token_0_price_x32.wrapping_mul(delta_time)),
+            cumulative_token_1_price_x32: latest
+                .cumulative_token_1_price_x32
+                .wrapping_add(token_1_price_x32.wrapping_mul(delta_time)),
+        }
+    }
+
+    /// The accumulators at `target`, interpolated between the two snapshots
+    /// around it. None if the ring buffer does not reach back that far
+    pub fn observe(&self, now: u64, target: u64) -> Option<Observation> {
+        if target > now {
+            return None;
+        }
+        let latest = self.latest;
+        if target >= latest.block_timestamp {
+            return Some(self.accumulate(target));
+        }
+
+        let mut after = latest;
+        for i in 0..OBSERVATION_NUM {
+            let index =
+                (usize::from(self.observation_index) + OBSERVATION_NUM - i) % OBSERVATION_NUM;
+            let before = self.observations[index];
+            if before.block_timestamp == 0 {
+                return None;
+            }
+            if before.block_timestamp <= target {
+                return Some(interpolate(&before, &after, target));
+            }
+            after = before;
+        }
+        None
+    }
+
+    /// Time weighted average prices of token_0 and token_1 as Q32.32 over the
+    /// `window` seconds before `now`
+    pub fn twap(&self, now: u64, window: u64) -> Option<(u128, u128)> {
+        if window == 0 {
+            return None;
+        }
+        let end = self.observe(now, now)?;
+        let start = self.observe(now, now.checked_sub(window)?)?;
+        let window = u128::from(window);
+        Some((
+            end.cumulative_token_0_price_x32
+                .wrapping_sub(start.cumulative_token_0_price_x32)
+                / window,
+            end.cumulative_token_1_price_x32
+                .wrapping_sub(start.cumulative_token_1_price_x32)
+                / window,
+        ))
+    }
+}
+
+/// The accumulators at `target`, between the `before` and `after` snapshots
+fn interpolate(before: &Observation, after: &Observation, target: u64) -> Observation {
+    let before_timestamp = before.block_timestamp;
+    let after_timestamp = after.block_timestamp;
+    if after_timestamp <= before_timestamp {
+        return *before;
+    }
+    let elapsed = U256::from(target - before_timestamp);
+    let duration = U256::from(after_timestamp - before_timestamp);
+    let between = |before: u128, after: u128| {
+        let delta = U256::from(after.wrapping_sub(before)) * elapsed / duration;
+        before.wrapping_add(delta.as_u128())
+    };
+    Observation {
+        block_timestamp: target,
+        cumulative_token_0_price_x32: between(
+            before.cumulative_token_0_price_x32,
+            after.cumulative_token_0_price_x32,
+        ),
+        cumulative_token_1_price_x32: between(
+            before.cumulative_token_1_price_x32,
+            after.cumulative_token_1_price_x32,
+        ),
+    }
+}
+
+#[cfg(test)]
+pub mod oracle_test {
+    use super::*;
+
+    const ONE: u128 = 1 << 32;
+
+    fn observation_state(block_timestamp: u64) -> Box<ObservationState> {
+        let mut state = Box::<ObservationState>::default();
+        state.initialize(Pubkey::default(), block_timestamp, ONE, ONE);
+        state
+    }
+
+    #[test]
+    fn twap_of_constant_price() {
+        let mut state = observation_state(1_000);
+        state.update(1_100, ONE, ONE);
+        assert_eq!(state.twap(1_200, 150), Some((ONE, ONE)));
+    }
+
+    #[test]
+    fn twap_weights_prices_by_time() {
+        let mut state = observation_state(1_000);
+        // price doubles at 1_100 and holds
+        state.update(1_100, 2 * ONE, ONE / 2);
+        let (price_0, price_1) = state.twap(1_200, 200).unwrap();
+        assert_eq!(price_0, 3 * ONE / 2);
+        assert_eq!(price_1, 3 * ONE / 4);
+        // a window within the second half only sees the new price
+        assert_eq!(state.twap(1_200, 50), Some((2 * ONE, ONE / 2)));
+    }
+
+    #[test]
+    fn manipulation_in_same_block_has_no_weight() {
+        let mut state = observation_state(1_000);
+        state.update(1_100, ONE, ONE);
+        // a swap moves the price 100x and is read back in the same block
+        state.update(1_100, 100 * ONE, ONE / 100);
+        assert_eq!(state.twap(1_100, 100), Some((ONE, ONE)));
+    }
+
+    #[test]
+    fn interpolates_between_snapshots() {
+        let mut state = observation_state(1_000);
+        state.update(1_020, 3 * ONE, ONE);
+        state.update(1_040, 3 * ONE, ONE);
+        // [1_010, 1_040] spends 10s at 1 and 20s at 3
+        assert_eq!(state.twap(1_040, 30).unwrap().0, 7 * ONE / 3);
+    }
+
+    #[test]
+    fn window_older_than_buffer() {
+        let mut state = observation_state(1_000);
+        assert_eq!(state.twap(1_100, 200), None);
+        for i in 1..=OBSERVATION_NUM as u64 + 1 {
+            state.update(1_000 + i * OBSERVATION_UPDATE_DURATION_DEFAULT, ONE, ONE);
+        }
+        let now = 1_000 + (OBSERVATION_NUM as u64 + 1) * OBSERVATION_UPDATE_DURATION_DEFAULT;
+        assert_eq!(
+            state.twap(now, 20 * OBSERVATION_UPDATE_DURATION_DEFAULT),
+            Some((ONE, ONE))
+        );
+        assert_eq!(state.twap(now, now - 1_000), None);
+    }
+}
@@ -1,6 +1,20 @@
 use std::ops::{BitAnd, BitOr, BitXor};
 
+use crate::curve::{
+    calculator::{CurveCalculator, SwapResult},
+    floor_div, spot_price_x32,
+    stable_swap::*,
+    weighted::*,
+    ConstantProductCurve, SwapCurve, TradeDirection, FEE_RATE_DENOMINATOR_VALUE,
+};
+use crate::error::ErrorCode;
+use crate::states::{
+    AmmConfig, FeeSchedule, TaxExemption, CREATOR_FEE_BUCKET, FUND_FEE_BUCKET,
+    MAX_SCHEDULED_FEE_BUCKETS, PROTOCOL_FEE_BUCKET,
+};
+use crate::utils::{scheduled_tax_rate, tax_amount, U256};
 use anchor_lang::prelude::*;
+use anchor_spl::associated_token::get_associated_token_address_with_program_id;
 use anchor_spl::token_interface::Mint;
 /// Seed to derive account address and signature
 pub const POOL_SEED: &str = "pool";
@@ -19,8 +33,157 @@ pub enum PoolStatusBitFlag {
     Disable,
 }
 
+/// How the liquidity of a pool is provided
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PoolType {
+    /// Full range liquidity, tracked by the lp mint
+    Standard = 0,
+    /// Ranged liquidity, tracked by positions and the `ClmmState` of the pool
+    Concentrated = 1,
+}
+
+/// The invariant a standard pool trades on
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CurveType {
+    ConstantProduct = 0,
+    StableSwap = 1,
+    Weighted = 2,
+    LiquidityBootstrapping = 3,
+}
+
+/// Curve selection, and its parameters, when creating a pool
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CurveParams {
+    ConstantProduct,
+    StableSwap {
+        amp: u64,
+    },
+    /// Weight of token_0 over WEIGHT_DENOMINATOR, token_1 takes the rest
+    Weighted {
+        weight_0: u64,
+    },
+    /// Weighted pool whose token_0 weight moves linearly from `start_weight_0`
+    /// at the open time to `end_weight_0` at `end_time`
+    LiquidityBootstrapping {
+        start_weight_0: u64,
+        end_weight_0: u64,
+        end_time: u64,
+    },
+}
+
+/// Launch tax schedule, the tax rates start at these rates at the open time
+/// and move to the pool's `in_tax_rate` and `out_tax_rate` over `duration`
+/// seconds
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub struct TaxSchedule {
+    pub start_in_tax_rate: u64,
+    pub start_out_tax_rate: u64,
+    pub duration: u64,
+    /// The rates move every `step` seconds, linearly when 0
+    pub step: u64,
+}
+
+impl TaxSchedule {
+    pub fn validate(&self) -> Result<()> {
+        require!(
+            self.start_in_tax_rate <= FEE_RATE_DENOMINATOR_VALUE
+                && self.start_out_tax_rate <= FEE_RATE_DENOMINATOR_VALUE
+                && self.duration > 0
+                && self.step <= self.duration,
+            ErrorCode::InvalidInput
+        );
+        Ok(())
+    }
+}
+
+/// Anti-whale guards on the taxed token, until `end_time`. Zero values turn
+/// a limit off
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub struct TradeLimits {
+    /// Max amount of the taxed token in or out of a single swap
+    pub max_tx_amount: u64,
+    /// Max amount of the taxed token in or out of a single swap, as a share
+    /// of its reserve over FEE_RATE_DENOMINATOR_VALUE
+    pub max_tx_rate: u64,
+    /// Max balance of the taxed token a buy may leave in the receiving account
+    pub max_wallet_amount: u64,
+    /// The limits stop applying at this timestamp
+    pub end_time: u64,
+}
+
+impl TradeLimits {
+    pub fn validate(&self) -> Result<()> {
+        require!(
+            self.max_tx_rate <= FEE_RATE_DENOMINATOR_VALUE,
+            ErrorCode::InvalidInput
+        );
+        Ok(())
+    }
+
+    /// Whether these limits only loosen `current`: each limit is raised or
+    /// turned off, and they end no later
+    pub fn loosens(&self, current: &TradeLimits) -> bool {
+        let loosens_limit = |new: u64, current: u64| new == 0 || (current != 0 && new >= current);
+        loosens_limit(self.max_tx_amount, current.max_tx_amount)
+            && loosens_limit(self.max_tx_rate, current.max_tx_rate)
+            && loosens_limit(self.max_wallet_amount, current.max_wallet_amount)
+            && self.end_time <= current.end_time
+    }
+}
+
+impl CurveParams {
+    pub fn validate(&self, mint_0_decimals: u8, mint_1_decimals: u8, open_time: u64) -> Result<()> {
+        match self {
+            CurveParams::ConstantProduct => {}
+            CurveParams::StableSwap { amp } => {
+                require!(
+                    (MIN_AMP..=MAX_AMP).contains(amp),
+                    ErrorCode::InvalidCurveParams
+                );
+                // amounts are compared as is, so the pegged tokens must share decimals
+                require_eq!(
+                    mint_0_decimals,
+                    mint_1_decimals,
+                    ErrorCode::InvalidCurveParams
+                );
+            }
+            CurveParams::Weighted { weight_0 } => {
+                require!(
+                    (MIN_WEIGHT..=WEIGHT_DENOMINATOR - MIN_WEIGHT).contains(weight_0),
+                    ErrorCode::InvalidCurveParams
+                );
+            }
+            CurveParams::LiquidityBootstrapping {
+                start_weight_0,
+                end_weight_0,
+                end_time,
+            } => {
+                let weights = MIN_WEIGHT..=WEIGHT_DENOMINATOR - MIN_WEIGHT;
+                require!(
+                    weights.contains(start_weight_0) && weights.contains(end_weight_0),
+                    ErrorCode::InvalidCurveParams
+                );
+                require_gt!(*end_time, open_time, ErrorCode::InvalidCurveParams);
+            }
+        }
+        Ok(())
+    }
+
+    /// The curve a new pool starts trading on
+    pub fn curve(&self) -> Box<dyn SwapCurve> {
+        match *self {
+            CurveParams::ConstantProduct => Box::new(ConstantProductCurve),
+            CurveParams::StableSwap { amp } => Box::new(StableSwapCurve::new(amp)),
+            CurveParams::Weighted { weight_0 } => Box::new(WeightedCurve::new(weight_0)),
+            CurveParams::LiquidityBootstrapping { start_weight_0, .. } => {
+                Box::new(WeightedCurve::new(start_weight_0))
+            }
+        }
+    }
+}
+
 #[account(zero_copy(unsafe))]
-#[repr(packed)]
+#[repr(C, packed)]
 #[derive(Default, Debug)]
 pub struct PoolState {
     pub auth_bump: u8,
@@ -81,12 +244,141 @@ pub struct PoolState {
     pub tax_disabled: bool,
     /// LP fee rate
     pub lp_fee_rate: u64,
+    /// 0: standard, 1: concentrated liquidity
+    pub pool_type: u8,
+    /// 0: constant product, 1: stable swap, 2: weighted, 3: liquidity bootstrapping
+    pub curve_type: u8,
+    /// Burn the collected tax of the tax mint instead of paying it out
+    pub tax_burn: bool,
+    pub padding1: [u8; 5],
+    /// StableSwap amplification coefficient, ramping linearly from
+    /// `amp_initial` to `amp_target` between the ramp start and stop times
+    pub amp_initial: u64,
+    pub amp_target: u64,
+    pub amp_ramp_start_time: u64,
+    pub amp_ramp_stop_time: u64,
+    /// Weight of token_0 over WEIGHT_DENOMINATOR for a weighted pool. For a
+    /// liquidity bootstrapping pool, the weight at `open_time`, moving linearly
+    /// to `end_weight_0` at `weight_end_time`
+    pub weight_0: u64,
+    pub end_weight_0: u64,
+    pub weight_end_time: u64,
+
+    /// The price observation account of the pool
+    pub observation_key: Pubkey,
+
+    /// Dynamic fee, an extra lp fee rate of `dynamic_fee_min_rate` plus the
+    /// recent volatility, capped at `dynamic_fee_max_rate`. The volatility
+    /// decays linearly to 0 over `dynamic_fee_decay_period` seconds after a
+    /// swap. Disabled when the decay period is 0
+    pub dynamic_fee_min_rate: u64,
+    pub dynamic_fee_max_rate: u64,
+    pub dynamic_fee_decay_period: u64,
+    /// Sum of the relative price moves of recent swaps, over FEE_RATE_DENOMINATOR_VALUE
+    pub volatility_accumulator: u64,
+    pub volatility_update_time: u64,
+
+    /// Launch tax schedule, see `TaxSchedule`. No schedule when the duration is 0
+    pub tax_schedule_start_in_rate: u64,
+    pub tax_schedule_start_out_rate: u64,
+    pub tax_schedule_duration: u64,
+    pub tax_schedule_step: u64,
+
+    /// Trade limits, see `TradeLimits`
+    pub max_tx_amount: u64,
+    pub max_tx_rate: u64,
+    pub max_wallet_amount: u64,
+    pub trade_limits_end_time: u64,
+
+    /// Share of the swap tax left in the reserves for the liquidity
+    /// providers instead of accruing to `tax_amount_0/1`, over
+    /// FEE_RATE_DENOMINATOR_VALUE
+    pub tax_liquidity_rate: u64,
+
+    /// Tax rates on the tax mint leg of deposits and withdrawals
+    pub deposit_tax_rate: u64,
+    pub withdraw_tax_rate: u64,
+
+    /// The fields from here on are past the size of the pools created before
+    /// them, which `migrate_pool` grows to the current layout
 
-    pub padding: [u64; 31],
+    /// Tax increase queued by the tax authority, applicable from
+    /// `pending_tax_apply_time`. None queued when the apply time is 0
+    pub pending_tax_mint: Pubkey,
+    pub pending_in_tax_rate: u64,
+    pub pending_out_tax_rate: u64,
+    pub pending_quote_in_tax_rate: u64,
+    pub pending_quote_out_tax_rate: u64,
+    pub pending_tax_apply_time: u64,
+
+    /// Wallet the tax authority delegates tax collection to, and the only
+    /// payee of a permissionless collection. None when default
+    pub tax_recipient: Pubkey,
+
+    /// In and out tax rates of the other token of the pair, swapped into or
+    /// out of the pool alongside the tax mint
+    pub quote_in_tax_rate: u64,
+    pub quote_out_tax_rate: u64,
+
+    /// The amounts of token_0 and token_1 owed to the fee schedule buckets of
+    /// the amm config, bucket `i + 2` accrues at index `i`
+    pub bucket_fees_token_0: [u64; MAX_SCHEDULED_FEE_BUCKETS],
+    pub bucket_fees_token_1: [u64; MAX_SCHEDULED_FEE_BUCKETS],
+
+    /// Share of the trade fee owed to the pool creator, capped by the amm config
+    pub creator_fee_rate: u64,
+    /// The amounts of token_0 and token_1 owed to the pool creator
+    pub creator_fees_token_0: u64,
+    pub creator_fees_token_1: u64,
+
+    /// Lp tax increase queued by the tax authority, applicable from
+    /// `pending_lp_tax_apply_time`. None queued when the apply time is 0
+    pub pending_deposit_tax_rate: u64,
+    pub pending_withdraw_tax_rate: u64,
+    pub pending_lp_tax_apply_time: u64,
+
+    /// Trade limits queued by the tax authority when they tighten or extend
+    /// the current ones, applicable from `pending_trade_limits_apply_time`.
+    /// None queued when the apply time is 0
+    pub pending_max_tx_amount: u64,
+    pub pending_max_tx_rate: u64,
+    pub pending_max_wallet_amount: u64,
+    pub pending_trade_limits_end_time: u64,
+    pub pending_trade_limits_apply_time: u64,
+
+    pub padding: [u64; 3],
 }
 
 impl PoolState {
-    pub const LEN: usize = 8 + 1 * 5 + 9 * 32 + 8 * 6 + 32 * 2 + 8 * 4 + 1 + 8 + 8 * 31;
+    pub const LEN: usize = 8
+        + 5
+        + 9 * 32
+        + 8 * 6
+        + 32 * 2
+        + 8 * 4
+        + 1
+        + 8
+        + 1
+        + 1
+        + 1
+        + 5
+        + 8 * 4
+        + 8 * 3
+        + 32
+        + 8 * 5
+        + 8 * 4
+        + 8 * 4
+        + 8
+        + 8 * 2
+        + 32
+        + 8 * 5
+        + 32
+        + 8 * 2
+        + 8 * MAX_SCHEDULED_FEE_BUCKETS * 2
+        + 8 * 3
+        + 8 * 3
+        + 8 * 3
+        + 8 * 5;
 
     pub fn initialize(
         &mut self,
@@ -100,11 +392,13 @@ impl PoolState {
         token_0_mint: &InterfaceAccount<Mint>,
         token_1_mint: &InterfaceAccount<Mint>,
         lp_mint: &InterfaceAccount<Mint>,
+        observation_key: Pubkey,
         // tax
         tax_mint: Pubkey,
         in_tax_rate: u64,
         out_tax_rate: u64,
         lp_fee_rate: u64,
+        curve_params: CurveParams,
     ) {
         self.auth_bump = auth_bump;
         self.lp_mint_decimals = lp_mint.decimals;
@@ -119,6 +413,7 @@ impl PoolState {
         self.token_1_mint = token_1_mint.key();
         self.token_0_program = *token_0_mint.to_account_info().owner;
         self.token_1_program = *token_1_mint.to_account_info().owner;
+        self.observation_key = observation_key;
 
         self.lp_supply = lp_supply;
         self.protocol_fees_token_0 = 0;
@@ -134,6 +429,605 @@ impl PoolState {
         self.tax_disabled = false;
         self.tax_authority = pool_creator.key();
         self.lp_fee_rate = lp_fee_rate;
+
+        // Curve
+        match curve_params {
+            CurveParams::ConstantProduct => {
+                self.curve_type = CurveType::ConstantProduct as u8;
+            }
+            CurveParams::StableSwap { amp } => {
+                self.curve_type = CurveType::StableSwap as u8;
+                self.amp_initial = amp;
+                self.amp_target = amp;
+            }
+            CurveParams::Weighted { weight_0 } => {
+                self.curve_type = CurveType::Weighted as u8;
+                self.weight_0 = weight_0;
+            }
+            CurveParams::LiquidityBootstrapping {
+                start_weight_0,
+                end_weight_0,
+                end_time,
+            } => {
+                self.curve_type = CurveType::LiquidityBootstrapping as u8;
+                self.weight_0 = start_weight_0;
+                self.end_weight_0 = end_weight_0;
+                self.weight_end_time = end_time;
+            }
+        }
+    }
+
+    /// Initialize a concentrated liquidity pool, which has no lp mint
+    pub fn initialize_clmm(
+        &mut self,
+        auth_bump: u8,
+        open_time: u64,
+        pool_creator: Pubkey,
+        amm_config: Pubkey,
+        token_0_vault: Pubkey,
+        token_1_vault: Pubkey,
+        token_0_mint: &InterfaceAccount<Mint>,
+        token_1_mint: &InterfaceAccount<Mint>,
+        // tax
+        tax_mint: Pubkey,
+        in_tax_rate: u64,
+        out_tax_rate: u64,
+    ) {
+        self.auth_bump = auth_bump;
+        self.mint_0_decimals = token_0_mint.decimals;
+        self.mint_1_decimals = token_1_mint.decimals;
+        self.amm_config = amm_config;
+        self.pool_creator = pool_creator;
+        self.token_0_vault = token_0_vault;
+        self.token_1_vault = token_1_vault;
+        self.lp_mint = Pubkey::default();
+        self.token_0_mint = token_0_mint.key();
+        self.token_1_mint = token_1_mint.key();
+        self.token_0_program = *token_0_mint.to_account_info().owner;
+        self.token_1_program = *token_1_mint.to_account_info().owner;
+        self.open_time = open_time;
+
+        // Tax
+        self.tax_mint = tax_mint;
+        self.in_tax_rate = in_tax_rate;
+        self.out_tax_rate = out_tax_rate;
+        self.tax_disabled = false;
+        self.tax_authority = pool_creator;
+        self.pool_type = PoolType::Concentrated as u8;
+    }
+
+    pub fn is_concentrated(&self) -> bool {
+        self.pool_type == PoolType::Concentrated as u8
+    }
+
+    /// Amplification coefficient of a stable swap pool at the given time
+    pub fn amp(&self, block_timestamp: u64) -> u64 {
+        current_amp(
+            self.amp_initial,
+            self.amp_target,
+            self.amp_ramp_start_time,
+            self.amp_ramp_stop_time,
+            block_timestamp,
+        )
+    }
+
+    pub fn is_liquidity_bootstrapping(&self) -> bool {
+        self.curve_type == CurveType::LiquidityBootstrapping as u8
+    }
+
+    /// The curve to swap on at the given time
+    pub fn curve(&self, block_timestamp: u64) -> Box<dyn SwapCurve> {
+        match self.curve_type {
+            x if x == CurveType::StableSwap as u8 => {
+                Box::new(StableSwapCurve::new(self.amp(block_timestamp)))
+            }
+            x if x == CurveType::Weighted as u8 => Box::new(WeightedCurve::new(self.weight_0)),
+            x if x == CurveType::LiquidityBootstrapping as u8 => {
+                Box::new(WeightedCurve::new(current_weight(
+                    self.weight_0,
+                    self.end_weight_0,
+                    self.open_time,
+                    self.weight_end_time,
+                    block_timestamp,
+                )))
+            }
+            _ => Box::new(ConstantProductCurve),
+        }
+    }
+
+    /// Spot prices of token_0 in token_1 and of token_1 in token_0 as Q32.32
+    /// for the given pool amounts, zero for an empty pool
+    pub fn token_price_x32(
+        &self,
+        token_0_amount: u64,
+        token_1_amount: u64,
+        block_timestamp: u64,
+    ) -> (u128, u128) {
+        spot_price_x32(
+            self.curve(block_timestamp).as_ref(),
+            u128::from(token_0_amount),
+            u128::from(token_1_amount),
+        )
+        .unwrap_or_default()
+    }
+
+    pub fn set_tax_schedule(&mut self, tax_schedule: TaxSchedule) {
+        self.tax_schedule_start_in_rate = tax_schedule.start_in_tax_rate;
+        self.tax_schedule_start_out_rate = tax_schedule.start_out_tax_rate;
+        self.tax_schedule_duration = tax_schedule.duration;
+        self.tax_schedule_step = tax_schedule.step;
+    }
+
+    /// In and out tax rates at the given time, following the launch tax
+    /// schedule if there is one
+    pub fn tax_rates(&self, block_timestamp: u64) -> (u64, u64) {
+        if self.tax_schedule_duration == 0 {
+            return (self.in_tax_rate, self.out_tax_rate);
+        }
+        let elapsed = block_timestamp.saturating_sub(self.open_time);
+        (
+            scheduled_tax_rate(
+                self.tax_schedule_start_in_rate,
+                self.in_tax_rate,
+                self.tax_schedule_duration,
+                self.tax_schedule_step,
+                elapsed,
+            ),
+            scheduled_tax_rate(
+                self.tax_schedule_start_out_rate,
+                self.out_tax_rate,
+                self.tax_schedule_duration,
+                self.tax_schedule_step,
+                elapsed,
+            ),
+        )
+    }
+
+    pub fn set_trade_limits(&mut self, trade_limits: TradeLimits) {
+        self.max_tx_amount = trade_limits.max_tx_amount;
+        self.max_tx_rate = trade_limits.max_tx_rate;
+        self.max_wallet_amount = trade_limits.max_wallet_amount;
+        self.trade_limits_end_time = trade_limits.end_time;
+    }
+
+    pub fn trade_limits(&self) -> TradeLimits {
+        TradeLimits {
+            max_tx_amount: self.max_tx_amount,
+            max_tx_rate: self.max_tx_rate,
+            max_wallet_amount: self.max_wallet_amount,
+            end_time: self.trade_limits_end_time,
+        }
+    }
+
+    pub fn has_pending_trade_limits(&self) -> bool {
+        self.pending_trade_limits_apply_time != 0
+    }
+
+    pub fn queue_trade_limits(&mut self, trade_limits: TradeLimits, apply_time: u64) {
+        self.pending_max_tx_amount = trade_limits.max_tx_amount;
+        self.pending_max_tx_rate = trade_limits.max_tx_rate;
+        self.pending_max_wallet_amount = trade_limits.max_wallet_amount;
+        self.pending_trade_limits_end_time = trade_limits.end_time;
+        self.pending_trade_limits_apply_time = apply_time;
+    }
+
+    pub fn clear_pending_trade_limits(&mut self) {
+        self.queue_trade_limits(
+            TradeLimits {
+                max_tx_amount: 0,
+                max_tx_rate: 0,
+                max_wallet_amount: 0,
+                end_time: 0,
+            },
+            0,
+        );
+    }
+
+    /// Take the queued trade limits once their apply time has passed
+    pub fn take_ready_trade_limits(&mut self, block_timestamp: u64) -> Option<TradeLimits> {
+        if !self.has_pending_trade_limits()
+            || block_timestamp < self.pending_trade_limits_apply_time
+        {
+            return None;
+        }
+        let trade_limits = TradeLimits {
+            max_tx_amount: self.pending_max_tx_amount,
+            max_tx_rate: self.pending_max_tx_rate,
+            max_wallet_amount: self.pending_max_wallet_amount,
+            end_time: self.pending_trade_limits_end_time,
+        };
+        self.clear_pending_trade_limits();
+        Some(trade_limits)
+    }
+
+    pub fn trade_limits_active(&self, block_timestamp: u64) -> bool {
+        block_timestamp < self.trade_limits_end_time
+    }
+
+    /// Check a swap moving `amount` of the taxed token in or out of the pool,
+    /// which held `reserve` of it before the swap
+    pub fn check_max_tx(&self, block_timestamp: u64, amount: u64, reserve: u64) -> Result<()> {
+        if !self.trade_limits_active(block_timestamp) {
+            return Ok(());
+        }
+        let max_tx_amount = self.max_tx_amount;
+        if max_tx_amount > 0 {
+            require_gte!(max_tx_amount, amount, ErrorCode::ExceededMaxTransaction);
+        }
+        if self.max_tx_rate > 0 {
+            let max_amount = tax_amount(reserve, self.max_tx_rate).unwrap();
+            require!(
+                u128::from(amount) <= max_amount,
+                ErrorCode::ExceededMaxTransaction
+            );
+        }
+        Ok(())
+    }
+
+    pub fn has_max_wallet(&self, block_timestamp: u64) -> bool {
+        self.trade_limits_active(block_timestamp) && self.max_wallet_amount > 0
+    }
+
+    /// Check the taxed token balance a buy leaves in the receiving account
+    pub fn check_max_wallet(&self, block_timestamp: u64, balance: u64) -> Result<()> {
+        if self.has_max_wallet(block_timestamp) {
+            let max_wallet_amount = self.max_wallet_amount;
+            require_gte!(max_wallet_amount, balance, ErrorCode::ExceededMaxWallet);
+        }
+        Ok(())
+    }
+
+    /// Accrue the tax of a swap in `mint`, minus the auto-liquidity share
+    /// which stays in the reserves. Returns that share
+    pub fn accrue_tax(&mut self, mint: Pubkey, tax: u64) -> u64 {
+        let liquidity_amount = tax_amount(tax, self.tax_liquidity_rate).unwrap() as u64;
+        self.add_tax_amount(mint, tax - liquidity_amount);
+        liquidity_amount
+    }
+
+    /// Accrue tax in `mint`
+    pub fn add_tax_amount(&mut self, mint: Pubkey, tax: u64) {
+        if mint == self.token_0_mint {
+            self.tax_amount_0 = self.tax_amount_0.checked_add(tax).unwrap();
+        } else {
+            self.tax_amount_1 = self.tax_amount_1.checked_add(tax).unwrap();
+        }
+    }
+
+    /// Tax of a deposit or withdrawal of `token_0_amount` and `token_1_amount`
+    /// at `tax_rate`, taken on the tax mint leg only
+    pub fn lp_tax(&self, token_0_amount: u64, token_1_amount: u64, tax_rate: u64) -> (u64, u64) {
+        if self.tax_disabled || tax_rate == 0 {
+            return (0, 0);
+        }
+        if self.tax_mint == self.token_0_mint {
+            (tax_amount(token_0_amount, tax_rate).unwrap() as u64, 0)
+        } else {
+            (0, tax_amount(token_1_amount, tax_rate).unwrap() as u64)
+        }
+    }
+
+    /// Whether `key` may collect the tax to any account
+    pub fn is_tax_collector(&self, key: Pubkey) -> bool {
+        key == self.tax_authority
+            || (self.tax_recipient != Pubkey::default() && key == self.tax_recipient)
+    }
+
+    /// The wallet a permissionless collection pays the tax to
+    pub fn tax_payee(&self) -> Pubkey {
+        if self.tax_recipient != Pubkey::default() {
+            self.tax_recipient
+        } else {
+            self.tax_authority
+        }
+    }
+
+    /// Take up to the requested amounts out of the accrued tax, returns the
+    /// amounts taken
+    pub fn take_tax(
+        &mut self,
+        amount_0_requested: u64,
+        amount_1_requested: u64,
+    ) -> Result<(u64, u64)> {
+        let amount_0 = amount_0_requested.min(self.tax_amount_0);
+        let amount_1 = amount_1_requested.min(self.tax_amount_1);
+        require!(amount_0 > 0 || amount_1 > 0, ErrorCode::NoPendingTax);
+
+        self.tax_amount_0 -= amount_0;
+        self.tax_amount_1 -= amount_1;
+        Ok((amount_0, amount_1))
+    }
+
+    /// Swap all the accrued tax of the input side of `trade_direction` into the
+    /// other side of the pool, its fees accrue like a swap. Returns the swap and
+    /// the amount paid out, of which at least `minimum_amount_out` is received
+    /// once `transfer_fee` of the quote mint is taken
+    pub fn swap_tax_as_quote(
+        &mut self,
+        block_timestamp: u64,
+        trade_direction: TradeDirection,
+        total_input_token_amount: u64,
+        total_output_token_amount: u64,
+        amm_config: &AmmConfig,
+        fee_schedule: Option<&FeeSchedule>,
+        minimum_amount_out: u64,
+        transfer_fee: impl Fn(u64) -> Result<u64>,
+    ) -> Result<(SwapResult, u64)> {
+        let zero_for_one = trade_direction == TradeDirection::ZeroForOne;
+        // The accrued tax is already in the vault, it is swapped in as is
+        let amount_in = if zero_for_one {
+            self.tax_amount_0
+        } else {
+            self.tax_amount_1
+        };
+        require_gt!(amount_in, 0, ErrorCode::NoPendingTax);
+
+        let curve = self.curve(block_timestamp);
+        let result = CurveCalculator::swap_base_input(
+            curve.as_ref(),
+            u128::from(amount_in),
+            u128::from(total_input_token_amount),
+            u128::from(total_output_token_amount),
+            amm_config.trade_fee_rate,
+            amm_config.protocol_fee_rate,
+            amm_config.fund_fee_rate,
+            0,
+            self.lp_fee_rate + self.dynamic_fee_rate(block_timestamp),
+            trade_direction,
+        )
+        .ok_or(ErrorCode::ZeroTradingTokens)?;
+
+        require!(
+            curve.check_invariant(
+                u128::from(total_input_token_amount),
+                u128::from(total_output_token_amount),
+                result.new_swap_source_amount,
+                result.new_swap_destination_amount,
+                trade_direction,
+            ),
+            ErrorCode::InvariantViolated
+        );
+
+        require_eq!(
+            u64::try_from(result.source_amount_swapped).unwrap(),
+            amount_in
+        );
+
+        // check minimum amount out
+        let amount_out = u64::try_from(result.destination_amount_swapped).unwrap();
+        let amount_received = amount_out.checked_sub(transfer_fee(amount_out)?).unwrap();
+        require_gt!(amount_received, 0);
+        require_gte!(
+            amount_received,
+            minimum_amount_out,
+            ErrorCode::ExceededSlippage
+        );
+
+        let protocol_fee = u64::try_from(result.protocol_fee).unwrap();
+        let fund_fee = u64::try_from(result.fund_fee).unwrap();
+        let creator_fee = self.creator_fee(amm_config.max_creator_fee_rate, result.trade_fee);
+
+        // the tax becomes part of the reserves
+        if zero_for_one {
+            self.tax_amount_0 = 0;
+            self.protocol_fees_token_0 = self
+                .protocol_fees_token_0
+                .checked_add(protocol_fee)
+                .unwrap();
+            self.fund_fees_token_0 = self.fund_fees_token_0.checked_add(fund_fee).unwrap();
+        } else {
+            self.tax_amount_1 = 0;
+            self.protocol_fees_token_1 = self
+                .protocol_fees_token_1
+                .checked_add(protocol_fee)
+                .unwrap();
+            self.fund_fees_token_1 = self.fund_fees_token_1.checked_add(fund_fee).unwrap();
+        }
+        self.add_bucket_fees(
+            zero_for_one,
+            &amm_config.scheduled_fees(fee_schedule, result.trade_fee)?,
+        );
+        self.add_creator_fee(zero_for_one, creator_fee);
+
+        Ok((result, amount_out))
+    }
+
+    /// Side of the tax burned in burn mode, `Some(true)` for token_0
+    pub fn tax_burn_token_0(&self) -> Option<bool> {
+        self.tax_burn.then(|| self.tax_mint == self.token_0_mint)
+    }
+
+    /// Split collected tax amounts into the amount burned in burn mode and the
+    /// amounts of token_0 and token_1 paid out
+    pub fn split_tax_burn(&self, amount_0: u64, amount_1: u64) -> (u64, u64, u64) {
+        match self.tax_burn_token_0() {
+            Some(true) => (amount_0, 0, amount_1),
+            Some(false) => (amount_1, amount_0, 0),
+            None => (0, amount_0, amount_1),
+        }
+    }
+
+    /// Take all the accrued tax of the tax mint to burn it, returns the amounts
+    /// of token_0 and token_1 taken
+    pub fn take_burn_tax(&mut self) -> Result<(u64, u64)> {
+        require!(!self.tax_disabled, ErrorCode::TaxDisabled);
+        require!(self.tax_burn, ErrorCode::TaxBurnNotEnabled);
+        let amounts = if self.tax_mint == self.token_0_mint {
+            let amount = self.tax_amount_0;
+            self.tax_amount_0 = 0;
+            (amount, 0)
+        } else {
+            let amount = self.tax_amount_1;
+            self.tax_amount_1 = 0;
+            (0, amount)
+        };
+        require!(amounts.0 > 0 || amounts.1 > 0, ErrorCode::NoPendingTax);
+        Ok(amounts)
+    }
+
+    /// In tax rate of the input token and out tax rate of the output token of
+    /// a swap at the given time, 0 when the tax is disabled
+    pub fn swap_tax_rates(&self, block_timestamp: u64, input_mint: Pubkey) -> (u64, u64) {
+        if self.tax_disabled {
+            return (0, 0);
+        }
+        let (in_tax_rate, out_tax_rate) = self.tax_rates(block_timestamp);
+        if input_mint == self.tax_mint {
+            (in_tax_rate, self.quote_out_tax_rate)
+        } else {
+            (self.quote_in_tax_rate, out_tax_rate)
+        }
+    }
+
+    /// Tax rates of a swap as `swap_tax_rates`, 0 when the payer holds a tax
+    /// exemption of the pool
+    pub fn payer_tax_rates(
+        &self,
+        block_timestamp: u64,
+        input_mint: Pubkey,
+        tax_exemption: Option<&TaxExemption>,
+    ) -> (u64, u64) {
+        if tax_exemption.is_some() {
+            return (0, 0);
+        }
+        self.swap_tax_rates(block_timestamp, input_mint)
+    }
+
+    pub fn set_tax(
+        &mut self,
+        tax_mint: Pubkey,
+        in_tax_rate: u64,
+        out_tax_rate: u64,
+        quote_in_tax_rate: u64,
+        quote_out_tax_rate: u64,
+    ) {
+        self.tax_mint = tax_mint;
+        self.in_tax_rate = in_tax_rate;
+        self.out_tax_rate = out_tax_rate;
+        self.quote_in_tax_rate = quote_in_tax_rate;
+        self.quote_out_tax_rate = quote_out_tax_rate;
+    }
+
+    /// The in and out tax rates of token_0, then of token_1, under the given tax
+    fn mint_tax_rates(
+        &self,
+        tax_mint: Pubkey,
+        in_tax_rate: u64,
+        out_tax_rate: u64,
+        quote_in_tax_rate: u64,
+        quote_out_tax_rate: u64,
+    ) -> [u64; 4] {
+        if tax_mint == self.token_0_mint {
+            [
+                in_tax_rate,
+                out_tax_rate,
+                quote_in_tax_rate,
+                quote_out_tax_rate,
+            ]
+        } else {
+            [
+                quote_in_tax_rate,
+                quote_out_tax_rate,
+                in_tax_rate,
+                out_tax_rate,
+            ]
+        }
+    }
+
+    /// Whether moving to the given tax raises any tax a trader can pay
+    pub fn is_tax_increase(
+        &self,
+        tax_mint: Pubkey,
+        in_tax_rate: u64,
+        out_tax_rate: u64,
+        quote_in_tax_rate: u64,
+        quote_out_tax_rate: u64,
+    ) -> bool {
+        let current = self.mint_tax_rates(
+            self.tax_mint,
+            self.in_tax_rate,
+            self.out_tax_rate,
+            self.quote_in_tax_rate,
+            self.quote_out_tax_rate,
+        );
+        let new = self.mint_tax_rates(
+            tax_mint,
+            in_tax_rate,
+            out_tax_rate,
+            quote_in_tax_rate,
+            quote_out_tax_rate,
+        );
+        new.iter()
+            .zip(current.iter())
+            .any(|(new, current)| new > current)
+    }
+
+    pub fn has_pending_tax_change(&self) -> bool {
+        self.pending_tax_apply_time != 0
+    }
+
+    pub fn queue_tax_change(
+        &mut self,
+        tax_mint: Pubkey,
+        in_tax_rate: u64,
+        out_tax_rate: u64,
+        quote_in_tax_rate: u64,
+        quote_out_tax_rate: u64,
+        apply_time: u64,
+    ) {
+        self.pending_tax_mint = tax_mint;
+        self.pending_in_tax_rate = in_tax_rate;
+        self.pending_out_tax_rate = out_tax_rate;
+        self.pending_quote_in_tax_rate = quote_in_tax_rate;
+        self.pending_quote_out_tax_rate = quote_out_tax_rate;
+        self.pending_tax_apply_time = apply_time;
+    }
+
+    pub fn clear_pending_tax_change(&mut self) {
+        self.queue_tax_change(Pubkey::default(), 0, 0, 0, 0, 0);
+    }
+
+    pub fn set_lp_tax(&mut self, deposit_tax_rate: u64, withdraw_tax_rate: u64) {
+        self.deposit_tax_rate = deposit_tax_rate;
+        self.withdraw_tax_rate = withdraw_tax_rate;
+    }
+
+    /// Whether moving to the given lp tax raises the deposit or withdraw tax
+    pub fn is_lp_tax_increase(&self, deposit_tax_rate: u64, withdraw_tax_rate: u64) -> bool {
+        deposit_tax_rate > self.deposit_tax_rate || withdraw_tax_rate > self.withdraw_tax_rate
+    }
+
+    pub fn has_pending_lp_tax_change(&self) -> bool {
+        self.pending_lp_tax_apply_time != 0
+    }
+
+    pub fn queue_lp_tax_change(
+        &mut self,
+        deposit_tax_rate: u64,
+        withdraw_tax_rate: u64,
+        apply_time: u64,
+    ) {
+        self.pending_deposit_tax_rate = deposit_tax_rate;
+        self.pending_withdraw_tax_rate = withdraw_tax_rate;
+        self.pending_lp_tax_apply_time = apply_time;
+    }
+
+    pub fn clear_pending_lp_tax_change(&mut self) {
+        self.queue_lp_tax_change(0, 0, 0);
+    }
+
+    /// Take the queued lp tax change once its apply time has passed, returns
+    /// its deposit and withdraw tax rates
+    pub fn take_ready_lp_tax_change(&mut self, block_timestamp: u64) -> Option<(u64, u64)> {
+        if !self.has_pending_lp_tax_change() || block_timestamp < self.pending_lp_tax_apply_time {
+            return None;
+        }
+        let rates = (
+            self.pending_deposit_tax_rate,
+            self.pending_withdraw_tax_rate,
+        );
+        self.clear_pending_lp_tax_change();
+        Some(rates)
     }
 
     pub fn set_tax_status(&mut self, tax_disabled: bool) {
@@ -145,35 +1039,222 @@ impl PoolState {
     }
 
     pub fn set_status_by_bit(&mut self, bit: PoolStatusBitIndex, flag: PoolStatusBitFlag) {
-        let s = u8::from(1) << (bit as u8);
+        let s = 1u8 << (bit as u8);
         if flag == PoolStatusBitFlag::Disable {
             self.status = self.status.bitor(s);
         } else {
-            let m = u8::from(255).bitxor(s);
+            let m = u8::MAX.bitxor(s);
             self.status = self.status.bitand(m);
         }
     }
 
     /// Get status by bit, if it is `noraml` status, return true
     pub fn get_status_by_bit(&self, bit: PoolStatusBitIndex) -> bool {
-        let status = u8::from(1) << (bit as u8);
+        let status = 1u8 << (bit as u8);
         self.status.bitand(status) == 0
     }
 
+    pub fn is_dynamic_fee(&self) -> bool {
+        self.dynamic_fee_decay_period > 0
+    }
+
+    /// Volatility accumulator decayed to the given time
+    fn decayed_volatility(&self, block_timestamp: u64) -> u64 {
+        let elapsed = block_timestamp.saturating_sub(self.volatility_update_time);
+        if elapsed >= self.dynamic_fee_decay_period {
+            return 0;
+        }
+        let remaining = u128::from(self.dynamic_fee_decay_period - elapsed);
+        (u128::from(self.volatility_accumulator) * remaining
+            / u128::from(self.dynamic_fee_decay_period)) as u64
+    }
+
+    /// Extra lp fee rate charged on top of `lp_fee_rate` at the given time
+    pub fn dynamic_fee_rate(&self, block_timestamp: u64) -> u64 {
+        if !self.is_dynamic_fee() {
+            return 0;
+        }
+        std::cmp::min(
+            self.dynamic_fee_min_rate
+                .saturating_add(self.decayed_volatility(block_timestamp)),
+            self.dynamic_fee_max_rate,
+        )
+    }
+
+    /// Add the relative move from `price_before_x32` to `price_after_x32` to
+    /// the decayed volatility
+    pub fn update_volatility(
+        &mut self,
+        block_timestamp: u64,
+        price_before_x32: u128,
+        price_after_x32: u128,
+    ) {
+        if !self.is_dynamic_fee() || price_before_x32 == 0 {
+            return;
+        }
+        let price_move = U256::from(price_before_x32.abs_diff(price_after_x32))
+            * U256::from(FEE_RATE_DENOMINATOR_VALUE)
+            / U256::from(price_before_x32);
+        let price_move = if price_move > U256::from(u64::MAX) {
+            u64::MAX
+        } else {
+            price_move.as_u64()
+        };
+        self.volatility_accumulator = self
+            .decayed_volatility(block_timestamp)
+            .saturating_add(price_move);
+        self.volatility_update_time = block_timestamp;
+    }
+
+    /// The reserves, vault amounts less the fees and the tax accrued on each side
     pub fn vault_amount_without_fee(&self, vault_0: u64, vault_1: u64) -> (u64, u64) {
+        let bucket_fees_token_0 = self.bucket_fees_token_0;
+        let bucket_fees_token_1 = self.bucket_fees_token_1;
         (
             vault_0
                 .checked_sub(
-                    self.protocol_fees_token_0 + self.fund_fees_token_0 + self.tax_amount_0,
+                    self.protocol_fees_token_0
+                        + self.fund_fees_token_0
+                        + bucket_fees_token_0.iter().sum::<u64>()
+                        + self.creator_fees_token_0
+                        + self.tax_amount_0,
                 )
                 .unwrap(),
             vault_1
                 .checked_sub(
-                    self.protocol_fees_token_1 + self.fund_fees_token_1 + self.tax_amount_1,
+                    self.protocol_fees_token_1
+                        + self.fund_fees_token_1
+                        + bucket_fees_token_1.iter().sum::<u64>()
+                        + self.creator_fees_token_1
+                        + self.tax_amount_1,
                 )
                 .unwrap(),
         )
     }
+
+    /// Accrue the fee schedule bucket fees of a swap on its input side
+    pub fn add_bucket_fees(&mut self, zero_for_one: bool, fees: &[u64; MAX_SCHEDULED_FEE_BUCKETS]) {
+        let mut bucket_fees = if zero_for_one {
+            self.bucket_fees_token_0
+        } else {
+            self.bucket_fees_token_1
+        };
+        for (bucket_fee, fee) in bucket_fees.iter_mut().zip(fees) {
+            *bucket_fee = bucket_fee.checked_add(*fee).unwrap();
+        }
+        if zero_for_one {
+            self.bucket_fees_token_0 = bucket_fees;
+        } else {
+            self.bucket_fees_token_1 = bucket_fees;
+        }
+    }
+
+    /// The pool creator's share of a trade fee, at the pool rate capped by the
+    /// current max of the amm config
+    pub fn creator_fee(&self, max_creator_fee_rate: u64, trade_fee: u128) -> u64 {
+        floor_div(
+            trade_fee,
+            u128::from(self.creator_fee_rate.min(max_creator_fee_rate)),
+            u128::from(FEE_RATE_DENOMINATOR_VALUE),
+        )
+        .and_then(|fee| u64::try_from(fee).ok())
+        .unwrap()
+    }
+
+    /// Accrue the pool creator's fee of a swap on its input side
+    pub fn add_creator_fee(&mut self, zero_for_one: bool, creator_fee: u64) {
+        if zero_for_one {
+            self.creator_fees_token_0 = self.creator_fees_token_0.checked_add(creator_fee).unwrap();
+        } else {
+            self.creator_fees_token_1 = self.creator_fees_token_1.checked_add(creator_fee).unwrap();
+        }
+    }
+
+    /// Owner of a fee bucket of the pool, the creator bucket belongs to the
+    /// pool creator
+    pub fn fee_bucket_owner(
+        &self,
+        amm_config: &AmmConfig,
+        fee_schedule: Option<&FeeSchedule>,
+        bucket: u8,
+    ) -> Option<Pubkey> {
+        if bucket == CREATOR_FEE_BUCKET {
+            return Some(self.pool_creator);
+        }
+        amm_config.fee_bucket_owner(bucket, fee_schedule)
+    }
+
+    /// The associated token account in `mint` of the owner of a fee bucket,
+    /// the only account a sweep of the bucket pays
+    pub fn fee_recipient(
+        &self,
+        amm_config: &AmmConfig,
+        fee_schedule: Option<&FeeSchedule>,
+        bucket: u8,
+        mint: &Pubkey,
+        token_program: &Pubkey,
+    ) -> Option<Pubkey> {
+        let bucket_owner = self.fee_bucket_owner(amm_config, fee_schedule, bucket)?;
+        Some(get_associated_token_address_with_program_id(
+            &bucket_owner,
+            mint,
+            token_program,
+        ))
+    }
+
+    /// Take up to the requested amounts out of the fees of a bucket, returns
+    /// the amounts taken
+    pub fn take_bucket_fees(
+        &mut self,
+        bucket: u8,
+        amount_0_requested: u64,
+        amount_1_requested: u64,
+    ) -> (u64, u64) {
+        let take = |accrued: &mut u64, requested: u64| {
+            let amount = requested.min(*accrued);
+            *accrued -= amount;
+            amount
+        };
+        let (mut fees_0, mut fees_1) = match bucket {
+            PROTOCOL_FEE_BUCKET => (self.protocol_fees_token_0, self.protocol_fees_token_1),
+            FUND_FEE_BUCKET => (self.fund_fees_token_0, self.fund_fees_token_1),
+            CREATOR_FEE_BUCKET => (self.creator_fees_token_0, self.creator_fees_token_1),
+            _ => {
+                let index = usize::from(bucket - 2);
+                let (bucket_fees_token_0, bucket_fees_token_1) =
+                    (self.bucket_fees_token_0, self.bucket_fees_token_1);
+                (bucket_fees_token_0[index], bucket_fees_token_1[index])
+            }
+        };
+        let amounts = (
+            take(&mut fees_0, amount_0_requested),
+            take(&mut fees_1, amount_1_requested),
+        );
+        match bucket {
+            PROTOCOL_FEE_BUCKET => {
+                self.protocol_fees_token_0 = fees_0;
+                self.protocol_fees_token_1 = fees_1;
+            }
+            FUND_FEE_BUCKET => {
+                self.fund_fees_token_0 = fees_0;
+                self.fund_fees_token_1 = fees_1;
+            }
+            CREATOR_FEE_BUCKET => {
+                self.creator_fees_token_0 = fees_0;
+                self.creator_fees_token_1 = fees_1;
+            }
+            _ => {
+                let index = usize::from(bucket - 2);
+                let (mut bucket_fees_token_0, mut bucket_fees_token_1) =
+                    (self.bucket_fees_token_0, self.bucket_fees_token_1);
+                bucket_fees_token_0[index] = fees_0;
+                bucket_fees_token_1[index] = fees_1;
+                self.bucket_fees_token_0 = bucket_fees_token_0;
+                self.bucket_fees_token_1 = bucket_fees_token_1;
+            }
+        }
+        amounts
+    }
 }
 
 #[cfg(test)]
@@ -187,78 +1268,849 @@ pub mod pool_test {
         fn get_set_status_by_bit() {
             let mut pool_state = PoolState::default();
             pool_state.set_status(4); // 0000100
-            assert_eq!(
-                pool_state.get_status_by_bit(PoolStatusBitIndex::Swap),
-                false
-            );
-            assert_eq!(
-                pool_state.get_status_by_bit(PoolStatusBitIndex::Deposit),
-                true
-            );
-            assert_eq!(
-                pool_state.get_status_by_bit(PoolStatusBitIndex::Withdraw),
-                true
-            );
+            assert!(!pool_state.get_status_by_bit(PoolStatusBitIndex::Swap));
+            assert!(pool_state.get_status_by_bit(PoolStatusBitIndex::Deposit));
+            assert!(pool_state.get_status_by_bit(PoolStatusBitIndex::Withdraw));
 
             // disable -> disable, nothing to change
             pool_state.set_status_by_bit(PoolStatusBitIndex::Swap, PoolStatusBitFlag::Disable);
-            assert_eq!(
-                pool_state.get_status_by_bit(PoolStatusBitIndex::Swap),
-                false
-            );
+            assert!(!pool_state.get_status_by_bit(PoolStatusBitIndex::Swap));
 
             // disable -> enable
             pool_state.set_status_by_bit(PoolStatusBitIndex::Swap, PoolStatusBitFlag::Enable);
-            assert_eq!(pool_state.get_status_by_bit(PoolStatusBitIndex::Swap), true);
+            assert!(pool_state.get_status_by_bit(PoolStatusBitIndex::Swap));
 
             // enable -> enable, nothing to change
             pool_state.set_status_by_bit(PoolStatusBitIndex::Swap, PoolStatusBitFlag::Enable);
-            assert_eq!(pool_state.get_status_by_bit(PoolStatusBitIndex::Swap), true);
+            assert!(pool_state.get_status_by_bit(PoolStatusBitIndex::Swap));
             // enable -> disable
             pool_state.set_status_by_bit(PoolStatusBitIndex::Swap, PoolStatusBitFlag::Disable);
+            assert!(!pool_state.get_status_by_bit(PoolStatusBitIndex::Swap));
+
+            pool_state.set_status(5); // 0000101
+            assert!(!pool_state.get_status_by_bit(PoolStatusBitIndex::Swap));
+            assert!(!pool_state.get_status_by_bit(PoolStatusBitIndex::Deposit));
+            assert!(pool_state.get_status_by_bit(PoolStatusBitIndex::Withdraw));
+
+            pool_state.set_status(7); // 0000111
+            assert!(!pool_state.get_status_by_bit(PoolStatusBitIndex::Swap));
+            assert!(!pool_state.get_status_by_bit(PoolStatusBitIndex::Deposit));
+            assert!(!pool_state.get_status_by_bit(PoolStatusBitIndex::Withdraw));
+
+            pool_state.set_status(3); // 0000011
+            assert!(pool_state.get_status_by_bit(PoolStatusBitIndex::Swap));
+            assert!(!pool_state.get_status_by_bit(PoolStatusBitIndex::Deposit));
+            assert!(!pool_state.get_status_by_bit(PoolStatusBitIndex::Withdraw));
+        }
+    }
+
+    mod tax_schedule_test {
+        use super::*;
+
+        fn scheduled_pool(step: u64) -> PoolState {
+            let mut pool_state = PoolState {
+                open_time: 1_000,
+                in_tax_rate: 10_000,
+                out_tax_rate: 20_000,
+                ..Default::default()
+            };
+            pool_state.set_tax_schedule(TaxSchedule {
+                start_in_tax_rate: 500_000,
+                start_out_tax_rate: 20_000,
+                duration: 100,
+                step,
+            });
+            pool_state
+        }
+
+        #[test]
+        fn fixed_rates_without_schedule() {
+            let pool_state = PoolState {
+                in_tax_rate: 10_000,
+                out_tax_rate: 20_000,
+                ..Default::default()
+            };
+            assert_eq!(pool_state.tax_rates(0), (10_000, 20_000));
+        }
+
+        #[test]
+        fn linear_decay() {
+            let pool_state = scheduled_pool(0);
+            assert_eq!(pool_state.tax_rates(1_000), (500_000, 20_000));
+            assert_eq!(pool_state.tax_rates(1_025), (377_500, 20_000));
+            assert_eq!(pool_state.tax_rates(1_099), (14_900, 20_000));
+            assert_eq!(pool_state.tax_rates(1_100), (10_000, 20_000));
+            assert_eq!(pool_state.tax_rates(u64::MAX), (10_000, 20_000));
+        }
+
+        #[test]
+        fn stepwise_decay() {
+            let pool_state = scheduled_pool(30);
+            assert_eq!(pool_state.tax_rates(1_029), (500_000, 20_000));
+            assert_eq!(pool_state.tax_rates(1_030), (353_000, 20_000));
+            assert_eq!(pool_state.tax_rates(1_099), (59_000, 20_000));
+            assert_eq!(pool_state.tax_rates(1_100), (10_000, 20_000));
+        }
+
+        #[test]
+        fn follows_updated_target() {
+            let mut pool_state = scheduled_pool(0);
+            pool_state.in_tax_rate = 100_000;
+            assert_eq!(pool_state.tax_rates(1_050), (300_000, 20_000));
+        }
+    }
+
+    mod trade_limits_test {
+        use super::*;
+
+        fn limited_pool() -> PoolState {
+            let mut pool_state = PoolState::default();
+            pool_state.set_trade_limits(TradeLimits {
+                max_tx_amount: 1_000,
+                max_tx_rate: 10_000,
+                max_wallet_amount: 5_000,
+                end_time: 2_000,
+            });
+            pool_state
+        }
+
+        #[test]
+        fn max_tx_amount_and_rate() {
+            let pool_state = limited_pool();
+            assert!(pool_state.check_max_tx(1_000, 1_000, 1_000_000).is_ok());
+            assert!(pool_state.check_max_tx(1_000, 1_001, 1_000_000).is_err());
+            // 1% of the reserve is lower than the absolute limit
+            assert!(pool_state.check_max_tx(1_000, 500, 50_000).is_ok());
+            assert!(pool_state.check_max_tx(1_000, 501, 50_000).is_err());
+        }
+
+        #[test]
+        fn max_wallet() {
+            let pool_state = limited_pool();
+            assert!(pool_state.check_max_wallet(1_000, 5_000).is_ok());
+            assert!(pool_state.check_max_wallet(1_000, 5_001).is_err());
+        }
+
+        #[test]
+        fn limits_expire() {
+            let pool_state = limited_pool();
+            assert!(pool_state.check_max_tx(2_000, u64::MAX, 1).is_ok());
+            assert!(pool_state.check_max_wallet(2_000, u64::MAX).is_ok());
+            assert!(!pool_state.has_max_wallet(2_000));
+        }
+
+        #[test]
+        fn zero_turns_a_limit_off() {
+            let mut pool_state = PoolState::default();
+            pool_state.set_trade_limits(TradeLimits {
+                max_tx_amount: 0,
+                max_tx_rate: 0,
+                max_wallet_amount: 0,
+                end_time: u64::MAX,
+            });
+            assert!(pool_state.check_max_tx(1_000, u64::MAX, 1).is_ok());
+            assert!(!pool_state.has_max_wallet(1_000));
+        }
+
+        #[test]
+        fn only_loosening_skips_the_timelock() {
+            let current = limited_pool().trade_limits();
+            let limits = |max_tx_amount, max_tx_rate, max_wallet_amount, end_time| TradeLimits {
+                max_tx_amount,
+                max_tx_rate,
+                max_wallet_amount,
+                end_time,
+            };
+            assert!(current.loosens(&current));
+            // higher limits, limits turned off, an earlier end
+            assert!(limits(2_000, 20_000, 10_000, 2_000).loosens(&current));
+            assert!(limits(0, 0, 0, 2_000).loosens(&current));
+            assert!(limits(1_000, 10_000, 5_000, 1_000).loosens(&current));
+            // lower limits, limits turned on, a later end
+            assert!(!limits(1, 10_000, 5_000, 2_000).loosens(&current));
+            assert!(!limits(1_000, 9_999, 5_000, 2_000).loosens(&current));
+            assert!(!limits(1_000, 10_000, 4_999, 2_000).loosens(&current));
+            assert!(!limits(1_000, 10_000, 5_000, u64::MAX).loosens(&current));
+            let unlimited = limits(0, 0, 0, 2_000);
+            assert!(!limits(u64::MAX, 0, 0, 2_000).loosens(&unlimited));
+        }
+
+        #[test]
+        fn queued_limits_wait_for_their_apply_time() {
+            let mut pool_state = limited_pool();
+            let tighter = TradeLimits {
+                max_tx_amount: 1,
+                max_tx_rate: 0,
+                max_wallet_amount: 0,
+                end_time: 3_000,
+            };
+            pool_state.queue_trade_limits(tighter, 1_500);
+            assert!(pool_state.has_pending_trade_limits());
+            assert_eq!(pool_state.take_ready_trade_limits(1_499), None);
+            // the current limits hold until the queued ones apply
+            assert!(pool_state.check_max_tx(1_499, 1_000, 1_000_000).is_ok());
+            assert_eq!(pool_state.take_ready_trade_limits(1_500), Some(tighter));
+            assert!(!pool_state.has_pending_trade_limits());
+            assert_eq!(pool_state.take_ready_trade_limits(2_500), None);
+        }
+    }
+
+    mod dynamic_fee_test {
+        use super::*;
+
+        const ONE: u128 = 1 << 32;
+
+        fn dynamic_fee_pool() -> PoolState {
+            PoolState {
+                dynamic_fee_min_rate: 1_000,
+                dynamic_fee_max_rate: 50_000,
+                dynamic_fee_decay_period: 100,
+                ..Default::default()
+            }
+        }
+
+        #[test]
+        fn disabled_by_default() {
+            let mut pool_state = PoolState::default();
+            pool_state.update_volatility(1_000, ONE, 2 * ONE);
+            assert_eq!(pool_state.dynamic_fee_rate(1_000), 0);
+        }
+
+        #[test]
+        fn rises_with_price_moves_and_decays() {
+            let mut pool_state = dynamic_fee_pool();
+            assert_eq!(pool_state.dynamic_fee_rate(1_000), 1_000);
+
+            // a 1% move adds 1% to the fee
+            pool_state.update_volatility(1_000, 100 * ONE, 101 * ONE);
+            assert_eq!(pool_state.dynamic_fee_rate(1_000), 11_000);
+            // a move back down in the same period adds up
+            pool_state.update_volatility(1_000, 101 * ONE, 100 * ONE);
+            assert_eq!(pool_state.dynamic_fee_rate(1_000), 20_900);
+
+            // halfway through the decay period
+            assert_eq!(pool_state.dynamic_fee_rate(1_050), 10_950);
+            // back to the base rate
+            assert_eq!(pool_state.dynamic_fee_rate(1_100), 1_000);
+        }
+
+        #[test]
+        fn capped_at_max_rate() {
+            let mut pool_state = dynamic_fee_pool();
+            pool_state.update_volatility(1_000, ONE, 2 * ONE);
+            assert_eq!(pool_state.dynamic_fee_rate(1_000), 50_000);
+            pool_state.update_volatility(1_000, ONE, u128::MAX);
+            let volatility_accumulator = pool_state.volatility_accumulator;
+            assert_eq!(volatility_accumulator, u64::MAX);
+            assert_eq!(pool_state.dynamic_fee_rate(1_099), 50_000);
+        }
+    }
+
+    mod tax_liquidity_test {
+        use super::*;
+
+        #[test]
+        fn liquidity_share_stays_out_of_accrued_tax() {
+            let mut pool_state = PoolState::default();
+            pool_state.token_1_mint = Pubkey::new_unique();
+            pool_state.tax_mint = pool_state.token_1_mint;
+            let tax_mint = pool_state.tax_mint;
+            assert_eq!(pool_state.accrue_tax(tax_mint, 1_000), 0);
+
+            pool_state.tax_liquidity_rate = 250_000;
+            assert_eq!(pool_state.accrue_tax(tax_mint, 1_000), 250);
+            let (tax_amount_0, tax_amount_1) = (pool_state.tax_amount_0, pool_state.tax_amount_1);
+            assert_eq!((tax_amount_0, tax_amount_1), (0, 1_750));
+            // the share stays in the reserves
+            assert_eq!(pool_state.vault_amount_without_fee(0, 10_000), (0, 8_250));
+        }
+
+        #[test]
+        fn tax_increase() {
+            let (token_0_mint, token_1_mint) = (Pubkey::new_unique(), Pubkey::new_unique());
+            let mut pool_state = PoolState {
+                token_0_mint,
+                token_1_mint,
+                ..Default::default()
+            };
+            pool_state.set_tax(token_0_mint, 10_000, 20_000, 0, 0);
+            assert!(!pool_state.is_tax_increase(token_0_mint, 10_000, 20_000, 0, 0));
+            assert!(!pool_state.is_tax_increase(token_0_mint, 0, 5_000, 0, 0));
+            assert!(pool_state.is_tax_increase(token_0_mint, 10_001, 0, 0, 0));
+            assert!(pool_state.is_tax_increase(token_0_mint, 0, 0, 1, 0));
+            // moving the tax to the other token taxes its traders from 0
+            assert!(pool_state.is_tax_increase(token_1_mint, 0, 1, 0, 0));
+            assert!(!pool_state.is_tax_increase(token_1_mint, 0, 0, 0, 0));
+            // the same rates on the same tokens, expressed from the other side
+            assert!(!pool_state.is_tax_increase(token_1_mint, 0, 0, 10_000, 20_000));
+
+            pool_state.queue_tax_change(token_1_mint, 1, 1, 0, 0, 1_000);
+            assert!(pool_state.has_pending_tax_change());
+            pool_state.clear_pending_tax_change();
+            assert!(!pool_state.has_pending_tax_change());
+        }
+
+        #[test]
+        fn lp_tax_increase_waits_for_its_apply_time() {
+            let mut pool_state = PoolState::default();
+            pool_state.set_lp_tax(10_000, 20_000);
+            assert!(!pool_state.is_lp_tax_increase(10_000, 20_000));
+            assert!(!pool_state.is_lp_tax_increase(0, 5_000));
+            assert!(pool_state.is_lp_tax_increase(10_001, 0));
+            assert!(pool_state.is_lp_tax_increase(0, 20_001));
+
+            pool_state.queue_lp_tax_change(10_000, 50_000, 1_000);
+            assert!(pool_state.has_pending_lp_tax_change());
+            assert_eq!(pool_state.take_ready_lp_tax_change(999), None);
+            assert!(pool_state.has_pending_lp_tax_change());
             assert_eq!(
-                pool_state.get_status_by_bit(PoolStatusBitIndex::Swap),
-                false
+                pool_state.take_ready_lp_tax_change(1_000),
+                Some((10_000, 50_000))
             );
+            assert!(!pool_state.has_pending_lp_tax_change());
+            assert_eq!(pool_state.take_ready_lp_tax_change(2_000), None);
+            // the current rates only move once the change is applied
+            let withdraw_tax_rate = pool_state.withdraw_tax_rate;
+            assert_eq!(withdraw_tax_rate, 20_000);
+        }
 
-            pool_state.set_status(5); // 0000101
+        #[test]
+        fn lp_tax_on_tax_mint_leg() {
+            let mut pool_state = PoolState::default();
+            pool_state.token_1_mint = Pubkey::new_unique();
+            pool_state.tax_mint = pool_state.token_1_mint;
+            assert_eq!(pool_state.lp_tax(1_000, 2_000, 0), (0, 0));
+            assert_eq!(pool_state.lp_tax(1_000, 2_000, 50_000), (0, 100));
+            pool_state.set_tax_status(true);
+            assert_eq!(pool_state.lp_tax(1_000, 2_000, 50_000), (0, 0));
+        }
+    }
+
+    mod tax_collector_test {
+        use super::*;
+
+        #[test]
+        fn delegated_recipient_collects_and_is_paid_by_cranks() {
+            let tax_authority = Pubkey::new_unique();
+            let mut pool_state = PoolState {
+                tax_authority,
+                ..Default::default()
+            };
+            assert!(pool_state.is_tax_collector(tax_authority));
+            assert!(!pool_state.is_tax_collector(Pubkey::default()));
+            assert_eq!(pool_state.tax_payee(), tax_authority);
+
+            let tax_recipient = Pubkey::new_unique();
+            pool_state.tax_recipient = tax_recipient;
+            assert!(pool_state.is_tax_collector(tax_recipient));
+            assert!(pool_state.is_tax_collector(tax_authority));
+            assert_eq!(pool_state.tax_payee(), tax_recipient);
+        }
+    }
+
+    mod tax_as_quote_test {
+        use super::*;
+
+        fn taxed_pool() -> PoolState {
+            PoolState {
+                tax_amount_0: 100_000,
+                tax_amount_1: 50_000,
+                ..Default::default()
+            }
+        }
+
+        fn amm_config() -> AmmConfig {
+            AmmConfig {
+                trade_fee_rate: 10_000,
+                protocol_fee_rate: 120_000,
+                fund_fee_rate: 40_000,
+                ..Default::default()
+            }
+        }
+
+        fn one_percent_transfer_fee(amount: u64) -> Result<u64> {
+            Ok(amount / 100)
+        }
+
+        #[test]
+        fn swaps_all_the_tax_and_accrues_its_fees() {
+            let mut pool_state = taxed_pool();
+            let (result, amount_out) = pool_state
+                .swap_tax_as_quote(
+                    0,
+                    TradeDirection::ZeroForOne,
+                    10_000_000,
+                    10_000_000,
+                    &amm_config(),
+                    None,
+                    0,
+                    one_percent_transfer_fee,
+                )
+                .unwrap();
+            assert_eq!(result.source_amount_swapped, 100_000);
+            assert_eq!(result.trade_fee, 1_000);
+            // 99_000 of the tax swapped after the trade fee
+            assert_eq!(amount_out, 98_029);
+
+            let (tax_amount_0, tax_amount_1) = (pool_state.tax_amount_0, pool_state.tax_amount_1);
+            assert_eq!((tax_amount_0, tax_amount_1), (0, 50_000));
+            let (protocol_fees_token_0, fund_fees_token_0, protocol_fees_token_1) = (
+                pool_state.protocol_fees_token_0,
+                pool_state.fund_fees_token_0,
+                pool_state.protocol_fees_token_1,
+            );
             assert_eq!(
-                pool_state.get_status_by_bit(PoolStatusBitIndex::Swap),
-                false
+                (
+                    protocol_fees_token_0,
+                    fund_fees_token_0,
+                    protocol_fees_token_1
+                ),
+                (120, 40, 0)
+            );
+        }
+
+        #[test]
+        fn minimum_amount_out_is_checked_after_the_transfer_fee() {
+            let swap = |pool_state: &mut PoolState, minimum_amount_out: u64| {
+                pool_state.swap_tax_as_quote(
+                    0,
+                    TradeDirection::ZeroForOne,
+                    10_000_000,
+                    10_000_000,
+                    &amm_config(),
+                    None,
+                    minimum_amount_out,
+                    one_percent_transfer_fee,
+                )
+            };
+            let mut pool_state = taxed_pool();
+            // 98_029 out, 97_049 received
+            assert!(swap(&mut pool_state, 97_050).is_err());
+            let (tax_amount_0, protocol_fees_token_0) =
+                (pool_state.tax_amount_0, pool_state.protocol_fees_token_0);
+            assert_eq!((tax_amount_0, protocol_fees_token_0), (100_000, 0));
+
+            assert_eq!(swap(&mut pool_state, 97_049).unwrap().1, 98_029);
+        }
+
+        #[test]
+        fn swaps_the_token_1_tax_the_other_way() {
+            let mut pool_state = taxed_pool();
+            let (result, _) = pool_state
+                .swap_tax_as_quote(
+                    0,
+                    TradeDirection::OneForZero,
+                    10_000_000,
+                    10_000_000,
+                    &amm_config(),
+                    None,
+                    0,
+                    one_percent_transfer_fee,
+                )
+                .unwrap();
+            assert_eq!(result.source_amount_swapped, 50_000);
+            let (tax_amount_0, tax_amount_1, protocol_fees_token_1) = (
+                pool_state.tax_amount_0,
+                pool_state.tax_amount_1,
+                pool_state.protocol_fees_token_1,
             );
             assert_eq!(
-                pool_state.get_status_by_bit(PoolStatusBitIndex::Deposit),
-                false
+                (tax_amount_0, tax_amount_1, protocol_fees_token_1),
+                (100_000, 0, 60)
             );
+
+            // nothing left to swap
+            assert!(pool_state
+                .swap_tax_as_quote(
+                    0,
+                    TradeDirection::OneForZero,
+                    10_000_000,
+                    10_000_000,
+                    &amm_config(),
+                    None,
+                    0,
+                    one_percent_transfer_fee,
+                )
+                .is_err());
+        }
+    }
+
+    mod take_tax_test {
+        use super::*;
+
+        fn taxed_pool() -> PoolState {
+            PoolState {
+                tax_amount_0: 1_000,
+                tax_amount_1: 2_000,
+                ..Default::default()
+            }
+        }
+
+        fn tax_amounts(pool_state: &PoolState) -> (u64, u64) {
+            (pool_state.tax_amount_0, pool_state.tax_amount_1)
+        }
+
+        #[test]
+        fn partial_collection_leaves_the_rest() {
+            let mut pool_state = taxed_pool();
+            assert_eq!(pool_state.take_tax(400, 500).unwrap(), (400, 500));
+            assert_eq!(tax_amounts(&pool_state), (600, 1_500));
+            assert_eq!(pool_state.take_tax(600, 1_500).unwrap(), (600, 1_500));
+            assert_eq!(tax_amounts(&pool_state), (0, 0));
+        }
+
+        #[test]
+        fn requests_above_the_accrued_tax_take_it_all() {
+            let mut pool_state = taxed_pool();
             assert_eq!(
-                pool_state.get_status_by_bit(PoolStatusBitIndex::Withdraw),
-                true
+                pool_state.take_tax(u64::MAX, 5_000).unwrap(),
+                (1_000, 2_000)
             );
+            assert_eq!(tax_amounts(&pool_state), (0, 0));
+            assert!(pool_state.take_tax(u64::MAX, u64::MAX).is_err());
+        }
+
+        #[test]
+        fn zero_request_leaves_a_side_untouched() {
+            let mut pool_state = taxed_pool();
+            assert_eq!(pool_state.take_tax(0, 500).unwrap(), (0, 500));
+            assert_eq!(tax_amounts(&pool_state), (1_000, 1_500));
+            assert_eq!(pool_state.take_tax(300, 0).unwrap(), (300, 0));
+            assert_eq!(tax_amounts(&pool_state), (700, 1_500));
+            assert!(pool_state.take_tax(0, 0).is_err());
+            assert_eq!(tax_amounts(&pool_state), (700, 1_500));
+        }
+    }
+
+    mod tax_burn_test {
+        use super::*;
+
+        fn burning_pool(tax_mint_is_token_0: bool) -> PoolState {
+            let token_0_mint = Pubkey::new_unique();
+            let token_1_mint = Pubkey::new_unique();
+            PoolState {
+                token_0_mint,
+                token_1_mint,
+                tax_mint: if tax_mint_is_token_0 {
+                    token_0_mint
+                } else {
+                    token_1_mint
+                },
+                tax_burn: true,
+                tax_amount_0: 1_000,
+                tax_amount_1: 2_000,
+                ..Default::default()
+            }
+        }
+
+        #[test]
+        fn burns_all_the_tax_of_the_tax_mint() {
+            let mut pool_state = burning_pool(true);
+            assert_eq!(pool_state.take_burn_tax().unwrap(), (1_000, 0));
+            let (tax_amount_0, tax_amount_1) = (pool_state.tax_amount_0, pool_state.tax_amount_1);
+            assert_eq!((tax_amount_0, tax_amount_1), (0, 2_000));
+            assert!(pool_state.take_burn_tax().is_err());
+
+            let mut pool_state = burning_pool(false);
+            assert_eq!(pool_state.take_burn_tax().unwrap(), (0, 2_000));
+            let (tax_amount_0, tax_amount_1) = (pool_state.tax_amount_0, pool_state.tax_amount_1);
+            assert_eq!((tax_amount_0, tax_amount_1), (1_000, 0));
+        }
+
+        #[test]
+        fn burns_only_in_burn_mode() {
+            let mut pool_state = PoolState {
+                tax_burn: false,
+                ..burning_pool(true)
+            };
+            assert!(pool_state.take_burn_tax().is_err());
+            assert_eq!(pool_state.split_tax_burn(1_000, 2_000), (0, 1_000, 2_000));
+
+            pool_state.tax_burn = true;
+            pool_state.tax_disabled = true;
+            assert!(pool_state.take_burn_tax().is_err());
+            let tax_amount_0 = pool_state.tax_amount_0;
+            assert_eq!(tax_amount_0, 1_000);
+        }
+
+        #[test]
+        fn collection_burns_the_tax_mint_and_pays_the_other_side() {
+            let pool_state = burning_pool(true);
+            assert_eq!(pool_state.split_tax_burn(1_000, 2_000), (1_000, 0, 2_000));
+            let pool_state = burning_pool(false);
+            assert_eq!(pool_state.split_tax_burn(1_000, 2_000), (2_000, 1_000, 0));
+        }
+    }
+
+    mod fee_bucket_test {
+        use super::*;
+
+        #[test]
+        fn bucket_fees_accrue_and_are_taken_per_bucket() {
+            let mut pool_state = PoolState {
+                protocol_fees_token_0: 10,
+                fund_fees_token_1: 20,
+                ..Default::default()
+            };
+            pool_state.add_bucket_fees(true, &[1, 2, 0, 0, 0, 0]);
+            pool_state.add_bucket_fees(false, &[0, 5, 0, 0, 0, 0]);
+            assert_eq!(pool_state.vault_amount_without_fee(100, 100), (87, 75));
 
-            pool_state.set_status(7); // 0000111
             assert_eq!(
-                pool_state.get_status_by_bit(PoolStatusBitIndex::Swap),
-                false
+                pool_state.take_bucket_fees(PROTOCOL_FEE_BUCKET, 4, 4),
+                (4, 0)
             );
             assert_eq!(
-                pool_state.get_status_by_bit(PoolStatusBitIndex::Deposit),
-                false
+                pool_state.take_bucket_fees(FUND_FEE_BUCKET, 0, 100),
+                (0, 20)
+            );
+            assert_eq!(pool_state.take_bucket_fees(3, 100, 1), (2, 1));
+            assert_eq!(pool_state.take_bucket_fees(3, 100, 100), (0, 4));
+
+            let (protocol_fees_token_0, bucket_fees_token_0) = (
+                pool_state.protocol_fees_token_0,
+                pool_state.bucket_fees_token_0,
+            );
+            assert_eq!(protocol_fees_token_0, 6);
+            assert_eq!(bucket_fees_token_0, [1, 0, 0, 0, 0, 0]);
+        }
+
+        #[test]
+        fn creator_fee_is_capped_and_kept_out_of_reserves() {
+            let mut pool_state = PoolState {
+                creator_fee_rate: 200_000,
+                ..Default::default()
+            };
+            assert_eq!(pool_state.creator_fee(300_000, 1_000), 200);
+            // a lowered cap applies to pools set above it
+            assert_eq!(pool_state.creator_fee(100_000, 1_000), 100);
+
+            pool_state.add_creator_fee(false, 100);
+            assert_eq!(
+                pool_state.vault_amount_without_fee(1_000, 1_000),
+                (1_000, 900)
             );
             assert_eq!(
-                pool_state.get_status_by_bit(PoolStatusBitIndex::Withdraw),
-                false
+                pool_state.take_bucket_fees(CREATOR_FEE_BUCKET, 50, 150),
+                (0, 100)
             );
+            let creator_fees_token_1 = pool_state.creator_fees_token_1;
+            assert_eq!(creator_fees_token_1, 0);
+        }
 
-            pool_state.set_status(3); // 0000011
-            assert_eq!(pool_state.get_status_by_bit(PoolStatusBitIndex::Swap), true);
+        #[test]
+        fn sweep_pays_only_the_bucket_owner() {
+            let (protocol_owner, fund_owner, pool_creator, bucket_owner) = (
+                Pubkey::new_unique(),
+                Pubkey::new_unique(),
+                Pubkey::new_unique(),
+                Pubkey::new_unique(),
+            );
+            let amm_config = AmmConfig {
+                protocol_owner,
+                fund_owner,
+                ..Default::default()
+            };
+            let mut fee_schedule = FeeSchedule::default();
+            fee_schedule.set(&[crate::states::FeeBucket {
+                owner: bucket_owner,
+                fee_rate: 100_000,
+            }]);
+            let pool_state = PoolState {
+                pool_creator,
+                ..Default::default()
+            };
+            let mint = Pubkey::new_unique();
+            let recipient = |owner: &Pubkey| {
+                get_associated_token_address_with_program_id(owner, &mint, &anchor_spl::token::ID)
+            };
+            let fee_recipient = |bucket: u8, fee_schedule: Option<&FeeSchedule>| {
+                pool_state.fee_recipient(
+                    &amm_config,
+                    fee_schedule,
+                    bucket,
+                    &mint,
+                    &anchor_spl::token::ID,
+                )
+            };
+
+            assert_eq!(
+                fee_recipient(PROTOCOL_FEE_BUCKET, None),
+                Some(recipient(&protocol_owner))
+            );
+            assert_eq!(
+                fee_recipient(FUND_FEE_BUCKET, None),
+                Some(recipient(&fund_owner))
+            );
+            assert_eq!(
+                fee_recipient(CREATOR_FEE_BUCKET, None),
+                Some(recipient(&pool_creator))
+            );
+            assert_eq!(
+                fee_recipient(2, Some(&fee_schedule)),
+                Some(recipient(&bucket_owner))
+            );
+            // any other destination is rejected
+            assert_ne!(
+                fee_recipient(PROTOCOL_FEE_BUCKET, None),
+                Some(recipient(&fund_owner))
+            );
+            assert_ne!(
+                fee_recipient(2, Some(&fee_schedule)),
+                Some(recipient(&protocol_owner))
+            );
+            // the owner's account of the other token program does not match either
+            assert_ne!(
+                fee_recipient(PROTOCOL_FEE_BUCKET, None),
+                Some(get_associated_token_address_with_program_id(
+                    &protocol_owner,
+                    &mint,
+                    &anchor_spl::token_2022::ID,
+                ))
+            );
+            // a bucket the fee schedule does not have has no recipient
+            assert_eq!(fee_recipient(2, None), None);
+            assert_eq!(fee_recipient(3, Some(&fee_schedule)), None);
+        }
+    }
+
+    mod pair_tax_test {
+        use super::*;
+
+        fn taxed_pair() -> PoolState {
+            let (token_0_mint, token_1_mint) = (Pubkey::new_unique(), Pubkey::new_unique());
+            let mut pool_state = PoolState {
+                token_0_mint,
+                token_1_mint,
+                ..Default::default()
+            };
+            pool_state.set_tax(token_0_mint, 10_000, 20_000, 30_000, 40_000);
+            pool_state
+        }
+
+        #[test]
+        fn rates_follow_the_swapped_tokens() {
+            let mut pool_state = taxed_pair();
+            let (token_0_mint, token_1_mint) = (pool_state.token_0_mint, pool_state.token_1_mint);
+            assert_eq!(pool_state.swap_tax_rates(0, token_0_mint), (10_000, 40_000));
+            assert_eq!(pool_state.swap_tax_rates(0, token_1_mint), (30_000, 20_000));
+            pool_state.set_tax_status(true);
+            assert_eq!(pool_state.swap_tax_rates(0, token_1_mint), (0, 0));
+        }
+
+        #[test]
+        fn exempt_payer_pays_no_tax() {
+            let pool_state = taxed_pair();
+            let (token_0_mint, token_1_mint) = (pool_state.token_0_mint, pool_state.token_1_mint);
+            let tax_exemption = TaxExemption {
+                wallet: Pubkey::new_unique(),
+                ..Default::default()
+            };
+            assert_eq!(
+                pool_state.payer_tax_rates(0, token_0_mint, Some(&tax_exemption)),
+                (0, 0)
+            );
+            assert_eq!(
+                pool_state.payer_tax_rates(0, token_1_mint, Some(&tax_exemption)),
+                (0, 0)
+            );
+            // other payers are taxed on both sides
+            assert_eq!(
+                pool_state.payer_tax_rates(0, token_0_mint, None),
+                (10_000, 40_000)
+            );
             assert_eq!(
-                pool_state.get_status_by_bit(PoolStatusBitIndex::Deposit),
-                false
+                pool_state.payer_tax_rates(0, token_1_mint, None),
+                (30_000, 20_000)
             );
+        }
+
+        #[test]
+        fn both_sides_accrue_in_one_swap() {
+            let mut pool_state = taxed_pair();
+            let (token_0_mint, token_1_mint) = (pool_state.token_0_mint, pool_state.token_1_mint);
+            pool_state.accrue_tax(token_1_mint, 300);
+            pool_state.accrue_tax(token_0_mint, 200);
+            let (tax_amount_0, tax_amount_1) = (pool_state.tax_amount_0, pool_state.tax_amount_1);
+            assert_eq!((tax_amount_0, tax_amount_1), (200, 300));
+            assert_eq!(
+                pool_state.vault_amount_without_fee(10_000, 10_000),
+                (9_800, 9_700)
+            );
+        }
+    }
+
+    mod migration_test {
+        use super::*;
+
+        /// Size of the pools created before the pool state outgrew its padding
+        const POOL_STATE_V1_LEN: usize = 702;
+
+        /// A pool created with the old layout after `migrate_pool`, the
+        /// fields past the old padding are zero
+        fn migrated_pool() -> PoolState {
+            PoolState {
+                tax_mint: Pubkey::new_unique(),
+                tax_authority: Pubkey::new_unique(),
+                in_tax_rate: 10_000,
+                out_tax_rate: 20_000,
+                ..Default::default()
+            }
+        }
+
+        #[test]
+        fn added_fields_start_at_the_old_padding() {
+            assert_eq!(PoolState::LEN, 8 + std::mem::size_of::<PoolState>());
+            // the old padding was the last three u64 of the account
             assert_eq!(
-                pool_state.get_status_by_bit(PoolStatusBitIndex::Withdraw),
-                false
+                8 + std::mem::offset_of!(PoolState, pending_tax_mint),
+                POOL_STATE_V1_LEN - 8 * 3
             );
         }
+
+        #[test]
+        fn migrated_pool_has_no_pending_tax_change() {
+            let pool_state = migrated_pool();
+            assert!(!pool_state.has_pending_tax_change());
+            assert!(!pool_state.has_pending_lp_tax_change());
+            assert!(!pool_state.has_pending_trade_limits());
+            assert_eq!(pool_state.tax_rates(0), (10_000, 20_000));
+        }
+
+        #[test]
+        fn migrated_pool_pays_tax_to_the_tax_authority() {
+            let pool_state = migrated_pool();
+            let tax_authority = pool_state.tax_authority;
+            assert_eq!(pool_state.tax_payee(), tax_authority);
+            assert!(!pool_state.is_tax_collector(Pubkey::default()));
+        }
+
+        #[test]
+        fn migrated_pool_taxes_only_the_tax_mint() {
+            let pool_state = migrated_pool();
+            let tax_mint = pool_state.tax_mint;
+            assert_eq!(pool_state.swap_tax_rates(0, tax_mint), (10_000, 0));
+            assert_eq!(
+                pool_state.swap_tax_rates(0, Pubkey::new_unique()),
+                (0, 20_000)
+            );
+        }
+
+        #[test]
+        fn migrated_pool_owes_no_bucket_fees() {
+            let mut pool_state = PoolState {
+                protocol_fees_token_0: 10,
+                ..migrated_pool()
+            };
+            assert_eq!(pool_state.vault_amount_without_fee(100, 100), (90, 100));
+            assert_eq!(pool_state.take_bucket_fees(2, u64::MAX, u64::MAX), (0, 0));
+        }
+
+        #[test]
+        fn migrated_pool_takes_no_creator_fee() {
+            let pool_state = migrated_pool();
+            assert_eq!(pool_state.creator_fee(300_000, 1_000), 0);
+            assert_eq!(pool_state.vault_amount_without_fee(100, 100), (100, 100));
+        }
     }
 }
@@ -0,0 +1,105 @@
+use crate::curve::clmm::{mul_div_floor, Q64};
+use crate::error::ErrorCode;
+use crate::utils::U256;
+use anchor_lang::prelude::*;
+
+pub const POSITION_SEED: &str = "position";
+
+/// Ranged liquidity owned by a user in a concentrated liquidity pool
+#[account]
+#[derive(Default, Debug)]
+pub struct PositionState {
+    /// Bump to identify PDA
+    pub bump: u8,
+    /// The pool the position belongs to
+    pub pool_id: Pubkey,
+    /// The owner of the position
+    pub owner: Pubkey,
+    /// The lower bound tick of the position
+    pub tick_lower_index: i32,
+    /// The upper bound tick of the position
+    pub tick_upper_index: i32,
+    /// The amount of liquidity owned by this position
+    pub liquidity: u128,
+    /// The token_0 fee growth of the aggregate position as of the last action on the individual position
+    pub fee_growth_inside_0_last_x64: u128,
+    /// The token_1 fee growth of the aggregate position as of the last action on the individual position
+    pub fee_growth_inside_1_last_x64: u128,
+    /// The fees owed to the position owner in token_0
+    pub token_fees_owed_0: u64,
+    /// The fees owed to the position owner in token_1
+    pub token_fees_owed_1: u64,
+
+    /// padding
+    pub padding: [u64; 8],
+}
+
+impl PositionState {
+    pub const LEN: usize = 8 + 1 + 32 * 2 + 4 * 2 + 16 * 3 + 8 * 2 + 8 * 8;
+
+    /// Credits accumulated fees to the position and applies the liquidity delta
+    pub fn update(
+        &mut self,
+        liquidity_delta: i128,
+        fee_growth_inside_0_x64: u128,
+        fee_growth_inside_1_x64: u128,
+    ) -> Result<()> {
+        let fees_owed_0 = Self::fees_owed(
+            fee_growth_inside_0_x64.wrapping_sub(self.fee_growth_inside_0_last_x64),
+            self.liquidity,
+        )?;
+        let fees_owed_1 = Self::fees_owed(
+            fee_growth_inside_1_x64.wrapping_sub(self.fee_growth_inside_1_last_x64),
+            self.liquidity,
+        )?;
+
+        self.liquidity = crate::curve::clmm::add_delta(self.liquidity, liquidity_delta)?;
+        self.fee_growth_inside_0_last_x64 = fee_growth_inside_0_x64;
+        self.fee_growth_inside_1_last_x64 = fee_growth_inside_1_x64;
+        self.token_fees_owed_0 = self
+            .token_fees_owed_0
+            .checked_add(fees_owed_0)
+            .ok_or(ErrorCode::MaxTokenOverflow)?;
+        self.token_fees_owed_1 = self
+            .token_fees_owed_1
+            .checked_add(fees_owed_1)
+            .ok_or(ErrorCode::MaxTokenOverflow)?;
+        Ok(())
+    }
+
+    fn fees_owed(fee_growth_delta_x64: u128, liquidity: u128) -> Result<u64> {
+        mul_div_floor(
+            U256::from(fee_growth_delta_x64),
+            U256::from(liquidity),
+            U256::from(Q64),
+        )
+        .and_then(|fees| u64::try_from(fees).ok())
+        .ok_or_else(|| error!(ErrorCode::MaxTokenOverflow))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn position_accrues_fees() {
+        let mut position = PositionState::default();
+        position.update(1_000, 0, 0).unwrap();
+        assert_eq!(position.liquidity, 1_000);
+
+        // half a token per unit of liquidity in token_0, a quarter in token_1
+        position.update(-400, Q64 / 2, Q64 / 4).unwrap();
+        assert_eq!(position.liquidity, 600);
+        assert_eq!(position.token_fees_owed_0, 500);
+        assert_eq!(position.token_fees_owed_1, 250);
+
+        // fee growth is tracked modulo 2^128
+        position.fee_growth_inside_0_last_x64 = u128::MAX - Q64 + 1;
+        position.update(0, 0, Q64 / 4).unwrap();
+        assert_eq!(position.token_fees_owed_0, 1_100);
+        assert_eq!(position.token_fees_owed_1, 250);
+
+        assert!(position.update(-601, Q64, Q64).is_err());
+    }
+}
@@ -0,0 +1,26 @@
+use anchor_lang::prelude::*;
+
+pub const STAKE_RECORD_SEED: &str = "stake_record";
+pub const STAKE_VAULT_SEED: &str = "stake_vault";
+
+/// Tokens of a fee discount mint staked by a wallet, counted toward the fee
+/// discount tier of its swaps
+#[account]
+#[derive(Default, Debug)]
+pub struct StakeRecord {
+    /// Bump to identify PDA
+    pub bump: u8,
+    /// The staking wallet
+    pub owner: Pubkey,
+    /// The staked mint
+    pub mint: Pubkey,
+    /// Amount staked, net of transfer fees
+    pub amount: u64,
+
+    /// padding
+    pub padding: [u64; 4],
+}
+
+impl StakeRecord {
+    pub const LEN: usize = 8 + 1 + 32 * 2 + 8 + 8 * 4;
+}
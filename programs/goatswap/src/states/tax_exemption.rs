@@ -0,0 +1,23 @@
+use anchor_lang::prelude::*;
+
+pub const TAX_EXEMPTION_SEED: &str = "tax_exemption";
+
+/// Marks a wallet as exempt from the tax of a pool, the account exists for as
+/// long as the exemption does
+#[account]
+#[derive(Default, Debug)]
+pub struct TaxExemption {
+    /// Bump to identify PDA
+    pub bump: u8,
+    /// The pool the exemption applies to
+    pub pool_id: Pubkey,
+    /// The exempt wallet, matched against the payer of a swap
+    pub wallet: Pubkey,
+
+    /// padding
+    pub padding: [u64; 4],
+}
+
+impl TaxExemption {
+    pub const LEN: usize = 8 + 1 + 32 * 2 + 8 * 4;
+}
@@ -0,0 +1,116 @@
+use crate::error::ErrorCode;
+use anchor_lang::prelude::*;
+
+pub const TAX_RECIPIENTS_SEED: &str = "tax_recipients";
+/// Maximum number of wallets the tax of a pool can be split across
+pub const MAX_TAX_RECIPIENTS: usize = 5;
+/// Shares are in basis points
+pub const TAX_SHARE_DENOMINATOR: u64 = 10_000;
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct TaxRecipient {
+    /// Owner of the token accounts receiving the share
+    pub wallet: Pubkey,
+    /// Share of the collected tax, over TAX_SHARE_DENOMINATOR
+    pub share_bps: u16,
+}
+
+/// The wallets `collect_tax` splits the tax of a pool across
+#[account]
+#[derive(Default, Debug)]
+pub struct TaxRecipients {
+    /// Bump to identify PDA
+    pub bump: u8,
+    pub pool_id: Pubkey,
+    /// Number of recipients in use, the tax goes to a single recipient when 0
+    pub count: u8,
+    pub recipients: [TaxRecipient; MAX_TAX_RECIPIENTS],
+
+    /// padding
+    pub padding: [u64; 4],
+}
+
+impl TaxRecipients {
+    pub const LEN: usize = 8 + 1 + 32 + 1 + (32 + 2) * MAX_TAX_RECIPIENTS + 8 * 4;
+
+    /// Replace the recipients, the shares must add up to TAX_SHARE_DENOMINATOR.
+    /// An empty list turns splitting off
+    pub fn set(&mut self, recipients: &[TaxRecipient]) -> Result<()> {
+        require!(
+            recipients.len() <= MAX_TAX_RECIPIENTS,
+            ErrorCode::InvalidTaxRecipients
+        );
+        if !recipients.is_empty() {
+            require!(
+                recipients.iter().all(|recipient| recipient.share_bps > 0)
+                    && recipients
+                        .iter()
+                        .map(|recipient| u64::from(recipient.share_bps))
+                        .sum::<u64>()
+                        == TAX_SHARE_DENOMINATOR,
+                ErrorCode::InvalidTaxRecipients
+            );
+        }
+        self.recipients = [TaxRecipient::default(); MAX_TAX_RECIPIENTS];
+        self.recipients[..recipients.len()].copy_from_slice(recipients);
+        self.count = recipients.len() as u8;
+        Ok(())
+    }
+
+    pub fn recipients(&self) -> &[TaxRecipient] {
+        &self.recipients[..usize::from(self.count)]
+    }
+
+    /// Each recipient's share of `amount`, rounded down. The rounding dust
+    /// goes to the first recipient and is returned alongside
+    pub fn split(&self, amount: u64) -> ([u64; MAX_TAX_RECIPIENTS], u64) {
+        let mut shares = [0u64; MAX_TAX_RECIPIENTS];
+        for (share, recipient) in shares.iter_mut().zip(self.recipients()) {
+            *share = (u128::from(amount) * u128::from(recipient.share_bps)
+                / u128::from(TAX_SHARE_DENOMINATOR)) as u64;
+        }
+        let dust = amount - shares.iter().sum::<u64>();
+        shares[0] += dust;
+        (shares, dust)
+    }
+}
+
+#[cfg(test)]
+pub mod tax_recipients_test {
+    use super::*;
+
+    fn recipient(share_bps: u16) -> TaxRecipient {
+        TaxRecipient {
+            wallet: Pubkey::new_unique(),
+            share_bps,
+        }
+    }
+
+    #[test]
+    fn split_gives_dust_to_first_recipient() {
+        let mut tax_recipients = TaxRecipients::default();
+        tax_recipients
+            .set(&[recipient(5_000), recipient(3_000), recipient(2_000)])
+            .unwrap();
+        assert_eq!(tax_recipients.split(1_000), ([500, 300, 200, 0, 0], 0));
+        // 3.5, 2.1 and 1.4 round down to 3, 2 and 1
+        let (shares, dust) = tax_recipients.split(7);
+        assert_eq!(dust, 1);
+        assert_eq!(shares, [4, 2, 1, 0, 0]);
+    }
+
+    #[test]
+    fn shares_must_add_up() {
+        let mut tax_recipients = TaxRecipients::default();
+        assert!(tax_recipients
+            .set(&[recipient(5_000), recipient(4_000)])
+            .is_err());
+        assert!(tax_recipients
+            .set(&[recipient(10_000), recipient(0)])
+            .is_err());
+        assert!(tax_recipients.set(&[recipient(2_000); 6]).is_err());
+        assert!(tax_recipients.set(&[recipient(2_000); 5]).is_ok());
+        assert!(tax_recipients.set(&[]).is_ok());
+        assert!(tax_recipients.recipients().is_empty());
+    }
+}
@@ -0,0 +1,382 @@
+use crate::curve::clmm::*;
+use crate::error::ErrorCode;
+use anchor_lang::prelude::*;
+
+pub const TICK_ARRAY_SEED: &str = "tick_array";
+/// Number of ticks stored in a tick array
+pub const TICK_ARRAY_SIZE: i32 = 60;
+pub const TICK_ARRAY_SIZE_USIZE: usize = 60;
+
+/// Liquidity and fee bookkeeping of an initialized tick
+#[zero_copy(unsafe)]
+#[repr(C, packed)]
+#[derive(Default, Debug)]
+pub struct TickState {
+    pub tick: i32,
+    /// Amount of net liquidity added (subtracted) when tick is crossed from left to right (right to left)
+    pub liquidity_net: i128,
+    /// The total position liquidity that references this tick
+    pub liquidity_gross: u128,
+    /// Fee growth per unit of liquidity on the _other_ side of this tick (relative to the current tick)
+    /// only has relative meaning, not absolute — the value depends on when the tick is initialized
+    pub fee_growth_outside_0_x64: u128,
+    pub fee_growth_outside_1_x64: u128,
+}
+
+impl TickState {
+    pub fn is_initialized(&self) -> bool {
+        self.liquidity_gross != 0
+    }
+
+    /// Updates a tick and returns true if the tick was flipped from initialized to
+    /// uninitialized, or vice versa
+    pub fn update(
+        &mut self,
+        tick_current: i32,
+        liquidity_delta: i128,
+        fee_growth_global_0_x64: u128,
+        fee_growth_global_1_x64: u128,
+        upper: bool,
+        max_liquidity: u128,
+    ) -> Result<bool> {
+        let liquidity_gross_before = self.liquidity_gross;
+        let liquidity_gross_after = add_delta(liquidity_gross_before, liquidity_delta)?;
+        require_gte!(
+            max_liquidity,
+            liquidity_gross_after,
+            ErrorCode::LiquidityOverflow
+        );
+
+        let flipped = (liquidity_gross_after == 0) != (liquidity_gross_before == 0);
+
+        if liquidity_gross_before == 0 {
+            // by convention, we assume that all growth before a tick was initialized happened _below_ the tick
+            if self.tick <= tick_current {
+                self.fee_growth_outside_0_x64 = fee_growth_global_0_x64;
+                self.fee_growth_outside_1_x64 = fee_growth_global_1_x64;
+            } else {
+                self.fee_growth_outside_0_x64 = 0;
+                self.fee_growth_outside_1_x64 = 0;
+            }
+        }
+
+        self.liquidity_gross = liquidity_gross_after;
+
+        // when the lower (upper) tick is crossed left to right (right to left),
+        // liquidity must be added (removed)
+        self.liquidity_net = if upper {
+            self.liquidity_net.checked_sub(liquidity_delta)
+        } else {
+            self.liquidity_net.checked_add(liquidity_delta)
+        }
+        .ok_or(ErrorCode::LiquidityOverflow)?;
+
+        if liquidity_gross_after == 0 {
+            self.clear();
+        }
+        Ok(flipped)
+    }
+
+    /// Transitions to the tick as needed by price movement, returns the liquidity_net
+    pub fn cross(&mut self, fee_growth_global_0_x64: u128, fee_growth_global_1_x64: u128) -> i128 {
+        self.fee_growth_outside_0_x64 =
+            fee_growth_global_0_x64.wrapping_sub(self.fee_growth_outside_0_x64);
+        self.fee_growth_outside_1_x64 =
+            fee_growth_global_1_x64.wrapping_sub(self.fee_growth_outside_1_x64);
+        self.liquidity_net
+    }
+
+    fn clear(&mut self) {
+        self.liquidity_net = 0;
+        self.liquidity_gross = 0;
+        self.fee_growth_outside_0_x64 = 0;
+        self.fee_growth_outside_1_x64 = 0;
+    }
+}
+
+/// Retrieves the all time fee growth data in token_0 and token_1, per unit of
+/// liquidity, inside a position's tick boundaries
+pub fn get_fee_growth_inside(
+    tick_lower: &TickState,
+    tick_upper: &TickState,
+    tick_current: i32,
+    fee_growth_global_0_x64: u128,
+    fee_growth_global_1_x64: u128,
+) -> (u128, u128) {
+    // calculate fee growth below
+    let (fee_growth_below_0_x64, fee_growth_below_1_x64) = if tick_current >= tick_lower.tick {
+        (
+            tick_lower.fee_growth_outside_0_x64,
+            tick_lower.fee_growth_outside_1_x64,
+        )
+    } else {
+        (
+            fee_growth_global_0_x64.wrapping_sub(tick_lower.fee_growth_outside_0_x64),
+            fee_growth_global_1_x64.wrapping_sub(tick_lower.fee_growth_outside_1_x64),
+        )
+    };
+
+    // calculate fee growth above
+    let (fee_growth_above_0_x64, fee_growth_above_1_x64) = if tick_current < tick_upper.tick {
+        (
+            tick_upper.fee_growth_outside_0_x64,
+            tick_upper.fee_growth_outside_1_x64,
+        )
+    } else {
+        (
+            fee_growth_global_0_x64.wrapping_sub(tick_upper.fee_growth_outside_0_x64),
+            fee_growth_global_1_x64.wrapping_sub(tick_upper.fee_growth_outside_1_x64),
+        )
+    };
+
+    (
+        fee_growth_global_0_x64
+            .wrapping_sub(fee_growth_below_0_x64)
+            .wrapping_sub(fee_growth_above_0_x64),
+        fee_growth_global_1_x64
+            .wrapping_sub(fee_growth_below_1_x64)
+            .wrapping_sub(fee_growth_above_1_x64),
+    )
+}
+
+/// A fixed size range of ticks of a concentrated liquidity pool
+#[account(zero_copy(unsafe))]
+#[repr(C, packed)]
+pub struct TickArrayState {
+    pub pool_id: Pubkey,
+    pub start_tick_index: i32,
+    pub ticks: [TickState; TICK_ARRAY_SIZE_USIZE],
+    pub initialized_tick_count: u8,
+
+    pub padding: [u8; 115],
+}
+
+impl Default for TickArrayState {
+    fn default() -> Self {
+        Self {
+            pool_id: Pubkey::default(),
+            start_tick_index: 0,
+            ticks: [TickState::default(); TICK_ARRAY_SIZE_USIZE],
+            initialized_tick_count: 0,
+            padding: [0; 115],
+        }
+    }
+}
+
+impl TickArrayState {
+    pub const LEN: usize = 8 + 32 + 4 + (4 + 16 * 4) * TICK_ARRAY_SIZE_USIZE + 1 + 115;
+
+    pub fn initialize(&mut self, pool_id: Pubkey, start_tick_index: i32, tick_spacing: u16) {
+        self.pool_id = pool_id;
+        self.start_tick_index = start_tick_index;
+        for (i, tick_state) in self.ticks.iter_mut().enumerate() {
+            tick_state.tick = start_tick_index + i as i32 * i32::from(tick_spacing);
+        }
+    }
+
+    /// Number of ticks covered by one tick array
+    pub fn tick_count(tick_spacing: u16) -> i32 {
+        TICK_ARRAY_SIZE * i32::from(tick_spacing)
+    }
+
+    /// Start index of the tick array containing the tick
+    pub fn get_array_start_index(tick_index: i32, tick_spacing: u16) -> i32 {
+        let ticks_in_array = Self::tick_count(tick_spacing);
+        tick_index.div_euclid(ticks_in_array) * ticks_in_array
+    }
+
+    pub fn check_is_valid_start_index(tick_index: i32, tick_spacing: u16) -> bool {
+        tick_index % Self::tick_count(tick_spacing) == 0
+            && tick_index >= Self::get_array_start_index(MIN_TICK, tick_spacing)
+            && tick_index <= Self::get_array_start_index(MAX_TICK, tick_spacing)
+    }
+
+    /// Start index of the tick array following this one in the swap direction
+    pub fn next_array_start_index(&self, tick_spacing: u16, zero_for_one: bool) -> i32 {
+        let ticks_in_array = Self::tick_count(tick_spacing);
+        if zero_for_one {
+            self.start_tick_index - ticks_in_array
+        } else {
+            self.start_tick_index + ticks_in_array
+        }
+    }
+
+    pub fn get_tick_state_mut(
+        &mut self,
+        tick_index: i32,
+        tick_spacing: u16,
+    ) -> Result<&mut TickState> {
+        let offset = self.tick_offset(tick_index, tick_spacing)?;
+        Ok(&mut self.ticks[offset])
+    }
+
+    fn tick_offset(&self, tick_index: i32, tick_spacing: u16) -> Result<usize> {
+        let start_tick_index = self.start_tick_index;
+        require!(
+            tick_index % i32::from(tick_spacing) == 0
+                && Self::get_array_start_index(tick_index, tick_spacing) == start_tick_index,
+            ErrorCode::InvalidTickArray
+        );
+        Ok(((tick_index - start_tick_index) / i32::from(tick_spacing)) as usize)
+    }
+
+    /// Update the initialized tick count after a tick flipped
+    pub fn update_initialized_tick_count(&mut self, add: bool) -> Result<()> {
+        self.initialized_tick_count = if add {
+            self.initialized_tick_count.checked_add(1)
+        } else {
+            self.initialized_tick_count.checked_sub(1)
+        }
+        .ok_or(ErrorCode::InvalidTickArray)?;
+        Ok(())
+    }
+
+    /// Find the next initialized tick in this array in the swap direction. When
+    /// moving left the current tick itself is included, when moving right the
+    /// search starts after it.
+    pub fn next_initialized_tick(
+        &mut self,
+        tick_current: i32,
+        tick_spacing: u16,
+        zero_for_one: bool,
+    ) -> Option<&mut TickState> {
+        if self.initialized_tick_count == 0 {
+            return None;
+        }
+        let start_tick_index = self.start_tick_index;
+        let offset = (tick_current - start_tick_index).div_euclid(i32::from(tick_spacing));
+        if zero_for_one {
+            if offset < 0 {
+                return None;
+            }
+            let last = (offset.min(TICK_ARRAY_SIZE - 1)) as usize;
+            self.ticks[..=last]
+                .iter_mut()
+                .rev()
+                .find(|tick_state| tick_state.is_initialized())
+        } else {
+            let first = (offset + 1).max(0);
+            if first >= TICK_ARRAY_SIZE {
+                return None;
+            }
+            self.ticks[first as usize..]
+                .iter_mut()
+                .find(|tick_state| tick_state.is_initialized())
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn array_start_index() {
+        assert_eq!(TickArrayState::get_array_start_index(0, 10), 0);
+        assert_eq!(TickArrayState::get_array_start_index(599, 10), 0);
+        assert_eq!(TickArrayState::get_array_start_index(600, 10), 600);
+        assert_eq!(TickArrayState::get_array_start_index(-1, 10), -600);
+        assert_eq!(TickArrayState::get_array_start_index(-600, 10), -600);
+        assert_eq!(TickArrayState::get_array_start_index(-601, 10), -1200);
+        assert!(TickArrayState::check_is_valid_start_index(-1200, 10));
+        assert!(!TickArrayState::check_is_valid_start_index(-1210, 10));
+        assert!(!TickArrayState::check_is_valid_start_index(
+            TickArrayState::get_array_start_index(MAX_TICK, 1) + 60,
+            1
+        ));
+    }
+
+    fn tick_array_with(ticks: &[i32], tick_spacing: u16) -> TickArrayState {
+        let mut tick_array = TickArrayState::default();
+        tick_array.initialize(Pubkey::default(), -600, tick_spacing);
+        for tick in ticks {
+            let tick_state = tick_array.get_tick_state_mut(*tick, tick_spacing).unwrap();
+            let flipped = tick_state.update(0, 100, 0, 0, false, u128::MAX).unwrap();
+            assert!(flipped);
+            tick_array.update_initialized_tick_count(true).unwrap();
+        }
+        tick_array
+    }
+
+    #[test]
+    fn next_initialized_tick_search() {
+        let mut tick_array = tick_array_with(&[-500, -300, -10], 10);
+        let next = |array: &mut TickArrayState, tick: i32, zero_for_one: bool| {
+            array
+                .next_initialized_tick(tick, 10, zero_for_one)
+                .map(|tick_state| tick_state.tick)
+        };
+        // moving left includes the current tick
+        assert_eq!(next(&mut tick_array, -300, true), Some(-300));
+        assert_eq!(next(&mut tick_array, -301, true), Some(-500));
+        assert_eq!(next(&mut tick_array, -501, true), None);
+        assert_eq!(next(&mut tick_array, 5000, true), Some(-10));
+        // moving right excludes it
+        assert_eq!(next(&mut tick_array, -300, false), Some(-10));
+        assert_eq!(next(&mut tick_array, -10, false), None);
+        assert_eq!(next(&mut tick_array, -5000, false), Some(-500));
+        assert!(tick_array.get_tick_state_mut(0, 10).is_err());
+        assert!(tick_array.get_tick_state_mut(-15, 10).is_err());
+    }
+
+    #[test]
+    fn tick_update_and_cross() {
+        let mut tick_state = TickState {
+            tick: 10,
+            ..Default::default()
+        };
+        // above the current tick, growth is assumed to have happened below
+        assert!(tick_state.update(0, 100, 7, 9, true, u128::MAX).unwrap());
+        assert_eq!({ tick_state.liquidity_net }, -100);
+        assert_eq!({ tick_state.fee_growth_outside_0_x64 }, 0);
+        assert!(!tick_state.update(0, 50, 7, 9, false, u128::MAX).unwrap());
+        assert_eq!({ tick_state.liquidity_net }, -50);
+        assert!(tick_state.update(0, 1, 7, 9, false, 150).is_err());
+
+        assert_eq!(tick_state.cross(20, 30), -50);
+        assert_eq!({ tick_state.fee_growth_outside_0_x64 }, 20);
+        assert_eq!({ tick_state.fee_growth_outside_1_x64 }, 30);
+
+        assert!(tick_state
+            .update(20, -150, 20, 30, false, u128::MAX)
+            .unwrap());
+        assert!(!tick_state.is_initialized());
+        assert_eq!({ tick_state.fee_growth_outside_0_x64 }, 0);
+    }
+
+    #[test]
+    fn fee_growth_inside_range() {
+        let lower = TickState {
+            tick: -10,
+            fee_growth_outside_0_x64: 100,
+            fee_growth_outside_1_x64: 10,
+            ..Default::default()
+        };
+        let upper = TickState {
+            tick: 10,
+            fee_growth_outside_0_x64: 50,
+            fee_growth_outside_1_x64: 5,
+            ..Default::default()
+        };
+        // in range: global - below - above
+        assert_eq!(
+            get_fee_growth_inside(&lower, &upper, 0, 1000, 100),
+            (850, 85)
+        );
+        // below range: lower outside is above the current tick
+        assert_eq!(
+            get_fee_growth_inside(&lower, &upper, -20, 1000, 100),
+            (50, 5)
+        );
+        // above range: upper outside is below the current tick
+        let upper = TickState {
+            fee_growth_outside_0_x64: 900,
+            fee_growth_outside_1_x64: 90,
+            ..upper
+        };
+        assert_eq!(
+            get_fee_growth_inside(&lower, &upper, 20, 1000, 100),
+            (800, 80)
+        );
+    }
+}
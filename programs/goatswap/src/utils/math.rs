@@ -1,6 +1,9 @@
-///! 128 and 256 bit numbers
-///! U128 is more efficient that u128
-///! https://github.com/solana-labs/solana/issues/19549
+//! 128 and 256 bit numbers
+//! U128 is more efficient that u128
+//! https://github.com/solana-labs/solana/issues/19549
+#![allow(clippy::assign_op_pattern)]
+#![allow(clippy::manual_div_ceil)]
+#![allow(clippy::reversed_empty_ranges)]
 use uint::construct_uint;
 construct_uint! {
     pub struct U128(2);
@@ -10,6 +13,10 @@ construct_uint! {
     pub struct U256(4);
 }
 
+construct_uint! {
+    pub struct U512(8);
+}
+
 pub trait CheckedCeilDiv: Sized {
     /// Perform ceiling division
     fn checked_ceil_div(&self, rhs: Self) -> Option<(Self, Self)>;
@@ -22,7 +29,7 @@ impl CheckedCeilDiv for u128 {
         // fail.
         if quotient == 0 {
             // return None;
-            if self.checked_mul(2 as u128)? >= rhs {
+            if self.checked_mul(2)? >= rhs {
                 return Some((1, 0));
             } else {
                 return Some((0, 0));
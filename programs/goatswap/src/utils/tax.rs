@@ -9,3 +9,32 @@ pub fn tax_amount(amount: u64, tax_rate: u64) -> Option<u128> {
         u128::from(FEE_RATE_DENOMINATOR_VALUE),
     )
 }
+
+/// Tax rate `elapsed` seconds into a schedule moving from `start_rate` to
+/// `target_rate` over `duration` seconds, in steps of `step` seconds or
+/// linearly when `step` is 0
+pub fn scheduled_tax_rate(
+    start_rate: u64,
+    target_rate: u64,
+    duration: u64,
+    step: u64,
+    elapsed: u64,
+) -> u64 {
+    if elapsed >= duration {
+        return target_rate;
+    }
+    let elapsed = if step > 0 {
+        elapsed - elapsed % step
+    } else {
+        elapsed
+    };
+    let elapsed = u128::from(elapsed);
+    let duration = u128::from(duration);
+    if start_rate > target_rate {
+        let delta = u128::from(start_rate - target_rate) * elapsed / duration;
+        start_rate - delta as u64
+    } else {
+        let delta = u128::from(target_rate - start_rate) * elapsed / duration;
+        start_rate + delta as u64
+    }
+}